@@ -0,0 +1,382 @@
+use crate::puzzle17::{load, Machine, VmError, STEP_BUDGET};
+use std::collections::{BTreeSet, VecDeque};
+use std::io::{stdin, stdout, BufRead, Write};
+
+const HELP: &str = "\
+commands:
+  [enter] | s [n]     single step, or n steps
+  c                   continue until a breakpoint, watchpoint or halt
+  o                   run until the next output
+  b <addr>, d <addr>  set or delete a breakpoint on a program address
+  w <reg>, u <reg>    watch or unwatch register a, b or c
+  set <reg> <value>   edit a register
+  r                   show registers
+  l                   list the program
+  t                   toggle printing every executed step
+  log [n]             show the last n executed steps
+  reset               restart with the initial registers
+  q                   quit";
+/// Executed steps kept for `log`, older ones are dropped.
+const LOG_LIMIT: usize = 10_000;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+enum Register {
+    A,
+    B,
+    C,
+}
+
+impl Register {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "a" | "A" => Some(Register::A),
+            "b" | "B" => Some(Register::B),
+            "c" | "C" => Some(Register::C),
+            _ => None,
+        }
+    }
+
+    fn get(self, machine: &Machine) -> i64 {
+        match self {
+            Register::A => machine.a,
+            Register::B => machine.b,
+            Register::C => machine.c,
+        }
+    }
+
+    fn set(self, machine: &mut Machine, value: i64) {
+        match self {
+            Register::A => machine.a = value,
+            Register::B => machine.b = value,
+            Register::C => machine.c = value,
+        }
+    }
+}
+
+/// Reason why a run stopped.
+enum Stop {
+    Steps,
+    Breakpoint(usize),
+    Watchpoint(Register, i64, i64),
+    Output(u8),
+    Halted,
+    Error(VmError),
+}
+
+struct Debugger {
+    initial: Machine,
+    machine: Machine,
+    output: Vec<u8>,
+    breakpoints: BTreeSet<usize>,
+    watchpoints: BTreeSet<Register>,
+    trace: bool,
+    log: VecDeque<String>,
+}
+
+impl Debugger {
+    fn new(machine: Machine) -> Self {
+        Self {
+            initial: machine.clone(),
+            machine,
+            output: Vec::new(),
+            breakpoints: BTreeSet::new(),
+            watchpoints: BTreeSet::new(),
+            trace: false,
+            log: VecDeque::new(),
+        }
+    }
+
+    fn halted(&self) -> bool {
+        self.machine.ip >= self.machine.instructions.len()
+    }
+
+    fn registers(&self) -> String {
+        format!(
+            "a={} b={} c={}",
+            self.machine.a, self.machine.b, self.machine.c
+        )
+    }
+
+    /// Executes one instruction and records it in the trace log.
+    fn step(&mut self) -> Option<Stop> {
+        let before = self.machine.clone();
+        let address = 2 * before.ip;
+        let instruction = before.instructions[before.ip];
+        let printed = self.output.len();
+        let result = self.machine.step(&mut self.output);
+
+        let mut entry = format!(
+            "{address:>3}: {:<10} {}",
+            instruction.to_string(),
+            self.registers()
+        );
+        if let Some(value) = self.output.get(printed) {
+            entry += &format!(" -> out {value}");
+        }
+        if self.trace {
+            println!("{entry}");
+        }
+        if self.log.len() == LOG_LIMIT {
+            self.log.pop_front();
+        }
+        self.log.push_back(entry);
+        if let Err(err) = result {
+            return Some(Stop::Error(err));
+        }
+
+        for &register in &self.watchpoints {
+            let (old, new) = (register.get(&before), register.get(&self.machine));
+            if old != new {
+                return Some(Stop::Watchpoint(register, old, new));
+            }
+        }
+        self.output[printed..]
+            .first()
+            .map(|&value| Stop::Output(value))
+    }
+
+    /// Runs until something stops execution, breakpoints are ignored for the first instruction.
+    fn run(&mut self, max_steps: Option<usize>, until_output: bool) -> Stop {
+        let mut executed = 0;
+        loop {
+            if self.halted() {
+                return Stop::Halted;
+            }
+            if executed > 0 && self.breakpoints.contains(&(2 * self.machine.ip)) {
+                return Stop::Breakpoint(2 * self.machine.ip);
+            }
+            if max_steps == Some(executed) {
+                return Stop::Steps;
+            }
+            if executed == STEP_BUDGET {
+                return Stop::Error(VmError::StepLimitExceeded(STEP_BUDGET));
+            }
+            executed += 1;
+            match self.step() {
+                Some(Stop::Output(_)) if !until_output => {}
+                Some(stop) => return stop,
+                None => {}
+            }
+        }
+    }
+
+    /// Registers, the next instruction and the output so far.
+    fn status(&self) -> String {
+        let next = match self.machine.instructions.get(self.machine.ip) {
+            Some(instruction) => format!("next {:>2}: {instruction}", 2 * self.machine.ip),
+            None => "halted".to_string(),
+        };
+        let output: Vec<String> = self.output.iter().map(u8::to_string).collect();
+        format!(
+            "{} | {next} | output: {}",
+            self.registers(),
+            output.join(",")
+        )
+    }
+
+    fn show(&self) {
+        println!("{}", self.status());
+    }
+
+    fn execute(&mut self, max_steps: Option<usize>, until_output: bool) {
+        match self.run(max_steps, until_output) {
+            Stop::Steps => {}
+            Stop::Breakpoint(address) => println!("breakpoint at {address}"),
+            Stop::Watchpoint(register, old, new) => {
+                println!("{register:?} changed from {old} to {new}")
+            }
+            Stop::Output(value) => println!("output {value}"),
+            Stop::Halted => println!("program halted"),
+            Stop::Error(err) => println!("error: {err}"),
+        }
+        self.show();
+    }
+
+    /// Handles one command line, returns false to quit.
+    fn command(&mut self, line: &str) -> Result<bool, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let number = |word: &str| {
+            word.parse::<i64>()
+                .map_err(|_| format!("`{word}` is not a number"))
+        };
+        // step counts and addresses, negative values would wrap around to huge ones
+        let count = |word: &str| {
+            word.parse::<usize>()
+                .map_err(|_| format!("`{word}` is not a non-negative number"))
+        };
+        let register =
+            |word: &str| Register::parse(word).ok_or_else(|| format!("`{word}` is not a register"));
+        match words.as_slice() {
+            [] | ["s"] => self.execute(Some(1), false),
+            ["s", steps] => self.execute(Some(count(steps)?), false),
+            ["c"] => self.execute(None, false),
+            ["o"] => self.execute(None, true),
+            ["b", address] => {
+                self.breakpoints.insert(count(address)?);
+            }
+            ["d", address] => {
+                self.breakpoints.remove(&count(address)?);
+            }
+            ["w", name] => {
+                self.watchpoints.insert(register(name)?);
+            }
+            ["u", name] => {
+                self.watchpoints.remove(&register(name)?);
+            }
+            ["set", name, value] => {
+                register(name)?.set(&mut self.machine, number(value)?);
+                self.show();
+            }
+            ["r"] => self.show(),
+            ["l"] => print!("{}", self.machine.disassemble()),
+            ["t"] => {
+                self.trace = !self.trace;
+                println!("trace {}", if self.trace { "on" } else { "off" });
+            }
+            ["log"] => self.log.iter().for_each(|entry| println!("{entry}")),
+            ["log", steps] => {
+                let start = self.log.len().saturating_sub(count(steps)?);
+                self.log
+                    .iter()
+                    .skip(start)
+                    .for_each(|entry| println!("{entry}"));
+            }
+            ["reset"] => {
+                self.machine = self.initial.clone();
+                self.output.clear();
+                self.log.clear();
+                self.show();
+            }
+            ["h"] | ["help"] => println!("{HELP}"),
+            ["q"] => return Ok(false),
+            _ => return Err(format!("unknown command `{line}`, try `help`")),
+        }
+        Ok(true)
+    }
+}
+
+/// Interactive debugger for a day 17 program file, reading commands line by line from stdin.
+pub fn run(input: &String) {
+    let machine = match load(input) {
+        Ok(machine) => machine,
+        Err(err) => return eprintln!("{err}"),
+    };
+    let mut debugger = Debugger::new(machine);
+    println!("type `help` for commands");
+    debugger.show();
+
+    let mut lines = stdin().lock().lines();
+    loop {
+        print!("(dbg) ");
+        stdout().flush().unwrap();
+        let Some(Ok(line)) = lines.next() else {
+            break;
+        };
+        match debugger.command(line.trim()) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(message) => println!("{message}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "Register A: 729\nRegister B: 0\nRegister C: 0\n\nProgram: 0,1,5,4,3,0\n";
+
+    fn debugger(input: &str) -> Debugger {
+        Debugger::new(load(&input.to_string()).unwrap())
+    }
+
+    #[test]
+    fn steps_through_the_program() {
+        let mut debugger = debugger(EXAMPLE);
+        assert_eq!(debugger.command(""), Ok(true));
+        assert_eq!(
+            debugger.status(),
+            "a=364 b=0 c=0 | next  2: out a | output: "
+        );
+        assert_eq!(debugger.command("s 2"), Ok(true));
+        assert_eq!(
+            debugger.status(),
+            "a=364 b=0 c=0 | next  0: adv 1 | output: 4"
+        );
+        assert_eq!(debugger.log.len(), 3);
+        assert_eq!(debugger.command("s 0"), Ok(true));
+        assert_eq!(debugger.machine.ip, 0);
+    }
+
+    #[test]
+    fn rejects_negative_counts() {
+        let mut debugger = debugger(EXAMPLE);
+        for command in ["s -1", "b -2", "d -2", "log -1"] {
+            assert_eq!(
+                debugger.command(command),
+                Err(format!(
+                    "`{}` is not a non-negative number",
+                    &command[command.len() - 2..]
+                ))
+            );
+        }
+        assert_eq!(debugger.machine.ip, 0);
+        assert!(debugger.breakpoints.is_empty());
+    }
+
+    #[test]
+    fn continues_to_breakpoints() {
+        let mut debugger = debugger(EXAMPLE);
+        debugger.command("b 4").unwrap();
+        debugger.command("c").unwrap();
+        assert_eq!(
+            debugger.status(),
+            "a=364 b=0 c=0 | next  4: jnz 0 | output: 4"
+        );
+        // the breakpoint the run starts on does not stop it again
+        debugger.command("c").unwrap();
+        assert_eq!(
+            debugger.status(),
+            "a=182 b=0 c=0 | next  4: jnz 0 | output: 4,6"
+        );
+        debugger.command("d 4").unwrap();
+        debugger.command("c").unwrap();
+        assert_eq!(
+            debugger.status(),
+            "a=0 b=0 c=0 | halted | output: 4,6,3,5,6,3,5,2,1,0"
+        );
+    }
+
+    #[test]
+    fn edits_and_shows_registers() {
+        let mut debugger = debugger(EXAMPLE);
+        debugger.command("set b -5").unwrap();
+        debugger.command("set C 7").unwrap();
+        assert_eq!(
+            debugger.status(),
+            "a=729 b=-5 c=7 | next  0: adv 1 | output: "
+        );
+        assert_eq!(
+            debugger.command("set x 1"),
+            Err("`x` is not a register".to_string())
+        );
+        debugger.command("s").unwrap();
+        debugger.command("reset").unwrap();
+        assert_eq!(
+            debugger.status(),
+            "a=729 b=0 c=0 | next  0: adv 1 | output: "
+        );
+        assert!(debugger.log.is_empty());
+        assert_eq!(debugger.command("q"), Ok(false));
+    }
+
+    #[test]
+    fn keeps_the_log_bounded() {
+        // `jnz 0` never changes A, so the program runs until the step count is used up
+        let mut debugger =
+            debugger("Register A: 1\nRegister B: 0\nRegister C: 0\n\nProgram: 3,0\n");
+        debugger.command(&format!("s {}", LOG_LIMIT + 5)).unwrap();
+        assert_eq!(debugger.log.len(), LOG_LIMIT);
+        assert_eq!(debugger.machine.ip, 0);
+    }
+}
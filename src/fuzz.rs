@@ -0,0 +1,194 @@
+use crate::generate::{default_size, generate, Rng};
+use crate::prelude::Aoc2024;
+use std::panic;
+use std::panic::AssertUnwindSafe;
+
+/// Interesting values to splice into inputs, mostly around integer limits.
+const SPECIAL: [&str; 8] = [
+    "0",
+    "-1",
+    "4294967296",
+    "9223372036854775807",
+    "99999999999999999999999",
+    "\n",
+    "\r\n",
+    "\n\n",
+];
+
+struct Report {
+    accepted: usize,
+    rejected: usize,
+    panics: usize,
+    first_panic: Option<(String, String)>,
+    generated_rejected: Option<String>,
+}
+
+/// Derives a broken input from a valid one.
+fn mutate(rng: &mut Rng, valid: &str) -> String {
+    let mut bytes = valid.as_bytes().to_vec();
+    let mutations = 1 + rng.below(4);
+    for _ in 0..mutations {
+        let position = rng.below(bytes.len() + 1);
+        match rng.below(7) {
+            0 if position < bytes.len() => bytes[position] = rng.below(128) as u8,
+            1 if position < bytes.len() => {
+                let end = (position + 1 + rng.below(8)).min(bytes.len());
+                bytes.drain(position..end);
+            }
+            2 => {
+                let end = (position + 1 + rng.below(16)).min(bytes.len());
+                let copy = bytes[position..end].to_vec();
+                bytes.splice(position..position, copy);
+            }
+            3 => {
+                let special = rng.pick(&SPECIAL).as_bytes();
+                bytes.splice(position..position, special.iter().copied());
+            }
+            4 => bytes.truncate(position),
+            5 => bytes.extend_from_slice(rng.pick(&SPECIAL[5..]).as_bytes()),
+            _ => bytes.insert(position, *rng.pick(b"0123456789,:|-#.\t ")),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Arbitrary bytes, not derived from any valid input.
+fn noise(rng: &mut Rng) -> String {
+    let bytes: Vec<u8> = (0..rng.below(64)).map(|_| rng.below(256) as u8).collect();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Parses `iterations` inputs for `day`, with `solve` the inputs that parse are solved as well.
+fn fuzz_day(puzzle: &dyn Aoc2024, day: u8, iterations: usize, seed: u64, solve: bool) -> Report {
+    let mut report = Report {
+        accepted: 0,
+        rejected: 0,
+        panics: 0,
+        first_panic: None,
+        generated_rejected: None,
+    };
+    let mut rng = Rng::new(seed ^ day as u64);
+    for iteration in 0..iterations {
+        let valid = generate(day, default_size(day).min(12), seed + iteration as u64)
+            .expect("every day has a generator");
+        let input = match iteration {
+            0 => valid,
+            _ if rng.chance(10) => noise(&mut rng),
+            _ => mutate(&mut rng, &valid),
+        };
+        // inputs that parse must also be solvable without panicking
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+            let parsed = puzzle.parse(&input);
+            if solve && parsed.is_ok() {
+                puzzle.solve_a(&input);
+                puzzle.solve_b(&input);
+            }
+            parsed
+        }));
+        match outcome {
+            Ok(Ok(())) => report.accepted += 1,
+            Ok(Err(err)) if iteration == 0 => report.generated_rejected = Some(err.to_string()),
+            Ok(Err(_)) => report.rejected += 1,
+            Err(payload) => {
+                report.panics += 1;
+                if report.first_panic.is_none() {
+                    let message = payload
+                        .downcast_ref::<&str>()
+                        .map(|message| message.to_string())
+                        .or_else(|| payload.downcast_ref::<String>().cloned())
+                        .unwrap_or_default();
+                    report.first_panic = Some((input, message));
+                }
+            }
+        }
+    }
+    report
+}
+
+/// Feeds random and mutated inputs to the parsers for `fuzz [<day>] [--iterations <n>] [--seed <s>]`.
+/// Every input must either parse or be rejected with a `ParseError`, and both parts are solved for
+/// the inputs that parse. Panics are reported.
+pub fn run(puzzles: &[Box<dyn Aoc2024>], args: &[String]) {
+    let usage = "usage: fuzz [<day>] [--iterations <n>] [--seed <s>]";
+    let mut days: Vec<usize> = (1..=puzzles.len()).collect();
+    let mut iterations = 1000;
+    let mut seed = 0;
+    let mut options = args.iter();
+    while let Some(option) = options.next() {
+        match option.as_str() {
+            "--iterations" => {
+                iterations = options.next().and_then(|v| v.parse().ok()).expect(usage)
+            }
+            "--seed" => seed = options.next().and_then(|v| v.parse().ok()).expect(usage),
+            day => {
+                let day = day
+                    .parse()
+                    .ok()
+                    .filter(|day| (1..=puzzles.len()).contains(day))
+                    .expect(usage);
+                days = vec![day];
+            }
+        }
+    }
+
+    // keep the output readable, the panics are collected in the report
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let reports: Vec<(usize, Report)> = days
+        .into_iter()
+        .map(|day| {
+            let puzzle = puzzles[day - 1].as_ref();
+            (day, fuzz_day(puzzle, day as u8, iterations, seed, true))
+        })
+        .collect();
+    panic::set_hook(hook);
+
+    let mut failed = false;
+    for (day, report) in reports {
+        println!(
+            "{}: {} accepted, {} rejected, {} panics",
+            puzzles[day - 1].name(),
+            report.accepted,
+            report.rejected,
+            report.panics
+        );
+        if let Some(err) = report.generated_rejected {
+            failed = true;
+            println!("  generated input rejected: {err}");
+        }
+        if let Some((input, message)) = report.first_panic {
+            failed = true;
+            let input: String = input.escape_debug().take(200).collect();
+            println!("  first panic: {message}");
+            println!("  input: \"{input}\"");
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsers_reject_broken_input_without_panicking() {
+        for (i, puzzle) in crate::get_all_puzzles().iter().enumerate() {
+            let day = i as u8 + 1;
+            let report = fuzz_day(puzzle.as_ref(), day, 100, 1, false);
+            assert_eq!(report.generated_rejected, None, "day {day}");
+            assert_eq!(report.first_panic, None, "day {day}");
+        }
+    }
+
+    #[test]
+    fn solvers_handle_broken_input_without_panicking() {
+        // fewer inputs, some days search for a long time on broken ones
+        for (i, puzzle) in crate::get_all_puzzles().iter().enumerate() {
+            let day = i as u8 + 1;
+            let report = fuzz_day(puzzle.as_ref(), day, 10, 2, true);
+            assert_eq!(report.first_panic, None, "day {day}");
+        }
+    }
+}
@@ -0,0 +1,860 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fmt::Write;
+
+/// Small SplitMix64 generator, so the same seed gives the same input on every platform.
+#[derive(Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in `0..n`.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Uniform value in `low..high`, `low` if the range is empty.
+    pub fn range(&mut self, low: i64, high: i64) -> i64 {
+        if high <= low {
+            return low;
+        }
+        low + (self.next_u64() % (high - low) as u64) as i64
+    }
+
+    pub fn chance(&mut self, percent: usize) -> bool {
+        self.below(100) < percent
+    }
+
+    pub fn pick<'a, T>(&mut self, values: &'a [T]) -> &'a T {
+        &values[self.below(values.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, values: &mut [T]) {
+        for i in (1..values.len()).rev() {
+            values.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// Size of the generated input if none is given, roughly matching the real puzzle inputs.
+pub fn default_size(day: u8) -> usize {
+    match day {
+        1 | 2 | 22 => 1000,
+        3 => 3000,
+        4 | 6 | 12 => 140,
+        5 => 49,
+        7 | 13 | 25 => 500,
+        8 | 10 => 50,
+        9 => 19999,
+        11 => 8,
+        14 => 500,
+        15 => 50,
+        16 | 20 => 141,
+        17 => 16,
+        18 => 71,
+        19 => 400,
+        21 => 5,
+        23 => 520,
+        24 => 45,
+        _ => 100,
+    }
+}
+
+/// Generates a random but valid input for the given day.
+/// `size` scales the input, it is the side length for grids and the number of entries otherwise.
+pub fn generate(day: u8, size: usize, seed: u64) -> Option<String> {
+    let mut rng = Rng::new(seed ^ ((day as u64) << 56));
+    let input = match day {
+        1 => location_lists(&mut rng, size),
+        2 => reports(&mut rng, size),
+        3 => corrupted_memory(&mut rng, size),
+        4 => letter_grid(&mut rng, size, b"XMAS"),
+        5 => print_queue(&mut rng, size),
+        6 => guard_lab(&mut rng, size),
+        7 => equations(&mut rng, size),
+        8 => antennas(&mut rng, size),
+        9 => disk_map(&mut rng, size),
+        10 => topographic_map(&mut rng, size),
+        11 => stones(&mut rng, size),
+        12 => garden(&mut rng, size),
+        13 => claw_machines(&mut rng, size),
+        14 => robots(&mut rng, size),
+        15 => warehouse(&mut rng, size),
+        16 => reindeer_maze(&mut rng, size),
+        17 => program(&mut rng, size),
+        18 => falling_bytes(&mut rng, size),
+        19 => towels(&mut rng, size),
+        20 => racetrack(&mut rng, size),
+        21 => door_codes(&mut rng, size),
+        22 => secrets(&mut rng, size),
+        23 => network(&mut rng, size),
+        24 => adder(&mut rng, size),
+        25 => schematics(&mut rng, size),
+        _ => return None,
+    };
+    Some(input)
+}
+
+fn render(grid: &[Vec<u8>]) -> String {
+    grid.iter()
+        .map(|row| String::from_utf8_lossy(row).into_owned())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Odd side length of at least 5, as needed for mazes with walls between the cells.
+fn maze_size(size: usize) -> usize {
+    size.max(5) | 1
+}
+
+/// Perfect maze on odd coordinates surrounded by walls, carved by an iterative DFS.
+fn carve_maze(rng: &mut Rng, size: usize) -> Vec<Vec<u8>> {
+    let mut grid = vec![vec![b'#'; size]; size];
+    let mut stack = vec![(1, 1)];
+    grid[1][1] = b'.';
+    while let Some(&(x, y)) = stack.last() {
+        let mut options: Vec<(usize, usize)> = [(0, 2), (2, 0), (0, -2), (-2, 0)]
+            .iter()
+            .map(|&(dx, dy)| (x as i64 + dx, y as i64 + dy))
+            .filter(|&(nx, ny)| nx > 0 && ny > 0 && nx < size as i64 - 1 && ny < size as i64 - 1)
+            .map(|(nx, ny)| (nx as usize, ny as usize))
+            .filter(|&(nx, ny)| grid[nx][ny] == b'#')
+            .collect();
+        if options.is_empty() {
+            stack.pop();
+            continue;
+        }
+        rng.shuffle(&mut options);
+        let (nx, ny) = options[0];
+        grid[(x + nx) / 2][(y + ny) / 2] = b'.';
+        grid[nx][ny] = b'.';
+        stack.push((nx, ny));
+    }
+    grid
+}
+
+fn location_lists(rng: &mut Rng, size: usize) -> String {
+    let left: Vec<i64> = (0..size).map(|_| rng.range(10000, 100000)).collect();
+    let right: Vec<i64> = (0..size)
+        .map(|_| {
+            if rng.chance(30) {
+                *rng.pick(&left)
+            } else {
+                rng.range(10000, 100000)
+            }
+        })
+        .collect();
+    left.iter()
+        .zip(&right)
+        .map(|(a, b)| format!("{a}   {b}"))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn reports(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let len = rng.range(5, 9) as usize;
+            let increasing = rng.chance(50);
+            let mut level = rng.range(1, 90);
+            let mut levels = Vec::new();
+            for _ in 0..len {
+                levels.push(level);
+                let step = if rng.chance(90) {
+                    rng.range(1, 4)
+                } else {
+                    rng.range(-2, 6)
+                };
+                level += if increasing { step } else { -step };
+            }
+            levels
+                .iter()
+                .map(i64::to_string)
+                .collect::<Vec<String>>()
+                .join(" ")
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn corrupted_memory(rng: &mut Rng, size: usize) -> String {
+    let junk = b"!@#$%^&*()[]{}<>?'+-,;: whyselectfromwhomhowmul";
+    let mut lines = Vec::new();
+    for _ in 0..6 {
+        let mut line = String::new();
+        while line.len() < size {
+            match rng.below(10) {
+                0..=2 => write!(line, "mul({},{})", rng.range(1, 1000), rng.range(1, 1000)),
+                3 => write!(line, "mul({} ,{})", rng.range(1, 1000), rng.range(1, 1000)),
+                4 => write!(line, "mul[{},{}]", rng.range(1, 1000), rng.range(1, 1000)),
+                5 => write!(line, "do()"),
+                6 => write!(line, "don't()"),
+                _ => write!(line, "{}", *rng.pick(junk) as char),
+            }
+            .unwrap();
+        }
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+fn letter_grid(rng: &mut Rng, size: usize, letters: &[u8]) -> String {
+    let grid: Vec<Vec<u8>> = (0..size)
+        .map(|_| (0..size).map(|_| *rng.pick(letters)).collect())
+        .collect();
+    render(&grid)
+}
+
+fn print_queue(rng: &mut Rng, size: usize) -> String {
+    let mut pages: Vec<i64> = (10..100).collect();
+    rng.shuffle(&mut pages);
+    pages.truncate(size.clamp(5, 90));
+
+    let mut rules = Vec::new();
+    for i in 0..pages.len() {
+        for j in i + 1..pages.len() {
+            rules.push(format!("{}|{}", pages[i], pages[j]));
+        }
+    }
+    rng.shuffle(&mut rules);
+
+    let updates: Vec<String> = (0..4 * size)
+        .map(|_| {
+            let max_len = pages.len().min(23);
+            let len = rng.range(2, max_len as i64 / 2 + 1) as usize * 2 + 1;
+            let mut indices: Vec<usize> = (0..pages.len()).collect();
+            rng.shuffle(&mut indices);
+            indices.truncate(len.min(pages.len()));
+            if rng.chance(50) {
+                indices.sort();
+            }
+            indices
+                .iter()
+                .map(|&i| pages[i].to_string())
+                .collect::<Vec<String>>()
+                .join(",")
+        })
+        .collect();
+    format!("{}\n\n{}", rules.join("\n"), updates.join("\n"))
+}
+
+fn guard_lab(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(2);
+    let mut grid: Vec<Vec<u8>> = (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| if rng.chance(5) { b'#' } else { b'.' })
+                .collect()
+        })
+        .collect();
+    grid[rng.below(size)][rng.below(size)] = b'^';
+    render(&grid)
+}
+
+fn equations(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let values: Vec<i64> = (0..rng.range(2, 9)).map(|_| rng.range(1, 100)).collect();
+            let mut target = values[0];
+            for &value in &values[1..] {
+                target = match rng.below(3) {
+                    0 => target + value,
+                    1 => target * value,
+                    _ => format!("{target}{value}").parse().unwrap(),
+                };
+            }
+            if rng.chance(40) {
+                target += rng.range(1, 10);
+            }
+            let values: Vec<String> = values.iter().map(i64::to_string).collect();
+            format!("{target}: {}", values.join(" "))
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn antennas(rng: &mut Rng, size: usize) -> String {
+    let frequencies = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    let mut grid = vec![vec![b'.'; size]; size];
+    for _ in 0..size / 4 {
+        let frequency = *rng.pick(frequencies);
+        for _ in 0..4 {
+            grid[rng.below(size)][rng.below(size)] = frequency;
+        }
+    }
+    render(&grid)
+}
+
+fn disk_map(rng: &mut Rng, size: usize) -> String {
+    (0..(size | 1))
+        .map(|i| {
+            let length = if i % 2 == 0 {
+                rng.range(1, 10)
+            } else {
+                rng.range(0, 10)
+            };
+            char::from(b'0' + length as u8)
+        })
+        .collect()
+}
+
+fn topographic_map(rng: &mut Rng, size: usize) -> String {
+    let peaks: Vec<(i64, i64)> = (0..size.max(1) / 3 + 1)
+        .map(|_| (rng.range(0, size as i64), rng.range(0, size as i64)))
+        .collect();
+    let grid: Vec<Vec<u8>> = (0..size as i64)
+        .map(|x| {
+            (0..size as i64)
+                .map(|y| {
+                    let distance = peaks
+                        .iter()
+                        .map(|(px, py)| (px - x).abs() + (py - y).abs())
+                        .min()
+                        .unwrap();
+                    b'9' - (distance % 10) as u8
+                })
+                .collect()
+        })
+        .collect();
+    render(&grid)
+}
+
+fn stones(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| rng.range(0, 1000000).to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn garden(rng: &mut Rng, size: usize) -> String {
+    let seeds: Vec<(i64, i64, u8)> = (0..size.max(1))
+        .map(|_| {
+            (
+                rng.range(0, size as i64),
+                rng.range(0, size as i64),
+                b'A' + rng.below(26) as u8,
+            )
+        })
+        .collect();
+    let grid: Vec<Vec<u8>> = (0..size as i64)
+        .map(|x| {
+            (0..size as i64)
+                .map(|y| {
+                    seeds
+                        .iter()
+                        .min_by_key(|(sx, sy, _)| (sx - x).pow(2) + (sy - y).pow(2))
+                        .unwrap()
+                        .2
+                })
+                .collect()
+        })
+        .collect();
+    render(&grid)
+}
+
+/// Offset part b of day 13 adds to every prize.
+const PRIZE_OFFSET: i64 = 10_000_000_000_000;
+
+fn claw_machines(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| loop {
+            let a = (rng.range(10, 100), rng.range(10, 100));
+            let b = (rng.range(10, 100), rng.range(10, 100));
+            let det = a.0 * b.1 - a.1 * b.0;
+            if det == 0 {
+                continue;
+            }
+            let prize = if rng.chance(50) {
+                let (a_presses, b_presses) = (rng.range(1, 101), rng.range(1, 101));
+                let mut prize = (
+                    a.0 * a_presses + b.0 * b_presses,
+                    a.1 * a_presses + b.1 * b_presses,
+                );
+                if rng.chance(50) {
+                    prize.0 += rng.range(1, 50);
+                }
+                prize
+            } else {
+                // whole presses near the far away prize of part b, which needs one button
+                // steeper and one flatter than the diagonal
+                if (a.0 < a.1) == (b.0 < b.1) {
+                    continue;
+                }
+                let near = (
+                    rng.range(1000, 20000) + PRIZE_OFFSET,
+                    rng.range(1000, 20000) + PRIZE_OFFSET,
+                );
+                let a_presses = (near.0 * b.1 - near.1 * b.0) / det;
+                let b_presses = (a.0 * near.1 - a.1 * near.0) / det;
+                let mut prize = (
+                    a.0 * a_presses + b.0 * b_presses - PRIZE_OFFSET,
+                    a.1 * a_presses + b.1 * b_presses - PRIZE_OFFSET,
+                );
+                if rng.chance(50) {
+                    prize.1 += rng.range(1, 50);
+                }
+                prize
+            };
+            break format!(
+                "Button A: X+{}, Y+{}\nButton B: X+{}, Y+{}\nPrize: X={}, Y={}",
+                a.0, a.1, b.0, b.1, prize.0, prize.1
+            );
+        })
+        .collect::<Vec<String>>()
+        .join("\n\n")
+}
+
+fn robots(rng: &mut Rng, size: usize) -> String {
+    let (width, height) = (101, 103);
+    // most robots gather in one block at `time`, so part b has something to find
+    let time = rng.range(0, width * height);
+    let corner = (rng.range(0, width - 30), rng.range(0, height - 30));
+    (0..size)
+        .map(|i| {
+            let velocity = (rng.range(-99, 100), rng.range(-99, 100));
+            let position = if i % 3 == 0 {
+                (rng.range(0, width), rng.range(0, height))
+            } else {
+                let target = (corner.0 + rng.range(0, 30), corner.1 + rng.range(0, 30));
+                (
+                    (target.0 - velocity.0 * time).rem_euclid(width),
+                    (target.1 - velocity.1 * time).rem_euclid(height),
+                )
+            };
+            format!(
+                "p={},{} v={},{}",
+                position.0, position.1, velocity.0, velocity.1
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn warehouse(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(4);
+    let mut grid: Vec<Vec<u8>> = (0..size)
+        .map(|x| {
+            (0..size)
+                .map(|y| {
+                    if x == 0 || y == 0 || x == size - 1 || y == size - 1 || rng.chance(10) {
+                        b'#'
+                    } else if rng.chance(30) {
+                        b'O'
+                    } else {
+                        b'.'
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    grid[rng.range(1, size as i64 - 1) as usize][rng.range(1, size as i64 - 1) as usize] = b'@';
+
+    let moves: Vec<u8> = (0..size * size * 8).map(|_| *rng.pick(b"<>^v")).collect();
+    let moves: Vec<String> = moves
+        .chunks(1000)
+        .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+        .collect();
+    format!("{}\n\n{}", render(&grid), moves.join("\n"))
+}
+
+fn reindeer_maze(rng: &mut Rng, size: usize) -> String {
+    let size = maze_size(size);
+    let mut grid = carve_maze(rng, size);
+    // open some walls so there are several routes to compare
+    for _ in 0..size * size / 20 {
+        let (x, y) = (rng.range(1, size as i64 - 1), rng.range(1, size as i64 - 1));
+        grid[x as usize][y as usize] = b'.';
+    }
+    grid[size - 2][1] = b'S';
+    grid[1][size - 2] = b'E';
+    render(&grid)
+}
+
+fn program(rng: &mut Rng, size: usize) -> String {
+    // the usual shape: shift A by three bits per loop and output a hash of the low bits
+    let outputs = size.clamp(1, 20) as u32;
+    let a = rng.range(8i64.pow(outputs - 1), 8i64.pow(outputs));
+    // like the real inputs, keep constants for which some A makes the program print itself
+    let program = loop {
+        let program = [
+            2,
+            4,
+            1,
+            rng.below(8),
+            7,
+            5,
+            1,
+            rng.below(8),
+            4,
+            rng.below(8),
+            0,
+            3,
+            5,
+            5,
+            3,
+            0,
+        ];
+        if has_quine(&program) {
+            break program;
+        }
+    };
+    let program: Vec<String> = program.iter().map(usize::to_string).collect();
+    format!(
+        "Register A: {a}\nRegister B: 0\nRegister C: 0\n\nProgram: {}",
+        program.join(",")
+    )
+}
+
+/// Whether some positive A makes a program of the shape built by `program` print itself.
+/// Every loop prints `(A % 8) ^ k1 ^ k2 ^ (A >> ((A % 8) ^ k1))` and shifts A by three bits,
+/// so A is searched three bits at a time from the last output.
+fn has_quine(program: &[usize; 16]) -> bool {
+    let (k1, k2) = (program[3] as i64, program[7] as i64);
+    let output = |a: i64| {
+        let b = (a & 7) ^ k1;
+        ((b ^ k2 ^ (a >> b)) & 7) as usize
+    };
+    let mut candidates = vec![0_i64];
+    for &expected in program.iter().rev() {
+        candidates = candidates
+            .iter()
+            .flat_map(|&a| (0..8).map(move |bits| a << 3 | bits))
+            .filter(|&a| a != 0 && output(a) == expected)
+            .collect();
+    }
+    !candidates.is_empty()
+}
+
+fn falling_bytes(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(2);
+    // the bytes part a lets fall, a share like the puzzle's 1024 of 5041 cells, miss a random
+    // monotone path so the exit can be reached
+    let mut path = HashSet::new();
+    let (mut x, mut y) = (0, 0);
+    while (x, y) != (size - 1, size - 1) {
+        if y == size - 1 || (x < size - 1 && rng.chance(50)) {
+            x += 1;
+        } else {
+            y += 1;
+        }
+        path.insert((x, y));
+    }
+    // every cell but start and exit falls eventually, so the exit is always cut off
+    let (mut cells, mut rest): (Vec<_>, Vec<_>) = (0..size)
+        .flat_map(|x| (0..size).map(move |y| (x, y)))
+        .filter(|&cell| cell != (0, 0) && cell != (size - 1, size - 1))
+        .partition(|cell| !path.contains(cell));
+    rng.shuffle(&mut cells);
+    let fallen = (size * size * 1024 / 5041).min(cells.len());
+    rest.extend(cells.drain(fallen..));
+    rng.shuffle(&mut rest);
+    cells.extend(rest);
+    let bytes = cells
+        .iter()
+        .map(|(x, y)| format!("{x},{y}"))
+        .collect::<Vec<String>>()
+        .join("\n");
+    // other grids than the puzzle's 71x71 need a header
+    match size {
+        71 => bytes,
+        _ => format!("size {size}x{size}\nbytes {fallen}\n{bytes}"),
+    }
+}
+
+fn towels(rng: &mut Rng, size: usize) -> String {
+    let colours = b"wubrg";
+    let count = size / 8 + 5;
+    let mut patterns: Vec<String> = Vec::new();
+    let mut seen = HashSet::new();
+    for _ in 0..count * 4 {
+        let len = rng.range(1, 9) as usize;
+        let pattern: String = (0..len).map(|_| *rng.pick(colours) as char).collect();
+        // like the real input, a single `g` stripe is never available
+        if pattern != "g" && seen.insert(pattern.clone()) {
+            patterns.push(pattern);
+        }
+        if patterns.len() == count {
+            break;
+        }
+    }
+    let designs: Vec<String> = (0..size)
+        .map(|_| {
+            if rng.chance(70) {
+                (0..rng.range(2, 9))
+                    .map(|_| rng.pick(&patterns).as_str())
+                    .collect()
+            } else {
+                // prefer random designs that really can't be built
+                let mut design = String::new();
+                for _ in 0..100 {
+                    design = (0..rng.range(20, 61))
+                        .map(|_| *rng.pick(colours) as char)
+                        .collect();
+                    if !can_build(&design, &patterns) {
+                        break;
+                    }
+                }
+                design
+            }
+        })
+        .collect();
+    format!("{}\n\n{}", patterns.join(", "), designs.join("\n"))
+}
+
+fn can_build(design: &str, patterns: &[String]) -> bool {
+    let mut reachable = vec![false; design.len() + 1];
+    reachable[0] = true;
+    for start in 0..design.len() {
+        if reachable[start] {
+            for pattern in patterns {
+                if design[start..].starts_with(pattern.as_str()) {
+                    reachable[start + pattern.len()] = true;
+                }
+            }
+        }
+    }
+    reachable[design.len()]
+}
+
+fn racetrack(rng: &mut Rng, size: usize) -> String {
+    let size = maze_size(size);
+    let maze = carve_maze(rng, size);
+
+    // the track is the longest route through the maze starting in the top left corner
+    let mut parent = vec![vec![None; size]; size];
+    let mut order = vec![(1, 1)];
+    parent[1][1] = Some((1, 1));
+    let mut i = 0;
+    while i < order.len() {
+        let (x, y) = order[i];
+        for (dx, dy) in [(0, 1), (1, 0), (0, -1), (-1, 0)] {
+            let (nx, ny) = ((x as i64 + dx) as usize, (y as i64 + dy) as usize);
+            if maze[nx][ny] == b'.' && parent[nx][ny].is_none() {
+                parent[nx][ny] = Some((x, y));
+                order.push((nx, ny));
+            }
+        }
+        i += 1;
+    }
+    let mut grid = vec![vec![b'#'; size]; size];
+    let end = *order.last().unwrap();
+    let mut current = end;
+    while current != (1, 1) {
+        grid[current.0][current.1] = b'.';
+        current = parent[current.0][current.1].unwrap();
+    }
+    grid[1][1] = b'S';
+    grid[end.0][end.1] = b'E';
+    render(&grid)
+}
+
+fn door_codes(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| format!("{:03}A", rng.range(0, 1000)))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn secrets(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| rng.range(1, 1 << 24).to_string())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn network(rng: &mut Rng, size: usize) -> String {
+    let mut names: Vec<String> = (b'a'..=b'z')
+        .flat_map(|a| (b'a'..=b'z').map(move |b| format!("{}{}", a as char, b as char)))
+        .collect();
+    rng.shuffle(&mut names);
+    names.truncate(size.clamp(2, names.len()));
+
+    // ordered, so the same seed orients the same edges
+    let mut edges: BTreeSet<(usize, usize)> = BTreeSet::new();
+    let mut connect = |a: usize, b: usize| {
+        if a != b {
+            edges.insert((a.min(b), a.max(b)));
+        }
+    };
+    // one large LAN party plus random links
+    let clique = names.len().min(13);
+    for a in 0..clique {
+        for b in a + 1..clique {
+            connect(a, b);
+        }
+    }
+    for a in 0..names.len() {
+        for _ in 0..6 {
+            connect(a, rng.below(names.len()));
+        }
+    }
+    let mut lines: Vec<String> = edges
+        .iter()
+        .map(|&(a, b)| {
+            if rng.chance(50) {
+                format!("{}-{}", names[a], names[b])
+            } else {
+                format!("{}-{}", names[b], names[a])
+            }
+        })
+        .collect();
+    lines.sort();
+    rng.shuffle(&mut lines);
+    lines.join("\n")
+}
+
+fn adder(rng: &mut Rng, size: usize) -> String {
+    let bits = size.clamp(2, 99);
+    let mut used = HashSet::new();
+    let mut wire = |rng: &mut Rng| loop {
+        let name: String = (0..3)
+            .map(|_| (b'a' + rng.below(23) as u8) as char)
+            .collect();
+        if used.insert(name.clone()) {
+            break name;
+        }
+    };
+
+    // (a, op, b, output) per gate, a ripple carry adder
+    let mut gates: Vec<(String, &str, String, String)> = Vec::new();
+    let mut carry = wire(rng);
+    gates.push(("x00".into(), "XOR", "y00".into(), "z00".into()));
+    gates.push(("x00".into(), "AND", "y00".into(), carry.clone()));
+    let mut swappable = Vec::new();
+    for i in 1..bits {
+        let (x, y, z) = (format!("x{i:02}"), format!("y{i:02}"), format!("z{i:02}"));
+        let half_sum = wire(rng);
+        let half_carry = wire(rng);
+        let carry_through = wire(rng);
+        let next_carry = if i == bits - 1 {
+            format!("z{bits:02}")
+        } else {
+            wire(rng)
+        };
+        let base = gates.len();
+        gates.push((x.clone(), "XOR", y.clone(), half_sum.clone()));
+        gates.push((x, "AND", y, half_carry.clone()));
+        gates.push((half_sum.clone(), "XOR", carry.clone(), z));
+        gates.push((half_sum, "AND", carry, carry_through.clone()));
+        gates.push((half_carry, "OR", carry_through, next_carry.clone()));
+        if i < bits - 1 {
+            // swaps that break the adder without creating a loop
+            swappable.push([
+                (base + 2, base + 3),
+                (base + 2, base + 1),
+                (base + 2, base + 4),
+                (base, base + 1),
+            ]);
+        }
+        carry = next_carry;
+    }
+    rng.shuffle(&mut swappable);
+    for options in swappable.iter().take(4) {
+        let (a, b) = *rng.pick(options);
+        let output = gates[a].3.clone();
+        gates[a].3 = gates[b].3.clone();
+        gates[b].3 = output;
+    }
+
+    let mut inputs = Vec::new();
+    for var in ['x', 'y'] {
+        for i in 0..bits {
+            inputs.push(format!("{var}{i:02}: {}", rng.below(2)));
+        }
+    }
+    let mut lines: Vec<String> = gates
+        .into_iter()
+        .map(|(a, op, b, out)| {
+            if rng.chance(50) {
+                format!("{a} {op} {b} -> {out}")
+            } else {
+                format!("{b} {op} {a} -> {out}")
+            }
+        })
+        .collect();
+    rng.shuffle(&mut lines);
+    format!("{}\n\n{}", inputs.join("\n"), lines.join("\n"))
+}
+
+fn schematics(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let is_key = rng.chance(50);
+            let heights: Vec<usize> = (0..5).map(|_| rng.below(6)).collect();
+            let rows: Vec<String> = (0..7)
+                .map(|row| {
+                    heights
+                        .iter()
+                        .map(|&height| {
+                            let filled = if is_key {
+                                row >= 6 - height
+                            } else {
+                                row <= height
+                            };
+                            if filled {
+                                '#'
+                            } else {
+                                '.'
+                            }
+                        })
+                        .collect()
+                })
+                .collect();
+            rows.join("\n")
+        })
+        .collect::<Vec<String>>()
+        .join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::Aoc2024;
+    use crate::puzzle13::Puzzle13;
+    use crate::puzzle17::Puzzle17;
+
+    #[test]
+    fn generates_tiny_inputs() {
+        for day in 1..=25 {
+            for size in 0..3 {
+                assert!(generate(day, size, 1).is_some(), "day {day} size {size}");
+            }
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_same_input() {
+        for day in 1..=25 {
+            let size = default_size(day).min(20);
+            assert_eq!(generate(day, size, 7), generate(day, size, 7), "day {day}");
+        }
+        assert_ne!(generate(1, 100, 7), generate(1, 100, 8));
+    }
+
+    #[test]
+    fn exercises_part_b() {
+        let input = generate(13, default_size(13), 1).unwrap();
+        let prizes = Puzzle13 {}.solve_b(&input).parse::<i64>().unwrap();
+        assert!(prizes > 0, "{prizes}");
+        for seed in 1..=5 {
+            let input = generate(17, default_size(17), seed).unwrap();
+            let quine = Puzzle17 {}.solve_b(&input);
+            assert!(quine.parse::<i64>().is_ok(), "seed {seed}: {quine}");
+        }
+    }
+
+    #[test]
+    fn empty_range_gives_its_start() {
+        assert_eq!(Rng::new(1).range(5, 5), 5);
+    }
+}
@@ -0,0 +1,333 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{Display, Write};
+use std::hash::Hash;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub struct NodeId(usize);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GraphFormat {
+    Dot,
+    GraphML,
+}
+
+impl GraphFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "dot" => Some(GraphFormat::Dot),
+            "graphml" => Some(GraphFormat::GraphML),
+            _ => None,
+        }
+    }
+}
+
+/// Arena based graph. Nodes are interned, adding the same value twice yields the same id.
+#[derive(Clone, Debug)]
+pub struct Graph<N> {
+    directed: bool,
+    nodes: Vec<N>,
+    index: HashMap<N, NodeId>,
+    outgoing: Vec<Vec<NodeId>>,
+    incoming: Vec<Vec<NodeId>>,
+}
+
+impl<N: Clone + Eq + Hash> Graph<N> {
+    pub fn new(directed: bool) -> Self {
+        Self {
+            directed,
+            nodes: Vec::new(),
+            index: HashMap::new(),
+            outgoing: Vec::new(),
+            incoming: Vec::new(),
+        }
+    }
+
+    pub fn add_node(&mut self, node: N) -> NodeId {
+        if let Some(&id) = self.index.get(&node) {
+            return id;
+        }
+        let id = NodeId(self.nodes.len());
+        self.index.insert(node.clone(), id);
+        self.nodes.push(node);
+        self.outgoing.push(Vec::new());
+        self.incoming.push(Vec::new());
+        id
+    }
+
+    /// Adds an edge between two nodes, inserting them if necessary.
+    pub fn connect(&mut self, from: N, to: N) -> (NodeId, NodeId) {
+        let from = self.add_node(from);
+        let to = self.add_node(to);
+        self.add_edge(from, to);
+        (from, to)
+    }
+}
+
+impl<N> Graph<N> {
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId) {
+        if self.outgoing[from.0].contains(&to) {
+            return;
+        }
+        self.outgoing[from.0].push(to);
+        self.incoming[to.0].push(from);
+        if !self.directed && from != to {
+            self.outgoing[to.0].push(from);
+            self.incoming[from.0].push(to);
+        }
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    pub fn node(&self, id: NodeId) -> &N {
+        &self.nodes[id.0]
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = NodeId> {
+        (0..self.nodes.len()).map(NodeId)
+    }
+
+    /// Nodes reachable over a single edge. For undirected graphs these are all neighbours.
+    pub fn neighbours(&self, id: NodeId) -> &[NodeId] {
+        &self.outgoing[id.0]
+    }
+
+    /// Edges as pairs of node ids, undirected edges are listed once.
+    pub fn edges(&self) -> impl Iterator<Item = (NodeId, NodeId)> + '_ {
+        self.ids().flat_map(move |from| {
+            self.outgoing[from.0]
+                .iter()
+                .filter(move |&&to| self.directed || from <= to)
+                .map(move |&to| (from, to))
+        })
+    }
+
+    /// Kahn's algorithm. On a cycle the nodes that could not be ordered are returned as error.
+    pub fn topological_sort(&self) -> Result<Vec<NodeId>, Vec<NodeId>> {
+        let mut in_degree: Vec<usize> = self.incoming.iter().map(Vec::len).collect();
+        let mut queue: VecDeque<NodeId> = self.ids().filter(|id| in_degree[id.0] == 0).collect();
+        let mut order = Vec::with_capacity(self.len());
+        while let Some(id) = queue.pop_front() {
+            order.push(id);
+            for &next in &self.outgoing[id.0] {
+                in_degree[next.0] -= 1;
+                if in_degree[next.0] == 0 {
+                    queue.push_back(next);
+                }
+            }
+        }
+        if order.len() == self.len() {
+            Ok(order)
+        } else {
+            Err(self.ids().filter(|id| in_degree[id.0] > 0).collect())
+        }
+    }
+
+    /// Connected components, edge direction is ignored.
+    pub fn connected_components(&self) -> Vec<Vec<NodeId>> {
+        let mut component: Vec<Option<usize>> = vec![None; self.len()];
+        let mut components = Vec::new();
+        for start in self.ids() {
+            if component[start.0].is_some() {
+                continue;
+            }
+            let mut members = vec![start];
+            component[start.0] = Some(components.len());
+            let mut stack = vec![start];
+            while let Some(id) = stack.pop() {
+                for &next in self.outgoing[id.0].iter().chain(&self.incoming[id.0]) {
+                    if component[next.0].is_none() {
+                        component[next.0] = Some(components.len());
+                        members.push(next);
+                        stack.push(next);
+                    }
+                }
+            }
+            components.push(members);
+        }
+        components
+    }
+}
+
+impl<N: Display> Graph<N> {
+    pub fn export(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Dot => self.to_dot(),
+            GraphFormat::GraphML => self.to_graphml(),
+        }
+    }
+
+    pub fn to_dot(&self) -> String {
+        let (kind, arrow) = if self.directed {
+            ("digraph", "->")
+        } else {
+            ("graph", "--")
+        };
+        let mut out = String::new();
+        writeln!(out, "{kind} G {{").unwrap();
+        for id in self.ids() {
+            writeln!(
+                out,
+                "    n{} [label=\"{}\"];",
+                id.0,
+                escape_dot(self.node(id))
+            )
+            .unwrap();
+        }
+        for (from, to) in self.edges() {
+            writeln!(out, "    n{} {arrow} n{};", from.0, to.0).unwrap();
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    pub fn to_graphml(&self) -> String {
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+        out.push_str(
+            "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n",
+        );
+        let kind = if self.directed {
+            "directed"
+        } else {
+            "undirected"
+        };
+        writeln!(out, "  <graph id=\"G\" edgedefault=\"{kind}\">").unwrap();
+        for id in self.ids() {
+            writeln!(
+                out,
+                "    <node id=\"n{}\"><data key=\"label\">{}</data></node>",
+                id.0,
+                escape_xml(self.node(id))
+            )
+            .unwrap();
+        }
+        for (from, to) in self.edges() {
+            writeln!(
+                out,
+                "    <edge source=\"n{}\" target=\"n{}\"/>",
+                from.0, to.0
+            )
+            .unwrap();
+        }
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+}
+
+/// Escapes a label for a quoted DOT string.
+fn escape_dot(label: impl Display) -> String {
+    label
+        .to_string()
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Escapes a label for XML text.
+fn escape_xml(label: impl Display) -> String {
+    label
+        .to_string()
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(graph: &Graph<&str>, ids: &[NodeId]) -> Vec<String> {
+        ids.iter().map(|&id| graph.node(id).to_string()).collect()
+    }
+
+    #[test]
+    fn sorts_topologically() {
+        let mut graph = Graph::new(true);
+        graph.connect("a", "b");
+        graph.connect("b", "c");
+        graph.add_node("d");
+        let order = graph.topological_sort().unwrap();
+        assert_eq!(labels(&graph, &order), ["a", "d", "b", "c"]);
+
+        // the nodes on the cycle and behind it can not be ordered
+        graph.connect("c", "b");
+        graph.connect("c", "e");
+        let stuck = graph.topological_sort().unwrap_err();
+        assert_eq!(labels(&graph, &stuck), ["b", "c", "e"]);
+    }
+
+    #[test]
+    fn finds_connected_components() {
+        let mut graph = Graph::new(true);
+        graph.connect("a", "b");
+        graph.connect("c", "b");
+        graph.add_node("d");
+        graph.connect("e", "f");
+        let mut components: Vec<Vec<String>> = graph
+            .connected_components()
+            .iter()
+            .map(|members| {
+                let mut members = labels(&graph, members);
+                members.sort();
+                members
+            })
+            .collect();
+        components.sort();
+        assert_eq!(components, [vec!["a", "b", "c"], vec!["d"], vec!["e", "f"]]);
+    }
+
+    #[test]
+    fn exports_dot() {
+        let mut graph = Graph::new(false);
+        graph.connect("say \"hi\"", "C:\\ <tmp> & more");
+        graph.connect("C:\\ <tmp> & more", "say \"hi\"");
+        assert_eq!(graph.edges().count(), 1);
+        assert_eq!(
+            graph.export(GraphFormat::Dot),
+            "graph G {\n    n0 [label=\"say \\\"hi\\\"\"];\n    n1 [label=\"C:\\\\ <tmp> & more\"];\n    n0 -- n1;\n}\n"
+        );
+        let mut graph = Graph::new(true);
+        graph.connect("line\nbreak", "b");
+        let dot = graph.export(GraphFormat::Dot);
+        assert!(
+            dot.starts_with("digraph G {\n    n0 [label=\"line\\nbreak\"];\n"),
+            "{dot}"
+        );
+        assert!(dot.contains("    n0 -> n1;\n"), "{dot}");
+    }
+
+    #[test]
+    fn exports_graphml() {
+        let mut graph = Graph::new(true);
+        graph.connect("say \"hi\"", "C:\\ <tmp> & more");
+        let graphml = graph.export(GraphFormat::GraphML);
+        assert!(
+            graphml.contains("<graph id=\"G\" edgedefault=\"directed\">"),
+            "{graphml}"
+        );
+        assert!(
+            graphml
+                .contains("<node id=\"n0\"><data key=\"label\">say &quot;hi&quot;</data></node>"),
+            "{graphml}"
+        );
+        assert!(
+            graphml.contains(
+                "<node id=\"n1\"><data key=\"label\">C:\\ &lt;tmp&gt; &amp; more</data></node>"
+            ),
+            "{graphml}"
+        );
+        assert!(
+            graphml.contains("<edge source=\"n0\" target=\"n1\"/>"),
+            "{graphml}"
+        );
+        assert!(graphml.ends_with("  </graph>\n</graphml>\n"), "{graphml}");
+    }
+}
@@ -1,6 +1,14 @@
+use crate::graph::GraphFormat;
 use crate::prelude::{read_day, Aoc2024};
+use std::env;
+use std::fs;
 use std::time::Instant;
 
+mod debugger;
+mod fuzz;
+mod generate;
+mod graph;
+mod number_theory;
 mod prelude;
 mod puzzle1;
 mod puzzle10;
@@ -27,6 +35,8 @@ mod puzzle6;
 mod puzzle7;
 mod puzzle8;
 mod puzzle9;
+mod symbolic;
+mod visualize;
 
 fn get_all_puzzles() -> Vec<Box<dyn Aoc2024>> {
     let mut puzzles: Vec<Box<dyn Aoc2024>> = Vec::new();
@@ -71,9 +81,373 @@ fn run_puzzle(content: String, puzzle: &Box<dyn Aoc2024>) {
     println!();
 }
 
+/// Prints the puzzle input as graph on stdout and a short summary on stderr.
+fn export_graph(puzzles: &[Box<dyn Aoc2024>], args: &[String]) {
+    let usage = "usage: --graph <day> [dot|graphml]";
+    let day: usize = args
+        .first()
+        .and_then(|day| day.parse().ok())
+        .filter(|day| (1..=puzzles.len()).contains(day))
+        .expect(usage);
+    let format = match args.get(1) {
+        Some(format) => GraphFormat::parse(format).expect(usage),
+        None => GraphFormat::Dot,
+    };
+    let puzzle = &puzzles[day - 1];
+    let Some(graph) = puzzle.graph(&read_day(day as u8)) else {
+        eprintln!("{} has no graph to export", puzzle.name());
+        return;
+    };
+
+    let max_degree = graph
+        .ids()
+        .map(|id| graph.neighbours(id).len())
+        .max()
+        .unwrap_or(0);
+    let order = match graph.topological_sort() {
+        _ if !graph.is_directed() => "undirected".to_string(),
+        Ok(_) => "acyclic".to_string(),
+        Err(cycle) => format!("{} nodes on or behind cycles", cycle.len()),
+    };
+    eprintln!(
+        "{}: {} nodes, {} edges, max degree {}, {} components, {}",
+        puzzle.name(),
+        graph.len(),
+        graph.edges().count(),
+        max_degree,
+        graph.connected_components().len(),
+        order
+    );
+    print!("{}", graph.export(format));
+}
+
+/// Prints a random input for `generate <day> [--size <n>] [--seed <s>]`.
+fn generate_input(args: &[String]) {
+    let usage = "usage: generate <day> [--size <n>] [--seed <s>]";
+    let day: u8 = args.first().and_then(|day| day.parse().ok()).expect(usage);
+    let mut size = generate::default_size(day);
+    let mut seed = 0;
+    let mut options = args[1..].iter();
+    while let Some(option) = options.next() {
+        let value = options.next().and_then(|value| value.parse().ok());
+        match option.as_str() {
+            "--size" => size = value.expect(usage) as usize,
+            "--seed" => seed = value.expect(usage),
+            _ => panic!("unknown option {option}"),
+        }
+    }
+    match generate::generate(day, size, seed) {
+        Some(input) => print!("{input}"),
+        None => eprintln!("no generator for day {day}"),
+    }
+}
+
+/// Prints the day 17 program in `disassemble <file>` as annotated assembly.
+fn disassemble_program(args: &[String]) {
+    let usage = "usage: disassemble <file>";
+    let path = args.first().expect(usage);
+    let content = fs::read_to_string(path).expect("could not read program file");
+    match puzzle17::disassemble(&content) {
+        Ok(listing) => print!("{listing}"),
+        Err(err) => eprintln!("{err}"),
+    }
+}
+
+/// Prints the puzzle input for `assemble <file> [--a <n>] [--b <n>] [--c <n>]`.
+fn assemble_program(args: &[String]) {
+    let usage = "usage: assemble <file> [--a <n>] [--b <n>] [--c <n>]";
+    let path = args.first().expect(usage);
+    let mut registers = [0; 3];
+    let mut options = args[1..].iter();
+    while let Some(option) = options.next() {
+        let value = options.next().and_then(|value| value.parse().ok());
+        match option.as_str() {
+            "--a" => registers[0] = value.expect(usage),
+            "--b" => registers[1] = value.expect(usage),
+            "--c" => registers[2] = value.expect(usage),
+            _ => panic!("unknown option {option}"),
+        }
+    }
+    let source = fs::read_to_string(path).expect("could not read source file");
+    match puzzle17::assemble(&source, registers) {
+        Ok(input) => println!("{input}"),
+        Err(err) => eprintln!("{err}"),
+    }
+}
+
+/// Starts the day 17 debugger for `debug <file>`.
+fn debug_program(args: &[String]) {
+    let path = args.first().expect("usage: debug <file>");
+    let content = fs::read_to_string(path).expect("could not read program file");
+    debugger::run(&content);
+}
+
+/// Prints the symbolic analysis for `symbolic <file> [<outputs>]`.
+fn analyse_program(args: &[String]) {
+    let path = args.first().expect("usage: symbolic <file> [<outputs>]");
+    let content = fs::read_to_string(path).expect("could not read program file");
+    match symbolic::analyse(&content, args.get(1).map(String::as_str)) {
+        Ok(report) => print!("{report}"),
+        Err(err) => eprintln!("{err}"),
+    }
+}
+
+/// Evaluates a day 24 circuit for `simulate <file> [--x <n>] [--y <n>] [--wires]`.
+fn simulate_circuit(args: &[String]) {
+    let usage = "usage: simulate <file> [--x <n>] [--y <n>] [--wires]";
+    let path = args.first().expect(usage);
+    let (mut x, mut y, mut wires) = (None, None, false);
+    let mut options = args[1..].iter();
+    while let Some(option) = options.next() {
+        match option.as_str() {
+            "--x" => x = Some(options.next().and_then(|v| v.parse().ok()).expect(usage)),
+            "--y" => y = Some(options.next().and_then(|v| v.parse().ok()).expect(usage)),
+            "--wires" => wires = true,
+            _ => panic!("unknown option {option}"),
+        }
+    }
+    let content = fs::read_to_string(path).expect("could not read circuit file");
+    match puzzle24::simulate(&content, x, y, wires) {
+        Ok(report) => print!("{report}"),
+        Err(err) => eprintln!("{err}"),
+    }
+}
+
+/// Writes a day 24 circuit as Verilog or DOT for `export <file> [verilog|dot]`.
+fn export_circuit(args: &[String]) {
+    let usage = "usage: export <file> [verilog|dot]";
+    let path = args.first().expect(usage);
+    let format = args
+        .get(1)
+        .map_or(Some(puzzle24::CircuitFormat::Verilog), |format| {
+            puzzle24::CircuitFormat::parse(format)
+        })
+        .expect(usage);
+    let content = fs::read_to_string(path).expect("could not read circuit file");
+    match puzzle24::export(&content, format) {
+        Ok(output) => print!("{output}"),
+        Err(err) => eprintln!("{err}"),
+    }
+}
+
+/// Compares a day 24 circuit with a reference for `equivalence <file> <add|and|<file>> [--samples <n>] [--seed <s>]`.
+fn check_circuit(args: &[String]) {
+    let usage = "usage: equivalence <file> <add|and|<file>> [--samples <n>] [--seed <s>]";
+    let (path, reference) = match args {
+        [path, reference, ..] => (path, reference),
+        _ => panic!("{usage}"),
+    };
+    let reference = match reference.as_str() {
+        "add" => puzzle24::Reference::Add,
+        "and" => puzzle24::Reference::And,
+        file => puzzle24::Reference::Circuit(
+            fs::read_to_string(file).expect("could not read reference circuit"),
+        ),
+    };
+    let (mut samples, mut seed) = (1000, 0);
+    let mut options = args[2..].iter();
+    while let Some(option) = options.next() {
+        match option.as_str() {
+            "--samples" => samples = options.next().and_then(|v| v.parse().ok()).expect(usage),
+            "--seed" => seed = options.next().and_then(|v| v.parse().ok()).expect(usage),
+            _ => panic!("unknown option {option}"),
+        }
+    }
+    let content = fs::read_to_string(path).expect("could not read circuit file");
+    match puzzle24::check_equivalence(&content, &reference, samples, seed) {
+        Ok(report) => print!("{report}"),
+        Err(err) => eprintln!("{err}"),
+    }
+}
+
+/// Prints statistics of a day 24 circuit for `analyse <file> [--wires]`.
+fn analyse_circuit(args: &[String]) {
+    let usage = "usage: analyse <file> [--wires]";
+    let path = args.first().expect(usage);
+    let wires = match args.get(1).map(String::as_str) {
+        Some("--wires") => true,
+        Some(option) => panic!("unknown option {option}"),
+        None => false,
+    };
+    let content = fs::read_to_string(path).expect("could not read circuit file");
+    match puzzle24::analyse(&content, wires) {
+        Ok(report) => print!("{report}"),
+        Err(err) => eprintln!("{err}"),
+    }
+}
+
+/// Prints a simplified day 24 circuit for `optimise <file>`.
+fn optimise_circuit(args: &[String]) {
+    let path = args.first().expect("usage: optimise <file>");
+    let content = fs::read_to_string(path).expect("could not read circuit file");
+    match puzzle24::optimise(&content) {
+        Ok(circuit) => print!("{circuit}"),
+        Err(err) => eprintln!("{err}"),
+    }
+}
+
+/// Prints the button presses of every layer for
+/// `presses <file> [--robots <n>] [--keypad <file>] [--directional <file>]`.
+fn print_presses(args: &[String]) {
+    let usage = "usage: presses <file> [--robots <n>] [--keypad <file>] [--directional <file>]";
+    let path = args.first().expect(usage);
+    let read_layout = |path: Option<&String>| {
+        fs::read_to_string(path.expect(usage)).expect("could not read keypad layout")
+    };
+    let (mut robots, mut keypad, mut directional) = (2, None, None);
+    let mut options = args[1..].iter();
+    while let Some(option) = options.next() {
+        match option.as_str() {
+            "--robots" => robots = options.next().and_then(|v| v.parse().ok()).expect(usage),
+            "--keypad" => keypad = Some(read_layout(options.next())),
+            "--directional" => directional = Some(read_layout(options.next())),
+            _ => panic!("unknown option {option}"),
+        }
+    }
+    let content = fs::read_to_string(path).expect("could not read codes file");
+    match puzzle21::sequences(&content, robots, keypad.as_ref(), directional.as_ref()) {
+        Ok(report) => print!("{report}"),
+        Err(err) => eprintln!("{err}"),
+    }
+}
+
+/// Prints exact day 21 costs by stack depth for
+/// `costs <file> --depth <n> [--step <s>] [--keypad <file>] [--directional <file>]`.
+fn print_costs(args: &[String]) {
+    let usage =
+        "usage: costs <file> --depth <n> [--step <s>] [--keypad <file>] [--directional <file>]";
+    let path = args.first().expect(usage);
+    let read_layout = |path: Option<&String>| {
+        fs::read_to_string(path.expect(usage)).expect("could not read keypad layout")
+    };
+    let (mut depth, mut step, mut keypad, mut directional) = (None, 1, None, None);
+    let mut options = args[1..].iter();
+    while let Some(option) = options.next() {
+        match option.as_str() {
+            "--depth" => depth = options.next().and_then(|v| v.parse().ok()),
+            "--step" => step = options.next().and_then(|v| v.parse().ok()).expect(usage),
+            "--keypad" => keypad = Some(read_layout(options.next())),
+            "--directional" => directional = Some(read_layout(options.next())),
+            _ => panic!("unknown option {option}"),
+        }
+    }
+    let content = fs::read_to_string(path).expect("could not read codes file");
+    let table = puzzle21::cost_table(
+        &content,
+        depth.expect(usage),
+        step,
+        keypad.as_ref(),
+        directional.as_ref(),
+    );
+    match table {
+        Ok(table) => print!("{table}"),
+        Err(err) => eprintln!("{err}"),
+    }
+}
+
+/// Validates the day 21 solver against a breadth first search for
+/// `check-keypads [--cases <n>] [--seed <s>]`.
+fn check_keypads(args: &[String]) {
+    let usage = "usage: check-keypads [--cases <n>] [--seed <s>]";
+    let (mut cases, mut seed) = (1000, 0);
+    let mut options = args.iter();
+    while let Some(option) = options.next() {
+        match option.as_str() {
+            "--cases" => cases = options.next().and_then(|v| v.parse().ok()).expect(usage),
+            "--seed" => seed = options.next().and_then(|v| v.parse().ok()).expect(usage),
+            _ => panic!("unknown option {option}"),
+        }
+    }
+    print!("{}", puzzle21::check_solver(cases, seed));
+}
+
+/// Scores a day 16 maze with other move costs, counts and lists its best routes for
+/// `maze <file> [--step <n>] [--turn <n>] [--facing <direction>] [--routes <n>] [--render]`.
+fn score_maze(args: &[String]) {
+    let usage = "usage: maze <file> [--step <n>] [--turn <n>] [--facing <up|right|down|left>] \
+                 [--routes <n>] [--render]";
+    let path = args.first().expect(usage);
+    let (mut step, mut turn, mut facing, mut routes, mut render) = (1, 1000, None, 0, false);
+    let mut options = args[1..].iter();
+    while let Some(option) = options.next() {
+        match option.as_str() {
+            "--step" => step = options.next().and_then(|v| v.parse().ok()).expect(usage),
+            "--turn" => turn = options.next().and_then(|v| v.parse().ok()).expect(usage),
+            "--facing" => {
+                facing = Some(
+                    options
+                        .next()
+                        .and_then(|v| puzzle16::Direction::parse(v))
+                        .expect(usage),
+                )
+            }
+            "--routes" => routes = options.next().and_then(|v| v.parse().ok()).expect(usage),
+            "--render" => render = true,
+            _ => panic!("unknown option {option}"),
+        }
+    }
+    let content = fs::read_to_string(path).expect("could not read maze file");
+    print!(
+        "{}",
+        puzzle16::scores(&content, step, turn, facing, routes, render)
+    );
+}
+
+/// Explores a day 18 memory space of any size, finds the first byte cutting off the exit for
+/// `bytes <file> [--size <width>x<height>] [--bytes <n>] [--after <k>]`.
+fn explore_memory(args: &[String]) {
+    let usage = "usage: bytes <file> [--size <width>x<height>] [--bytes <n>] [--after <k>]";
+    let path = args.first().expect(usage);
+    let (mut size, mut bytes, mut after) = (None, None, None);
+    let mut options = args[1..].iter();
+    while let Some(option) = options.next() {
+        match option.as_str() {
+            "--size" => {
+                size = Some(
+                    options
+                        .next()
+                        .and_then(|v| puzzle18::parse_size(v))
+                        .expect(usage),
+                )
+            }
+            "--bytes" => bytes = Some(options.next().and_then(|v| v.parse().ok()).expect(usage)),
+            "--after" => after = Some(options.next().and_then(|v| v.parse().ok()).expect(usage)),
+            _ => panic!("unknown option {option}"),
+        }
+    }
+    let content = fs::read_to_string(path).expect("could not read bytes file");
+    match puzzle18::explore(&content, size, bytes, after) {
+        Ok(report) => print!("{report}"),
+        Err(err) => eprintln!("{err}"),
+    }
+}
+
 fn main() {
     let puzzles = get_all_puzzles();
 
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("--visualize") => return visualize::run(&puzzles, &args[1..]),
+        Some("--graph") => return export_graph(&puzzles, &args[1..]),
+        Some("generate") => return generate_input(&args[1..]),
+        Some("fuzz") => return fuzz::run(&puzzles, &args[1..]),
+        Some("disassemble") => return disassemble_program(&args[1..]),
+        Some("assemble") => return assemble_program(&args[1..]),
+        Some("debug") => return debug_program(&args[1..]),
+        Some("symbolic") => return analyse_program(&args[1..]),
+        Some("simulate") => return simulate_circuit(&args[1..]),
+        Some("export") => return export_circuit(&args[1..]),
+        Some("equivalence") => return check_circuit(&args[1..]),
+        Some("analyse") => return analyse_circuit(&args[1..]),
+        Some("optimise") => return optimise_circuit(&args[1..]),
+        Some("presses") => return print_presses(&args[1..]),
+        Some("costs") => return print_costs(&args[1..]),
+        Some("check-keypads") => return check_keypads(&args[1..]),
+        Some("maze") => return score_maze(&args[1..]),
+        Some("bytes") => return explore_memory(&args[1..]),
+        _ => {}
+    }
+
     // execute last puzzle for development
     let content = read_day(puzzles.len() as u8);
     run_puzzle(content, puzzles.last().unwrap());
@@ -0,0 +1,374 @@
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::{Add, Mul};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NumberTheoryError {
+    Overflow,
+    NoSolution,
+    Singular,
+}
+
+type Result<T> = std::result::Result<T, NumberTheoryError>;
+
+/// Signed integers the number theory helpers work on, all arithmetic is checked.
+pub trait Integer: Copy + Ord + Debug {
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+    fn checked_div(self, other: Self) -> Option<Self>;
+    fn checked_rem_euclid(self, other: Self) -> Option<Self>;
+    fn checked_abs(self) -> Option<Self>;
+}
+
+macro_rules! impl_integer {
+    ($($t:ty),*) => {
+        $(impl Integer for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            fn checked_add(self, other: Self) -> Option<Self> {
+                <$t>::checked_add(self, other)
+            }
+            fn checked_sub(self, other: Self) -> Option<Self> {
+                <$t>::checked_sub(self, other)
+            }
+            fn checked_mul(self, other: Self) -> Option<Self> {
+                <$t>::checked_mul(self, other)
+            }
+            fn checked_div(self, other: Self) -> Option<Self> {
+                <$t>::checked_div(self, other)
+            }
+            fn checked_rem_euclid(self, other: Self) -> Option<Self> {
+                <$t>::checked_rem_euclid(self, other)
+            }
+            fn checked_abs(self) -> Option<Self> {
+                <$t>::checked_abs(self)
+            }
+        })*
+    };
+}
+
+impl_integer!(i64, i128);
+
+fn add<T: Integer>(a: T, b: T) -> Result<T> {
+    a.checked_add(b).ok_or(NumberTheoryError::Overflow)
+}
+
+fn sub<T: Integer>(a: T, b: T) -> Result<T> {
+    a.checked_sub(b).ok_or(NumberTheoryError::Overflow)
+}
+
+fn mul<T: Integer>(a: T, b: T) -> Result<T> {
+    a.checked_mul(b).ok_or(NumberTheoryError::Overflow)
+}
+
+/// Exact division, fails if `b` does not divide `a`.
+fn div_exact<T: Integer>(a: T, b: T) -> Result<T> {
+    if b == T::ZERO {
+        return Err(NumberTheoryError::Singular);
+    }
+    if a.checked_rem_euclid(b).ok_or(NumberTheoryError::Overflow)? != T::ZERO {
+        return Err(NumberTheoryError::NoSolution);
+    }
+    a.checked_div(b).ok_or(NumberTheoryError::Overflow)
+}
+
+/// Greatest common divisor, always non-negative.
+pub fn gcd<T: Integer>(a: T, b: T) -> Result<T> {
+    let (mut a, mut b) = (a, b);
+    while b != T::ZERO {
+        let remainder = a.checked_rem_euclid(b).ok_or(NumberTheoryError::Overflow)?;
+        (a, b) = (b, remainder);
+    }
+    a.checked_abs().ok_or(NumberTheoryError::Overflow)
+}
+
+pub fn lcm<T: Integer>(a: T, b: T) -> Result<T> {
+    if a == T::ZERO || b == T::ZERO {
+        return Ok(T::ZERO);
+    }
+    let a = a.checked_abs().ok_or(NumberTheoryError::Overflow)?;
+    let b = b.checked_abs().ok_or(NumberTheoryError::Overflow)?;
+    mul(div_exact(a, gcd(a, b)?)?, b)
+}
+
+/// Extended Euclid, returns `(g, x, y)` with `a * x + b * y == g == gcd(a, b)`.
+pub fn extended_gcd<T: Integer>(a: T, b: T) -> Result<(T, T, T)> {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_s, mut s) = (T::ONE, T::ZERO);
+    let (mut old_t, mut t) = (T::ZERO, T::ONE);
+    while r != T::ZERO {
+        let quotient = old_r.checked_div(r).ok_or(NumberTheoryError::Overflow)?;
+        (old_r, r) = (r, sub(old_r, mul(quotient, r)?)?);
+        (old_s, s) = (s, sub(old_s, mul(quotient, s)?)?);
+        (old_t, t) = (t, sub(old_t, mul(quotient, t)?)?);
+    }
+    if old_r < T::ZERO {
+        let neg = |v: T| sub(T::ZERO, v);
+        return Ok((neg(old_r)?, neg(old_s)?, neg(old_t)?));
+    }
+    Ok((old_r, old_s, old_t))
+}
+
+/// Inverse of `a` modulo `m` in `0..m`, if `a` and `m` are coprime.
+pub fn mod_inverse<T: Integer>(a: T, m: T) -> Result<T> {
+    let (g, x, _) = extended_gcd(a, m)?;
+    if g != T::ONE {
+        return Err(NumberTheoryError::NoSolution);
+    }
+    x.checked_rem_euclid(m).ok_or(NumberTheoryError::Overflow)
+}
+
+/// Chinese remainder theorem for `(residue, modulus)` pairs, moduli need not be coprime.
+/// Returns the smallest non-negative solution together with the combined modulus.
+pub fn crt<T: Integer>(congruences: &[(T, T)]) -> Result<(T, T)> {
+    let mut result = (T::ZERO, T::ONE);
+    for &(residue, modulus) in congruences {
+        if modulus <= T::ZERO {
+            return Err(NumberTheoryError::NoSolution);
+        }
+        let (r1, m1) = result;
+        let residue = residue
+            .checked_rem_euclid(modulus)
+            .ok_or(NumberTheoryError::Overflow)?;
+        let g = gcd(m1, modulus)?;
+        let diff = sub(residue, r1)?;
+        // r1 + m1 * k == residue (mod modulus)  <=>  (m1 / g) * k == diff / g (mod modulus / g)
+        let reduced = div_exact(modulus, g)?;
+        let k = mul(
+            div_exact(diff, g)?.checked_rem_euclid(reduced).unwrap(),
+            mod_inverse(
+                div_exact(m1, g)?.checked_rem_euclid(reduced).unwrap(),
+                reduced,
+            )?,
+        )?
+        .checked_rem_euclid(reduced)
+        .unwrap();
+        let combined = mul(m1, reduced)?;
+        let value = add(r1, mul(m1, k)?)?;
+        result = (value.checked_rem_euclid(combined).unwrap(), combined);
+    }
+    Ok(result)
+}
+
+/// Determinant via fraction free Bareiss elimination.
+fn determinant<T: Integer>(mut matrix: Vec<Vec<T>>) -> Result<T> {
+    let n = matrix.len();
+    let mut sign = T::ONE;
+    let mut previous = T::ONE;
+    for k in 0..n {
+        if matrix[k][k] == T::ZERO {
+            match (k + 1..n).find(|&row| matrix[row][k] != T::ZERO) {
+                Some(row) => {
+                    matrix.swap(k, row);
+                    sign = sub(T::ZERO, sign)?;
+                }
+                None => return Ok(T::ZERO),
+            }
+        }
+        for i in k + 1..n {
+            for j in k + 1..n {
+                let value = sub(
+                    mul(matrix[i][j], matrix[k][k])?,
+                    mul(matrix[i][k], matrix[k][j])?,
+                )?;
+                // Bareiss guarantees this division is exact
+                matrix[i][j] = div_exact(value, previous)?;
+            }
+        }
+        previous = matrix[k][k];
+    }
+    mul(sign, matrix[n - 1][n - 1])
+}
+
+/// Solves `a * x == b` for an integer vector `x` with Cramer's rule.
+/// Singular systems and systems with only fractional solutions are reported as errors.
+pub fn solve_linear_system<T: Integer>(a: &[Vec<T>], b: &[T]) -> Result<Vec<T>> {
+    assert!(
+        a.len() == b.len() && a.iter().all(|row| row.len() == b.len()),
+        "system must be square"
+    );
+    if a.is_empty() {
+        return Ok(Vec::new());
+    }
+    let det = determinant(a.to_vec())?;
+    if det == T::ZERO {
+        return Err(NumberTheoryError::Singular);
+    }
+    (0..b.len())
+        .map(|column| {
+            let replaced: Vec<Vec<T>> = a
+                .iter()
+                .zip(b)
+                .map(|(row, &value)| {
+                    let mut row = row.clone();
+                    row[column] = value;
+                    row
+                })
+                .collect();
+            div_exact(determinant(replaced)?, det)
+        })
+        .collect()
+}
+
+/// Unsigned integer of any size, for results that outgrow `u128`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BigUint {
+    /// Base 2^64 digits, least significant first, without leading zeros.
+    limbs: Vec<u64>,
+}
+
+impl From<u64> for BigUint {
+    fn from(value: u64) -> Self {
+        let limbs = if value == 0 { Vec::new() } else { vec![value] };
+        Self { limbs }
+    }
+}
+
+impl BigUint {
+    fn normalize(mut self) -> Self {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+        self
+    }
+}
+
+impl Add for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: &BigUint) -> BigUint {
+        let mut limbs = Vec::with_capacity(self.limbs.len().max(other.limbs.len()) + 1);
+        let mut carry = false;
+        for i in 0..self.limbs.len().max(other.limbs.len()) {
+            let a = self.limbs.get(i).copied().unwrap_or(0);
+            let b = other.limbs.get(i).copied().unwrap_or(0);
+            let (sum, overflow_a) = a.overflowing_add(b);
+            let (sum, overflow_b) = sum.overflowing_add(carry as u64);
+            limbs.push(sum);
+            carry = overflow_a || overflow_b;
+        }
+        limbs.push(carry as u64);
+        BigUint { limbs }.normalize()
+    }
+}
+
+impl Mul<u64> for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, factor: u64) -> BigUint {
+        let mut limbs = Vec::with_capacity(self.limbs.len() + 1);
+        let mut carry = 0;
+        for &limb in &self.limbs {
+            let product = limb as u128 * factor as u128 + carry;
+            limbs.push(product as u64);
+            carry = product >> 64;
+        }
+        limbs.push(carry as u64);
+        BigUint { limbs }.normalize()
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for BigUint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // split into base 10^19 digits by repeated division, least significant first
+        const CHUNK: u128 = 10_000_000_000_000_000_000;
+        let mut value = self.clone();
+        let mut chunks = Vec::new();
+        while !value.limbs.is_empty() {
+            let mut remainder = 0;
+            for limb in value.limbs.iter_mut().rev() {
+                let current = remainder << 64 | *limb as u128;
+                *limb = (current / CHUNK) as u64;
+                remainder = current % CHUNK;
+            }
+            chunks.push(remainder as u64);
+            value = value.normalize();
+        }
+        let mut digits = chunks.pop().unwrap_or(0).to_string();
+        for chunk in chunks.iter().rev() {
+            digits.push_str(&format!("{chunk:019}"));
+        }
+        f.pad(&digits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use NumberTheoryError::*;
+
+    #[test]
+    fn gcd_and_lcm() {
+        assert_eq!(gcd(-12i64, 18), Ok(6));
+        assert_eq!(gcd(0i64, 0), Ok(0));
+        assert_eq!(gcd(i64::MIN, -1), Err(Overflow));
+        assert_eq!(lcm(4i64, 6), Ok(12));
+        assert_eq!(lcm(-4i64, 6), Ok(12));
+        assert_eq!(lcm(0i64, 5), Ok(0));
+        assert_eq!(lcm(i64::MIN, -1), Err(Overflow));
+        assert_eq!(lcm(i64::MAX, i64::MAX - 1), Err(Overflow));
+    }
+
+    #[test]
+    fn extended_gcd_gives_bezout_coefficients() {
+        assert_eq!(extended_gcd(240i64, 46), Ok((2, -9, 47)));
+        for (a, b) in [(-12i64, 18), (7, -3), (0, 5), (5, 0)] {
+            let (g, x, y) = extended_gcd(a, b).unwrap();
+            assert_eq!(Ok(g), gcd(a, b));
+            assert_eq!(a * x + b * y, g, "{a} {b}");
+        }
+    }
+
+    #[test]
+    fn mod_inverse_needs_coprime_numbers() {
+        assert_eq!(mod_inverse(3i64, 11), Ok(4));
+        assert_eq!(mod_inverse(-3i64, 11), Ok(7));
+        assert_eq!(mod_inverse(6i64, 9), Err(NoSolution));
+    }
+
+    #[test]
+    fn crt_combines_congruences() {
+        assert_eq!(crt(&[(2i64, 3), (3, 5), (2, 7)]), Ok((23, 105)));
+        // moduli sharing a factor with consistent residues
+        assert_eq!(crt(&[(2i64, 6), (8, 10)]), Ok((8, 30)));
+        // odd and even at once
+        assert_eq!(crt(&[(1i64, 4), (2, 6)]), Err(NoSolution));
+        assert_eq!(crt(&[(1i64, 0)]), Err(NoSolution));
+        assert_eq!(crt::<i64>(&[]), Ok((0, 1)));
+    }
+
+    #[test]
+    fn solves_linear_systems() {
+        let a = [vec![94i64, 22], vec![34, 67]];
+        assert_eq!(solve_linear_system(&a, &[8400, 5400]), Ok(vec![80, 40]));
+        let singular = [vec![1i64, 2], vec![2, 4]];
+        assert_eq!(solve_linear_system(&singular, &[3, 6]), Err(Singular));
+        let fractional = [vec![2i64, 0], vec![0, 2]];
+        assert_eq!(solve_linear_system(&fractional, &[1, 2]), Err(NoSolution));
+        let large = [vec![i64::MAX, 2], vec![2, i64::MAX]];
+        assert_eq!(solve_linear_system(&large, &[1, 1]), Err(Overflow));
+        // the same system fits in 128 bits
+        let large = [vec![i64::MAX as i128, 2], vec![2, i64::MAX as i128]];
+        assert_eq!(solve_linear_system(&large, &[1, 1]), Err(NoSolution));
+    }
+}
@@ -1,9 +1,54 @@
+use crate::graph::Graph;
+use std::fmt::{Debug, Display, Formatter, Write};
 use std::{env, fs};
 
+/// Lazily produced snapshots of a simulation, one rendered board per step.
+pub type Frames = Box<dyn Iterator<Item = String>>;
+
+/// Malformed puzzle input, `line` is zero based.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid input in line {}: {}",
+            self.line + 1,
+            self.message
+        )
+    }
+}
+
 pub trait Aoc2024 {
     fn name(&self) -> String;
     fn solve_a(&self, input: &String) -> String;
     fn solve_b(&self, input: &String) -> String;
+
+    /// Only parses the input, so parsers can be checked without solving the puzzle.
+    fn parse(&self, input: &String) -> Result<(), ParseError>;
+
+    /// Step-by-step replay of the simulation behind part a or b, if the puzzle has one.
+    fn frames(&self, _input: &String, _part_b: bool) -> Option<Frames> {
+        None
+    }
+
+    /// The puzzle input as a graph with printable node labels, if it describes one.
+    fn graph(&self, _input: &String) -> Option<Graph<String>> {
+        None
+    }
 }
 
 pub fn read_day(day: u8) -> String {
@@ -12,3 +57,15 @@ pub fn read_day(day: u8) -> String {
     let f = fs::read_to_string(&filepath);
     f.expect(format!("could not open input file @ {:?}", filepath).as_str())
 }
+
+/// Renders a grid row by row using the `Debug` impl of its cells.
+pub fn render_grid<T: Debug>(grid: &[Vec<T>]) -> String {
+    let mut out = String::new();
+    for row in grid {
+        for el in row {
+            write!(out, "{:?}", el).unwrap();
+        }
+        out.push('\n');
+    }
+    out
+}
@@ -1,22 +1,28 @@
 use std::iter::zip;
 
-use crate::prelude::Aoc2024;
+use crate::prelude::{Aoc2024, ParseError};
 
-fn get_lists(contents: &String) -> (Vec<i64>, Vec<i64>) {
+/// Both lists of location IDs. IDs are non-negative 32 bit numbers, so differences and
+/// similarity scores can't overflow.
+fn get_lists(contents: &str) -> Result<(Vec<i64>, Vec<i64>), ParseError> {
     let (first_list, second_list): (Vec<i64>, Vec<i64>) = contents
         .split("\n")
-        .filter_map(|line| {
-            let mut parts = line
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let parts: Vec<Option<u32>> = line
                 .split_whitespace()
-                .filter_map(|num| num.parse::<i64>().ok());
-            if let (Some(a), Some(b)) = (parts.next(), parts.next()) {
-                Some((a, b))
-            } else {
-                None
+                .map(|num| num.parse::<u32>().ok())
+                .collect();
+            match parts[..] {
+                [Some(a), Some(b)] => Ok((a as i64, b as i64)),
+                _ => Err(ParseError::new(i, "expected two location IDs")),
             }
         })
+        .collect::<Result<Vec<(i64, i64)>, ParseError>>()?
+        .into_iter()
         .unzip();
-    (first_list, second_list)
+    Ok((first_list, second_list))
 }
 
 pub struct Puzzle1 {}
@@ -27,7 +33,10 @@ impl Aoc2024 for Puzzle1 {
     }
 
     fn solve_a(&self, input: &String) -> String {
-        let (mut first_list, mut second_list) = get_lists(input);
+        let (mut first_list, mut second_list) = match get_lists(input) {
+            Ok(lists) => lists,
+            Err(err) => return err.to_string(),
+        };
         first_list.sort_unstable();
         second_list.sort_unstable();
         let result: i64 = zip(first_list, second_list)
@@ -37,7 +46,10 @@ impl Aoc2024 for Puzzle1 {
     }
 
     fn solve_b(&self, input: &String) -> String {
-        let (mut first_list, mut second_list) = get_lists(input);
+        let (mut first_list, mut second_list) = match get_lists(input) {
+            Ok(lists) => lists,
+            Err(err) => return err.to_string(),
+        };
         first_list.sort_unstable();
         second_list.sort_unstable();
 
@@ -66,4 +78,34 @@ impl Aoc2024 for Puzzle1 {
         }
         result.to_string()
     }
+
+    fn parse(&self, input: &String) -> Result<(), ParseError> {
+        get_lists(input).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate;
+
+    #[test]
+    fn parses_generated_input() {
+        let input = generate::generate(1, generate::default_size(1), 1).unwrap();
+        assert_eq!(Puzzle1 {}.parse(&input), Ok(()));
+    }
+
+    #[test]
+    fn rejects_broken_input() {
+        let inputs = [
+            // a location ID that is not a number
+            ("3   4\n4   x\n", 1),
+            // three IDs on a line
+            ("3   4   5\n", 0),
+        ];
+        for (input, line) in inputs {
+            let result = Puzzle1 {}.parse(&input.to_string());
+            assert_eq!(result.map_err(|err| err.line), Err(line), "{input:?}");
+        }
+    }
 }
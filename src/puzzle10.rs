@@ -1,4 +1,4 @@
-use crate::prelude::Aoc2024;
+use crate::prelude::{Aoc2024, ParseError};
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
@@ -34,7 +34,8 @@ impl Node {
 
 pub struct Puzzle10 {}
 
-fn flood_fill(input: &String) -> Vec<Vec<RefCell<Node>>> {
+/// The topographic map, a square of heights `0` to `9`.
+fn get_board(input: &str) -> Result<Vec<Vec<RefCell<Node>>>, ParseError> {
     let board: Vec<Vec<RefCell<Node>>> = input
         .lines()
         .map(str::trim)
@@ -43,11 +44,23 @@ fn flood_fill(input: &String) -> Vec<Vec<RefCell<Node>>> {
             line.as_bytes()
                 .iter()
                 .enumerate()
-                .map(|(y, el)| Node::new(x, y, el).into())
+                .map(|(y, el)| match el {
+                    b'0'..=b'9' => Ok(Node::new(x, y, el).into()),
+                    _ => Err(ParseError::new(
+                        x,
+                        format!("expected a height but got {:?}", *el as char),
+                    )),
+                })
                 .collect()
         })
-        .collect();
+        .collect::<Result<_, _>>()?;
+    if let Some(i) = board.iter().position(|row| row.len() != board.len()) {
+        return Err(ParseError::new(i, "map must be square"));
+    }
+    Ok(board)
+}
 
+fn flood_fill(board: Vec<Vec<RefCell<Node>>>) -> Vec<Vec<RefCell<Node>>> {
     let mut current_nodes: HashMap<Position, &RefCell<Node>> = HashMap::new();
     let size = board.len();
     for i in 0..size {
@@ -108,7 +121,10 @@ impl Aoc2024 for Puzzle10 {
     }
 
     fn solve_a(&self, input: &String) -> String {
-        let nodes = flood_fill(input);
+        let nodes = match get_board(input) {
+            Ok(board) => flood_fill(board),
+            Err(err) => return err.to_string(),
+        };
         let result: usize = nodes
             .iter()
             .map(|line| {
@@ -121,11 +137,44 @@ impl Aoc2024 for Puzzle10 {
     }
 
     fn solve_b(&self, input: &String) -> String {
-        let nodes = flood_fill(input);
+        let nodes = match get_board(input) {
+            Ok(board) => flood_fill(board),
+            Err(err) => return err.to_string(),
+        };
         let result: usize = nodes
             .iter()
             .map(|line| line.iter().map(|node| node.borrow().rank).sum::<usize>())
             .sum();
         result.to_string()
     }
+
+    fn parse(&self, input: &String) -> Result<(), ParseError> {
+        get_board(input).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate;
+
+    #[test]
+    fn parses_generated_input() {
+        let input = generate::generate(10, generate::default_size(10), 1).unwrap();
+        assert_eq!(Puzzle10 {}.parse(&input), Ok(()));
+    }
+
+    #[test]
+    fn rejects_broken_input() {
+        let inputs = [
+            // a truncated last row used to index past the map
+            ("8987\n7877\n6767\n567", 3),
+            // a height that is not a digit
+            ("0123\n1x34\n8765\n9876\n", 1),
+        ];
+        for (input, line) in inputs {
+            let result = Puzzle10 {}.parse(&input.to_string());
+            assert_eq!(result.map_err(|err| err.line), Err(line), "{input:?}");
+        }
+    }
 }
@@ -1,4 +1,4 @@
-use crate::prelude::Aoc2024;
+use crate::prelude::{Aoc2024, ParseError};
 use std::collections::HashMap;
 
 fn blink_hashmap(stones: &HashMap<i64, i64>, target_stones: &mut HashMap<i64, i64>) {
@@ -33,21 +33,24 @@ fn blink_hashmap(stones: &HashMap<i64, i64>, target_stones: &mut HashMap<i64, i6
 
 pub struct Puzzle11 {}
 
-fn get_stones_as_hashmap(input: &String) -> HashMap<i64, i64> {
+/// The engraved numbers on the first line, non-negative 32 bit numbers so multiplying them
+/// by 2024 can't overflow.
+fn get_stones_as_hashmap(input: &str) -> Result<HashMap<i64, i64>, ParseError> {
     let mut lookup: HashMap<i64, i64> = HashMap::new();
 
     let stones: Vec<i64> = input
         .lines()
         .next()
-        .unwrap()
-        .split(" ")
-        .filter_map(|num| num.parse::<i64>().ok())
-        .collect();
+        .ok_or_else(|| ParseError::new(0, "no stones"))?
+        .split_whitespace()
+        .map(|num| num.parse::<u32>().map(i64::from))
+        .collect::<Result<_, _>>()
+        .map_err(|_| ParseError::new(0, "expected numbers separated by spaces"))?;
 
     for stone in &stones {
         lookup.insert(*stone, 1);
     }
-    lookup
+    Ok(lookup)
 }
 
 impl Aoc2024 for Puzzle11 {
@@ -56,7 +59,10 @@ impl Aoc2024 for Puzzle11 {
     }
 
     fn solve_a(&self, input: &String) -> String {
-        let mut lookup = get_stones_as_hashmap(input);
+        let mut lookup = match get_stones_as_hashmap(input) {
+            Ok(lookup) => lookup,
+            Err(err) => return err.to_string(),
+        };
         let mut lookup_target = lookup.clone();
 
         for _ in 0..25 {
@@ -71,7 +77,10 @@ impl Aoc2024 for Puzzle11 {
     }
 
     fn solve_b(&self, input: &String) -> String {
-        let mut lookup = get_stones_as_hashmap(input);
+        let mut lookup = match get_stones_as_hashmap(input) {
+            Ok(lookup) => lookup,
+            Err(err) => return err.to_string(),
+        };
         let mut lookup_target = lookup.clone();
 
         for _ in 0..75 {
@@ -84,4 +93,34 @@ impl Aoc2024 for Puzzle11 {
         let result: i64 = lookup.iter().map(|(_, count)| count).sum();
         result.to_string()
     }
+
+    fn parse(&self, input: &String) -> Result<(), ParseError> {
+        get_stones_as_hashmap(input).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate;
+
+    #[test]
+    fn parses_generated_input() {
+        let input = generate::generate(11, generate::default_size(11), 1).unwrap();
+        assert_eq!(Puzzle11 {}.parse(&input), Ok(()));
+    }
+
+    #[test]
+    fn rejects_broken_input() {
+        let inputs = [
+            // an empty input used to unwrap the missing first line
+            ("", 0),
+            // a stone that is not a number
+            ("125 17 x\n", 0),
+        ];
+        for (input, line) in inputs {
+            let result = Puzzle11 {}.parse(&input.to_string());
+            assert_eq!(result.map_err(|err| err.line), Err(line), "{input:?}");
+        }
+    }
 }
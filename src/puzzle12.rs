@@ -1,4 +1,4 @@
-use crate::prelude::Aoc2024;
+use crate::prelude::{Aoc2024, ParseError};
 use std::fmt::{Debug, Formatter, Write};
 
 #[derive(PartialEq, Eq, Clone, Copy)]
@@ -205,12 +205,27 @@ fn flood_fill_sides(board: &mut Vec<Vec<Field>>, x: i32, y: i32) -> (usize, usiz
 
 pub struct Puzzle12 {}
 
-fn get_board(input: &String) -> Vec<Vec<Field>> {
-    input
+/// The garden, a square of plant types given as uppercase letters.
+fn get_board(input: &str) -> Result<Vec<Vec<Field>>, ParseError> {
+    let board: Vec<Vec<Field>> = input
         .lines()
         .map(str::trim)
-        .map(|line| line.as_bytes().iter().map(Field::from).collect())
-        .collect()
+        .enumerate()
+        .map(
+            |(i, line)| match line.bytes().find(|el| !el.is_ascii_uppercase()) {
+                Some(el) => Err(ParseError::new(
+                    i,
+                    format!("unknown plant {:?}", el as char),
+                )),
+                None => Ok(line.as_bytes().iter().map(Field::from).collect()),
+            },
+        )
+        .collect::<Result<_, _>>()?;
+    // the flood fill assumes a square garden
+    if let Some(i) = board.iter().position(|row| row.len() != board.len()) {
+        return Err(ParseError::new(i, "garden must be square"));
+    }
+    Ok(board)
 }
 
 impl Aoc2024 for Puzzle12 {
@@ -219,7 +234,10 @@ impl Aoc2024 for Puzzle12 {
     }
 
     fn solve_a(&self, input: &String) -> String {
-        let mut board = get_board(input);
+        let mut board = match get_board(input) {
+            Ok(board) => board,
+            Err(err) => return err.to_string(),
+        };
 
         let mut cost = 0;
         while let Some((x, y)) = board.iter().enumerate().find_map(|(row_idx, row)| {
@@ -234,7 +252,10 @@ impl Aoc2024 for Puzzle12 {
     }
 
     fn solve_b(&self, input: &String) -> String {
-        let mut board = get_board(input);
+        let mut board = match get_board(input) {
+            Ok(board) => board,
+            Err(err) => return err.to_string(),
+        };
 
         let mut cost = 0;
         while let Some((x, y)) = board.iter().enumerate().find_map(|(row_idx, row)| {
@@ -247,4 +268,34 @@ impl Aoc2024 for Puzzle12 {
         }
         cost.to_string()
     }
+
+    fn parse(&self, input: &String) -> Result<(), ParseError> {
+        get_board(input).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate;
+
+    #[test]
+    fn parses_generated_input() {
+        let input = generate::generate(12, generate::default_size(12), 1).unwrap();
+        assert_eq!(Puzzle12 {}.parse(&input), Ok(()));
+    }
+
+    #[test]
+    fn rejects_broken_input() {
+        let inputs = [
+            // a truncated last row
+            ("AAAA\nBBBD\nBBCC\nEE\n", 3),
+            // a plot that is not a letter
+            ("AAAA\nBB1D\nBBCC\nEEEC\n", 1),
+        ];
+        for (input, line) in inputs {
+            let result = Puzzle12 {}.parse(&input.to_string());
+            assert_eq!(result.map_err(|err| err.line), Err(line), "{input:?}");
+        }
+    }
 }
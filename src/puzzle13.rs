@@ -1,4 +1,5 @@
-use crate::prelude::Aoc2024;
+use crate::number_theory::{solve_linear_system, NumberTheoryError};
+use crate::prelude::{Aoc2024, ParseError};
 use ndarray::prelude::*;
 
 #[derive(Clone, Debug)]
@@ -9,69 +10,91 @@ struct ClawMachine {
 }
 
 impl ClawMachine {
-    fn solve_a(&self) -> Option<i64> {
-        let y = (self.target[0] * self.a[1] - self.target[1] * self.a[0])
-            / (self.b[0] * self.a[1] - self.b[1] * self.a[0]);
-        let x = (self.target[0] - self.b[0] * y) / self.a[0];
-
-        let a_presses = x;
-        let b_presses = y;
-        if a_presses * self.a[0] + b_presses * self.b[0] == self.target[0]
-            && a_presses * self.a[1] + b_presses * self.b[1] == self.target[1]
-        {
-            Some(3 * a_presses + b_presses)
-        } else {
-            None
+    /// Token cost of reaching `target`, `None` if it can not be hit with whole button presses.
+    /// Only overflows are errors.
+    fn cost(&self, target: &Array1<i64>) -> Result<Option<i64>, NumberTheoryError> {
+        let presses = match solve_linear_system(
+            &[vec![self.a[0], self.b[0]], vec![self.a[1], self.b[1]]],
+            &[target[0], target[1]],
+        ) {
+            Ok(presses) => presses,
+            Err(NumberTheoryError::Overflow) => return Err(NumberTheoryError::Overflow),
+            Err(_) => return Ok(None),
+        };
+        let (a_presses, b_presses) = (presses[0], presses[1]);
+        if a_presses < 0 || b_presses < 0 {
+            return Ok(None);
         }
+        let cost = a_presses
+            .checked_mul(3)
+            .and_then(|cost| cost.checked_add(b_presses));
+        cost.map(Some).ok_or(NumberTheoryError::Overflow)
     }
 
-    fn solve_b(&self) -> Option<i64> {
+    fn solve_a(&self) -> Result<Option<i64>, NumberTheoryError> {
+        self.cost(&self.target)
+    }
+
+    fn solve_b(&self) -> Result<Option<i64>, NumberTheoryError> {
         let target: Array1<i64> = array![10000000000000, 10000000000000] + self.target.view();
-        let y = (target[0] * self.a[1] - target[1] * self.a[0])
-            / (self.b[0] * self.a[1] - self.b[1] * self.a[0]);
-        let x = (target[0] - self.b[0] * y) / self.a[0];
-
-        let a_presses = x;
-        let b_presses = y;
-        if a_presses * self.a[0] + b_presses * self.b[0] == target[0]
-            && a_presses * self.a[1] + b_presses * self.b[1] == target[1]
-        {
-            Some(3 * a_presses + b_presses)
-        } else {
-            None
-        }
+        self.cost(&target)
     }
 }
 
-fn parse_button(line: &str) -> Array1<i64> {
-    let parts: Vec<&str> = line.split_whitespace().map(|part| part.trim()).collect();
-    let x_str = parts[2];
-    let y_str = parts[3];
-    let x: i64 = x_str[2..x_str.len() - 1].parse().unwrap();
-    let y: i64 = y_str[2..].parse().unwrap();
-    array![x, y]
-}
+pub struct Puzzle13 {}
 
-fn parse_target(line: &str) -> Array1<i64> {
-    let parts: Vec<&str> = line.split_whitespace().map(|part| part.trim()).collect();
-    let x_str = parts[1];
-    let y_str = parts[2];
-    let x: i64 = x_str[2..x_str.len() - 1].parse().unwrap();
-    let y: i64 = y_str[2..].parse().unwrap();
-    array![x, y]
+/// Sum of the token costs of the machines with a prize.
+fn total_cost(costs: impl Iterator<Item = Result<Option<i64>, NumberTheoryError>>) -> String {
+    let mut total: i64 = 0;
+    for cost in costs {
+        match cost.map(|cost| total.checked_add(cost.unwrap_or(0))) {
+            Ok(Some(sum)) => total = sum,
+            _ => return "Overflow while solving the machines".to_string(),
+        }
+    }
+    total.to_string()
 }
 
-pub struct Puzzle13 {}
+/// Parses `<prefix> X<sign><x>, Y<sign><y>`. Values are non-negative 32 bit numbers, so the
+/// far away prizes of part b and the token costs fit.
+fn parse_pair(line: &str, prefix: &str, sign: char) -> Option<Array1<i64>> {
+    let (x, y) = line.trim().strip_prefix(prefix)?.split_once(", ")?;
+    let value = |value: &str, axis: char| {
+        value
+            .trim()
+            .strip_prefix(axis)?
+            .strip_prefix(sign)?
+            .parse::<u32>()
+            .ok()
+    };
+    Some(array![value(x, 'X')? as i64, value(y, 'Y')? as i64])
+}
 
-fn get_machines(input: &String) -> Vec<ClawMachine> {
-    input
-        .split("\n\n")
+fn get_machines(input: &str) -> Result<Vec<ClawMachine>, ParseError> {
+    let lines: Vec<(usize, &str)> = input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .collect();
+    let last = input.lines().count().saturating_sub(1);
+    lines
+        .chunks(3)
         .map(|machine| {
-            let mut lines = machine.lines();
-            let a = parse_button(lines.next().unwrap());
-            let b = parse_button(lines.next().unwrap());
-            let target = parse_target(lines.next().unwrap());
-            ClawMachine { a, b, target }
+            let parse = |index: usize, prefix: &str, sign: char| {
+                let line = machine.get(index);
+                line.and_then(|&(_, line)| parse_pair(line, prefix, sign))
+                    .ok_or_else(|| {
+                        ParseError::new(
+                            line.map_or(last, |&(i, _)| i),
+                            format!("expected `{prefix} X{sign}<n>, Y{sign}<n>`"),
+                        )
+                    })
+            };
+            Ok(ClawMachine {
+                a: parse(0, "Button A:", '+')?,
+                b: parse(1, "Button B:", '+')?,
+                target: parse(2, "Prize:", '=')?,
+            })
         })
         .collect()
 }
@@ -82,16 +105,77 @@ impl Aoc2024 for Puzzle13 {
     }
 
     fn solve_a(&self, input: &String) -> String {
-        let machines = get_machines(input);
+        let machines = match get_machines(input) {
+            Ok(machines) => machines,
+            Err(err) => return err.to_string(),
+        };
 
-        let res: i64 = machines.iter().filter_map(ClawMachine::solve_a).sum();
-        res.to_string()
+        total_cost(machines.iter().map(ClawMachine::solve_a))
     }
 
     fn solve_b(&self, input: &String) -> String {
-        let machines = get_machines(input);
+        let machines = match get_machines(input) {
+            Ok(machines) => machines,
+            Err(err) => return err.to_string(),
+        };
+
+        total_cost(machines.iter().map(ClawMachine::solve_b))
+    }
+
+    fn parse(&self, input: &String) -> Result<(), ParseError> {
+        get_machines(input).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate;
+
+    #[test]
+    fn parses_generated_input() {
+        let input = generate::generate(13, generate::default_size(13), 1).unwrap();
+        assert_eq!(Puzzle13 {}.parse(&input), Ok(()));
+    }
+
+    #[test]
+    fn rejects_broken_input() {
+        let inputs = [
+            // a mangled prize used to unwrap a failed number parse
+            (
+                "Button A: X+94, Y+34\nButton B: X+22, Y+67\nPrize: X=5668, Y#=\n",
+                2,
+            ),
+            // a machine without its second button and prize
+            ("Button A: X+94, Y+34\n", 0),
+        ];
+        for (input, line) in inputs {
+            let result = Puzzle13 {}.parse(&input.to_string());
+            assert_eq!(result.map_err(|err| err.line), Err(line), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn solves_the_example() {
+        let input = "\
+Button A: X+94, Y+34\nButton B: X+22, Y+67\nPrize: X=8400, Y=5400\n\n\
+Button A: X+26, Y+66\nButton B: X+67, Y+21\nPrize: X=12748, Y=12176\n\n\
+Button A: X+17, Y+86\nButton B: X+84, Y+37\nPrize: X=7870, Y=6450\n\n\
+Button A: X+69, Y+23\nButton B: X+27, Y+71\nPrize: X=18641, Y=10279\n"
+            .to_string();
+        assert_eq!(Puzzle13 {}.solve_a(&input), "480");
+        assert_eq!(Puzzle13 {}.solve_b(&input), "875318608908");
+    }
 
-        let res: i64 = machines.iter().filter_map(ClawMachine::solve_b).sum();
-        res.to_string()
+    #[test]
+    fn reports_overflows() {
+        // the determinant of these buttons does not fit in 64 bits
+        let input = "Button A: X+4294967295, Y+1\nButton B: X+1, Y+4294967295\n\
+                     Prize: X=5, Y=5\n"
+            .to_string();
+        assert_eq!(
+            Puzzle13 {}.solve_a(&input),
+            "Overflow while solving the machines"
+        );
     }
 }
@@ -1,47 +1,75 @@
-use crate::prelude::Aoc2024;
+use crate::number_theory::{crt, lcm};
+use crate::prelude::{Aoc2024, Frames, ParseError};
 use std::fmt::{Debug, Formatter, Write};
 
+#[derive(Clone)]
 struct Vector {
     x: i64,
     y: i64,
 }
 
+#[derive(Clone)]
 struct Robot {
     pos: Vector,
     vel: Vector,
 }
 
+#[derive(Clone)]
 struct Board {
     width: i64,
     height: i64,
     robots: Vec<Robot>,
 }
 
-impl From<&str> for Vector {
-    fn from(value: &str) -> Self {
-        let mut parts = value.split("=").last().unwrap().split(",");
-        let x = parts.next().unwrap().parse::<i64>().unwrap();
-        let y = parts.next().unwrap().parse::<i64>().unwrap();
-        Self { x, y }
+impl Vector {
+    /// Parses `<name>=<x>,<y>`, coordinates are 32 bit so stepping robots can't overflow.
+    fn parse(value: &str, name: &str) -> Option<Self> {
+        let (x, y) = value
+            .strip_prefix(name)?
+            .strip_prefix('=')?
+            .split_once(',')?;
+        Some(Self {
+            x: x.parse::<i32>().ok()?.into(),
+            y: y.parse::<i32>().ok()?.into(),
+        })
     }
 }
 
-impl From<&str> for Robot {
-    fn from(value: &str) -> Self {
+impl Robot {
+    fn parse(value: &str) -> Option<Self> {
         let mut parts = value.split_whitespace();
-        let pos = Vector::from(parts.next().unwrap());
-        let vel = Vector::from(parts.next().unwrap());
-        Self { pos, vel }
+        let (pos, vel) = (parts.next()?, parts.next()?);
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self {
+            pos: Vector::parse(pos, "p")?,
+            vel: Vector::parse(vel, "v")?,
+        })
     }
 }
 
-impl From<&str> for Board {
-    fn from(value: &str) -> Self {
-        Self {
+impl Board {
+    fn parse(value: &str) -> Result<Self, ParseError> {
+        let mut board = Self {
             width: 101,
             height: 103,
-            robots: value.lines().map(Robot::from).collect(),
+            robots: Vec::new(),
+        };
+        for (i, line) in value.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let robot = Robot::parse(line)
+                .ok_or_else(|| ParseError::new(i, "expected `p=<x>,<y> v=<x>,<y>`"))?;
+            if !(0..board.width).contains(&robot.pos.x) || !(0..board.height).contains(&robot.pos.y)
+            {
+                let message = format!("robot is outside the {}x{} room", board.width, board.height);
+                return Err(ParseError::new(i, message));
+            }
+            board.robots.push(robot);
         }
+        Ok(board)
     }
 }
 
@@ -82,6 +110,11 @@ impl Board {
         board
     }
 
+    /// Time after which every robot is back at its start position.
+    fn period(&self) -> i64 {
+        lcm(self.width, self.height).expect("board period overflows")
+    }
+
     fn has_line(&self) -> bool {
         for row in self.to_board() {
             let mut start = None;
@@ -105,6 +138,37 @@ impl Board {
         false
     }
 
+    /// Time within one period at which the robots cluster most.
+    /// x positions repeat every `width` steps and y positions every `height` steps, so the most
+    /// clustered step for each axis is found separately and the two are combined.
+    fn most_clustered(&self) -> Option<i64> {
+        let mut board = self.clone();
+        let mut best_x = (i64::MAX, 0);
+        let mut best_y = (i64::MAX, 0);
+        for i in 0..board.width.max(board.height) {
+            let spread_x = board.spread(|robot| robot.pos.x);
+            let spread_y = board.spread(|robot| robot.pos.y);
+            if i < board.width && spread_x < best_x.0 {
+                best_x = (spread_x, i);
+            }
+            if i < board.height && spread_y < best_y.0 {
+                best_y = (spread_y, i);
+            }
+            board.step(1);
+        }
+        crt(&[(best_x.1, board.width), (best_y.1, board.height)])
+            .ok()
+            .map(|(time, _)| time)
+    }
+
+    /// Scaled variance of the robot coordinates along one axis, low when robots cluster.
+    fn spread(&self, coordinate: fn(&Robot) -> i64) -> i64 {
+        let n = self.robots.len() as i64;
+        let sum: i64 = self.robots.iter().map(coordinate).sum();
+        let sum_squares: i64 = self.robots.iter().map(|r| coordinate(r).pow(2)).sum();
+        n * sum_squares - sum * sum
+    }
+
     fn score(&mut self) -> i64 {
         let mut quad_i = 0;
         let mut quad_ii = 0;
@@ -139,7 +203,10 @@ impl Aoc2024 for Puzzle14 {
     }
 
     fn solve_a(&self, input: &String) -> String {
-        let mut board = Board::from(input.as_str());
+        let mut board = match Board::parse(input) {
+            Ok(board) => board,
+            Err(err) => return err.to_string(),
+        };
 
         board.step(100);
 
@@ -147,13 +214,85 @@ impl Aoc2024 for Puzzle14 {
     }
 
     fn solve_b(&self, input: &String) -> String {
-        let mut board = Board::from(input.as_str());
-        for i in 0..10000 {
+        let mut board = match Board::parse(input) {
+            Ok(board) => board,
+            Err(err) => return err.to_string(),
+        };
+        let start = board.clone();
+        // after one period every robot is back at its start, so there is nothing new to find
+        for seconds in 1..=board.period() {
             board.step(1);
             if board.has_line() {
-                return i.to_string();
+                return seconds.to_string();
+            }
+        }
+        // without a line the picture is where the robots cluster most
+        match start.most_clustered() {
+            Some(seconds) => seconds.to_string(),
+            None => "Not found".to_string(),
+        }
+    }
+
+    fn frames(&self, input: &String, part_b: bool) -> Option<Frames> {
+        let mut board = Board::parse(input).ok()?;
+        let initial = format!("{:?}", board);
+        // part a moves the robots for 100 seconds, part b until they first draw a line
+        let seconds = if part_b { board.period() } else { 100 };
+        let mut found = false;
+        let steps = (0..seconds).map_while(move |_| {
+            if found {
+                return None;
             }
+            board.step(1);
+            found = part_b && board.has_line();
+            Some(format!("{:?}", board))
+        });
+        Some(Box::new(std::iter::once(initial).chain(steps)))
+    }
+
+    fn parse(&self, input: &String) -> Result<(), ParseError> {
+        Board::parse(input).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate;
+
+    #[test]
+    fn parses_generated_input() {
+        let input = generate::generate(14, generate::default_size(14), 1).unwrap();
+        assert_eq!(Puzzle14 {}.parse(&input), Ok(()));
+    }
+
+    #[test]
+    fn reports_the_cluster_without_a_line() {
+        // generated robots gather in a block without drawing a line
+        let input = generate::generate(14, generate::default_size(14), 3).unwrap();
+        assert_eq!(Puzzle14 {}.solve_b(&input), "10161");
+    }
+
+    #[test]
+    fn counts_the_seconds_until_the_line() {
+        // twelve robots with different speeds line up in row 50 after three seconds
+        let input: Vec<String> = (0..12)
+            .map(|k| format!("p={k},{} v=0,{}", 47 - 3 * k, k + 1))
+            .collect();
+        assert_eq!(Puzzle14 {}.solve_b(&input.join("\n")), "3");
+    }
+
+    #[test]
+    fn rejects_broken_input() {
+        let inputs = [
+            // a mangled position used to unwrap a failed number parse
+            ("p=86,32 v=99,23\np=13\u{17}81 v=35,-62\n", 1),
+            // a robot outside the room
+            ("p=0,4 v=3,-3\np=101,0 v=1,1\n", 1),
+        ];
+        for (input, line) in inputs {
+            let result = Puzzle14 {}.parse(&input.to_string());
+            assert_eq!(result.map_err(|err| err.line), Err(line), "{input:?}");
         }
-        "Not found".to_string()
     }
 }
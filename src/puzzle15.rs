@@ -1,4 +1,4 @@
-use crate::prelude::Aoc2024;
+use crate::prelude::{render_grid, Aoc2024, Frames, ParseError};
 use std::cmp::PartialEq;
 use std::fmt::{Debug, Formatter, Write};
 
@@ -41,14 +41,16 @@ impl Direction {
     }
 }
 
-impl From<u8> for Field {
-    fn from(value: u8) -> Self {
+impl TryFrom<u8> for Field {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
-            b'.' => Field::FREE,
-            b'#' => Field::BLOCKED,
-            b'O' => Field::PACKAGE, // Initially no directions visited
-            b'@' => Field::ROBOT,
-            _ => Field::FREE,
+            b'.' => Ok(Field::FREE),
+            b'#' => Ok(Field::BLOCKED),
+            b'O' => Ok(Field::PACKAGE), // Initially no directions visited
+            b'@' => Ok(Field::ROBOT),
+            _ => Err(format!("unknown field {:?}", value as char)),
         }
     }
 }
@@ -77,56 +79,63 @@ fn get_robot(board: &Vec<Vec<Field>>) -> Option<(i32, i32)> {
     None
 }
 
-fn walk_board(board: &Vec<Vec<Field>>, instructions: &Vec<Direction>) -> Vec<Vec<Field>> {
-    let mut board = board.clone();
-    let (mut x, mut y) = get_robot(&board).expect("GUARD not found");
+/// Moves the robot by one instruction, for either board width.
+type StepFn = fn(&mut [Vec<Field>], &mut (i32, i32), &Direction);
 
-    for instruction in instructions {
-        let (dx, dy) = instruction.delta();
-        let next_x = x + dx;
-        let next_y = y + dy;
-        if next_x < 0 || next_x >= board.len() as i32 || next_y < 0 || next_y >= board.len() as i32
-        {
-            continue;
-        }
-        let next_field = board[next_x as usize][next_y as usize];
-        match next_field {
-            Field::BLOCKED => continue,
-            Field::PACKAGE => {
-                let mut blocked = true;
-                let mut next_x = next_x;
-                let mut next_y = next_y;
-                loop {
-                    next_x += dx;
-                    next_y += dy;
-                    if next_x < 0
-                        || next_x >= board.len() as i32
-                        || next_y < 0
-                        || next_y >= board.len() as i32
-                    {
+/// Moves the robot one instruction on a single-width board, pushing packages ahead of it.
+fn step_board(board: &mut [Vec<Field>], robot: &mut (i32, i32), instruction: &Direction) {
+    let (x, y) = *robot;
+    let (dx, dy) = instruction.delta();
+    let next_x = x + dx;
+    let next_y = y + dy;
+    if next_x < 0 || next_x >= board.len() as i32 || next_y < 0 || next_y >= board.len() as i32 {
+        return;
+    }
+    let next_field = board[next_x as usize][next_y as usize];
+    match next_field {
+        Field::BLOCKED => return,
+        Field::PACKAGE => {
+            let mut blocked = true;
+            let mut next_x = next_x;
+            let mut next_y = next_y;
+            loop {
+                next_x += dx;
+                next_y += dy;
+                if next_x < 0
+                    || next_x >= board.len() as i32
+                    || next_y < 0
+                    || next_y >= board.len() as i32
+                {
+                    break;
+                }
+                let next_field = board[next_x as usize][next_y as usize];
+                match next_field {
+                    Field::BLOCKED => break,
+                    Field::PACKAGE => continue,
+                    _ => {
+                        blocked = false;
+                        board[next_x as usize][next_y as usize] = Field::PACKAGE;
                         break;
                     }
-                    let next_field = board[next_x as usize][next_y as usize];
-                    match next_field {
-                        Field::BLOCKED => break,
-                        Field::PACKAGE => continue,
-                        _ => {
-                            blocked = false;
-                            board[next_x as usize][next_y as usize] = Field::PACKAGE;
-                            break;
-                        }
-                    }
-                }
-                if blocked {
-                    continue;
                 }
             }
-            _ => {}
-        };
-        board[x as usize][y as usize] = Field::FREE;
-        x = next_x;
-        y = next_y;
-        board[next_x as usize][next_y as usize] = Field::ROBOT;
+            if blocked {
+                return;
+            }
+        }
+        _ => {}
+    };
+    board[x as usize][y as usize] = Field::FREE;
+    *robot = (next_x, next_y);
+    board[next_x as usize][next_y as usize] = Field::ROBOT;
+}
+
+fn walk_board(board: &Vec<Vec<Field>>, instructions: &Vec<Direction>) -> Vec<Vec<Field>> {
+    let mut board = board.clone();
+    let mut robot = get_robot(&board).expect("GUARD not found");
+
+    for instruction in instructions {
+        step_board(&mut board, &mut robot, instruction);
     }
     board
 }
@@ -160,7 +169,7 @@ fn convert_board(board: Vec<Vec<Field>>) -> Vec<Vec<Field>> {
         .collect()
 }
 
-fn is_blocked(board: &Vec<Vec<Field>>, x: i32, y: i32, direction: &Direction) -> bool {
+fn is_blocked(board: &[Vec<Field>], x: i32, y: i32, direction: &Direction) -> bool {
     let (dx, dy) = direction.delta();
     let next_x = x + dx;
     let next_y = y + dy;
@@ -193,7 +202,7 @@ fn is_blocked(board: &Vec<Vec<Field>>, x: i32, y: i32, direction: &Direction) ->
     }
 }
 
-fn move_packages(board: &mut Vec<Vec<Field>>, x: i32, y: i32, direction: &Direction) {
+fn move_packages(board: &mut [Vec<Field>], x: i32, y: i32, direction: &Direction) {
     let (dx, dy) = direction.delta();
     let next_x = x + dx;
     let next_y = y + dy;
@@ -233,50 +242,51 @@ fn move_packages(board: &mut Vec<Vec<Field>>, x: i32, y: i32, direction: &Direct
     }
 }
 
+/// Moves the robot one instruction on a double-width board, pushing wide packages ahead of it.
+fn step_double_board(board: &mut [Vec<Field>], robot: &mut (i32, i32), instruction: &Direction) {
+    let (x, y) = *robot;
+    let (dx, dy) = instruction.delta();
+    let next_x = x + dx;
+    let next_y = y + dy;
+    if next_x < 0 || next_x >= board.len() as i32 || next_y < 0 || next_y >= board[0].len() as i32 {
+        return;
+    }
+    let next_field = board[next_x as usize][next_y as usize];
+    match next_field {
+        Field::BLOCKED => return,
+        Field::PACKAGE => {
+            if is_blocked(board, next_x, next_y, instruction) {
+                return;
+            }
+        }
+        Field::PackageLeft => {
+            if is_blocked(board, next_x, next_y, instruction)
+                || is_blocked(board, next_x, next_y + 1, instruction)
+            {
+                return;
+            }
+        }
+        Field::PackageRight => {
+            if is_blocked(board, next_x, next_y, instruction)
+                || is_blocked(board, next_x, next_y - 1, instruction)
+            {
+                return;
+            }
+        }
+        _ => {}
+    };
+    move_packages(board, next_x, next_y, instruction);
+    board[x as usize][y as usize] = Field::FREE;
+    board[next_x as usize][next_y as usize] = Field::ROBOT;
+    *robot = (next_x, next_y);
+}
+
 fn walk_double_board(board: &Vec<Vec<Field>>, instructions: &Vec<Direction>) -> Vec<Vec<Field>> {
     let mut board = board.clone();
-    let (mut x, mut y) = get_robot(&board).expect("GUARD not found");
+    let mut robot = get_robot(&board).expect("GUARD not found");
 
     for instruction in instructions {
-        let (dx, dy) = instruction.delta();
-        let next_x = x + dx;
-        let next_y = y + dy;
-        if next_x < 0
-            || next_x >= board.len() as i32
-            || next_y < 0
-            || next_y >= board[0].len() as i32
-        {
-            continue;
-        }
-        let next_field = board[next_x as usize][next_y as usize];
-        match next_field {
-            Field::BLOCKED => continue,
-            Field::PACKAGE => {
-                if is_blocked(&board, next_x, next_y, instruction) {
-                    continue;
-                }
-            }
-            Field::PackageLeft => {
-                if is_blocked(&board, next_x, next_y, instruction)
-                    || is_blocked(&board, next_x, next_y + 1, instruction)
-                {
-                    continue;
-                }
-            }
-            Field::PackageRight => {
-                if is_blocked(&board, next_x, next_y, instruction)
-                    || is_blocked(&board, next_x, next_y - 1, instruction)
-                {
-                    continue;
-                }
-            }
-            _ => {}
-        };
-        move_packages(&mut board, next_x, next_y, instruction);
-        board[x as usize][y as usize] = Field::FREE;
-        board[next_x as usize][next_y as usize] = Field::ROBOT;
-        x = next_x;
-        y = next_y;
+        step_double_board(&mut board, &mut robot, instruction);
     }
     board
 }
@@ -297,23 +307,65 @@ fn score(board: &Vec<Vec<Field>>) -> usize {
 
 pub struct Puzzle15 {}
 
-fn parse_input(input: &String) -> (Vec<Vec<Field>>, Vec<Direction>) {
-    let mut parts = input.split("\n\n");
-    let board = parts.next().unwrap();
+/// The warehouse and the robot's moves. The warehouse has to be a walled rectangle with one
+/// robot, the moves never check the bounds and rely on the walls.
+fn parse_input(input: &str) -> Result<(Vec<Vec<Field>>, Vec<Direction>), ParseError> {
+    let input = input.replace("\r\n", "\n");
+    let (board, moves) = input
+        .split_once("\n\n")
+        .ok_or_else(|| ParseError::new(0, "expected the warehouse and the moves"))?;
 
     let board: Vec<Vec<Field>> = board
         .lines()
         .map(str::trim)
-        .map(|line| line.bytes().map(Field::from).collect())
-        .collect();
+        .enumerate()
+        .map(|(i, line)| {
+            line.bytes()
+                .map(Field::try_from)
+                .collect::<Result<_, _>>()
+                .map_err(|message| ParseError::new(i, message))
+        })
+        .collect::<Result<_, _>>()?;
+    let width = board.first().map_or(0, |row| row.len());
+    if width == 0 {
+        return Err(ParseError::new(0, "the warehouse is empty"));
+    }
+    for (i, row) in board.iter().enumerate() {
+        if row.len() != width {
+            return Err(ParseError::new(i, "all rows must have the same length"));
+        }
+        let border = i == 0 || i == board.len() - 1;
+        let walled = match border {
+            true => row.iter().all(|&el| el == Field::BLOCKED),
+            false => row.first() == Some(&Field::BLOCKED) && row.last() == Some(&Field::BLOCKED),
+        };
+        if !walled {
+            return Err(ParseError::new(
+                i,
+                "the warehouse must be surrounded by walls",
+            ));
+        }
+    }
+    let robots = board.iter().flatten().filter(|&&el| el == Field::ROBOT);
+    if robots.count() != 1 {
+        return Err(ParseError::new(
+            0,
+            "the warehouse must contain exactly one robot",
+        ));
+    }
 
-    let instructions: Vec<Direction> = parts
-        .next()
-        .unwrap()
-        .bytes()
-        .filter_map(Direction::parse)
-        .collect();
-    (board, instructions)
+    let offset = board.len() + 1;
+    let instructions: Vec<Direction> = moves
+        .lines()
+        .enumerate()
+        .flat_map(|(i, line)| line.trim().bytes().map(move |el| (i, el)))
+        .map(|(i, el)| {
+            Direction::parse(el).ok_or_else(|| {
+                ParseError::new(offset + i, format!("unknown move {:?}", el as char))
+            })
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((board, instructions))
 }
 
 impl Aoc2024 for Puzzle15 {
@@ -322,15 +374,67 @@ impl Aoc2024 for Puzzle15 {
     }
 
     fn solve_a(&self, input: &String) -> String {
-        let (board, instructions) = parse_input(input);
+        let (board, instructions) = match parse_input(input) {
+            Ok(input) => input,
+            Err(err) => return err.to_string(),
+        };
         let board = walk_board(&board, &instructions);
         score(&board).to_string()
     }
 
     fn solve_b(&self, input: &String) -> String {
-        let (board, instructions) = parse_input(input);
+        let (board, instructions) = match parse_input(input) {
+            Ok(input) => input,
+            Err(err) => return err.to_string(),
+        };
         let board = convert_board(board);
         let board = walk_double_board(&board, &instructions);
         score(&board).to_string()
     }
+
+    fn frames(&self, input: &String, part_b: bool) -> Option<Frames> {
+        let (board, instructions) = parse_input(input).ok()?;
+        let (mut board, step): (_, StepFn) = if part_b {
+            (convert_board(board), step_double_board)
+        } else {
+            (board, step_board)
+        };
+        let mut robot = get_robot(&board).expect("GUARD not found");
+        let initial = render_grid(&board);
+        let steps = instructions.into_iter().map(move |instruction| {
+            step(&mut board, &mut robot, &instruction);
+            render_grid(&board)
+        });
+        Some(Box::new(std::iter::once(initial).chain(steps)))
+    }
+
+    fn parse(&self, input: &String) -> Result<(), ParseError> {
+        parse_input(input).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate;
+
+    #[test]
+    fn parses_generated_input() {
+        let input = generate::generate(15, generate::default_size(15), 1).unwrap();
+        assert_eq!(Puzzle15 {}.parse(&input), Ok(()));
+    }
+
+    #[test]
+    fn rejects_broken_input() {
+        let inputs = [
+            // noise used to unwrap the missing robot
+            ("\u{1c}PJ\u{0}4x\n\u{c}J%", 0),
+            // a warehouse without a robot
+            ("#####\n#.O.#\n#####\n\n<^\n", 0),
+        ];
+        for (input, line) in inputs {
+            let result = Puzzle15 {}.parse(&input.to_string());
+            assert_eq!(result.map_err(|err| err.line), Err(line), "{input:?}");
+        }
+    }
 }
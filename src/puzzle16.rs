@@ -1,21 +1,42 @@
-use crate::prelude::Aoc2024;
+use crate::number_theory::BigUint;
+use crate::prelude::{Aoc2024, ParseError};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 use std::fmt::{Debug, Formatter, Write};
 
+/// Score of moving one tile forward in the puzzle.
+const STEP_COST: usize = 1;
+/// Score of turning 90 degrees in the puzzle.
+const TURN_COST: usize = 1000;
+/// Answer when no end can be reached.
+const NO_ROUTE: &str = "No route from S to E";
+/// Error when a score does not fit, `usize::MAX` marks unreachable states.
+const TOO_EXPENSIVE: &str = "the scores do not fit in 64 bits with these costs";
+
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
-enum Direction {
+pub enum Direction {
     UP,
     RIGHT, // Each bit represents a direction
     DOWN,
     LEFT,
 }
 
+const DIRECTIONS: [Direction; 4] = [
+    Direction::UP,
+    Direction::RIGHT,
+    Direction::DOWN,
+    Direction::LEFT,
+];
+
 #[derive(PartialEq, Eq, Clone, Copy)]
 enum Field {
     FREE,
     BLOCKED,
     END,
     START,
-    BEST,
+    /// A digit tile `1` to `9`, entering it costs that many steps. There is no `0` tile, a free
+    /// step would make the best routes endless.
+    Weighted(u8),
 }
 
 impl Debug for Field {
@@ -25,43 +46,47 @@ impl Debug for Field {
             Field::BLOCKED => f.write_char('#'),
             Field::END => f.write_char('E'),
             Field::START => f.write_char('S'),
-            Field::BEST => f.write_char('O'),
+            Field::Weighted(weight) => f.write_char((b'0' + weight) as char),
         }
     }
 }
 
-#[derive(PartialEq, Eq, Clone, Copy)]
-struct Node {
-    pub field: Field,
-    pub score: usize,
-}
+impl TryFrom<u8> for Field {
+    type Error = String;
 
-impl Debug for Node {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.write_fmt(format_args!("{:?}", self.field))
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            b'.' => Ok(Field::FREE),
+            b'#' => Ok(Field::BLOCKED),
+            b'E' => Ok(Field::END),
+            b'S' => Ok(Field::START),
+            b'1'..=b'9' => Ok(Field::Weighted(value - b'0')),
+            _ => Err(format!("unknown field {:?}", value as char)),
+        }
     }
 }
 
-impl From<u8> for Node {
-    fn from(value: u8) -> Self {
-        Self {
-            field: Field::from(value),
-            score: usize::MAX,
+impl Field {
+    /// Number of steps it costs to enter the tile.
+    fn weight(&self) -> usize {
+        match self {
+            Field::Weighted(weight) => *weight as usize,
+            _ => 1,
         }
     }
 }
-impl From<u8> for Field {
-    fn from(value: u8) -> Self {
+
+impl Direction {
+    pub fn parse(value: &str) -> Option<Self> {
         match value {
-            b'#' => Field::BLOCKED,
-            b'E' => Field::END,
-            b'S' => Field::START,
-            _ => Field::FREE,
+            "up" | "north" => Some(Direction::UP),
+            "right" | "east" => Some(Direction::RIGHT),
+            "down" | "south" => Some(Direction::DOWN),
+            "left" | "west" => Some(Direction::LEFT),
+            _ => None,
         }
     }
-}
 
-impl Direction {
     fn delta(&self) -> (i32, i32) {
         match self {
             Direction::UP => (-1, 0),   // UP
@@ -90,70 +115,400 @@ impl Direction {
     }
 }
 
-fn get_position(board: &Vec<Vec<Node>>, field: Field) -> Option<(i32, i32)> {
-    for i in 0..board.len() {
-        for j in 0..board[0].len() {
-            if board[i][j].field == field {
-                return Some((i as i32, j as i32));
+/// Scores of the reindeer's moves.
+#[derive(Clone, Copy)]
+struct Costs {
+    step: usize,
+    turn: usize,
+}
+
+/// The maze as a flat grid. Search states are a tile together with the facing, numbered
+/// `tile * 4 + direction` so scores can be kept in plain vectors.
+struct Maze {
+    fields: Vec<Field>,
+    width: usize,
+    height: usize,
+}
+
+impl Maze {
+    /// Parses the maze, shorter rows are padded with walls. It needs at least one S and one E.
+    fn new(input: &str) -> Result<Self, ParseError> {
+        let rows: Vec<&[u8]> = input.lines().map(str::trim).map(str::as_bytes).collect();
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        let mut fields = vec![Field::BLOCKED; width * rows.len()];
+        for (i, row) in rows.iter().enumerate() {
+            for (j, &value) in row.iter().enumerate() {
+                fields[i * width + j] =
+                    Field::try_from(value).map_err(|err| ParseError::new(i, err))?;
             }
         }
+        let maze = Self {
+            fields,
+            width,
+            height: rows.len(),
+        };
+        let last = rows.len().saturating_sub(1);
+        if maze.starts().is_empty() {
+            return Err(ParseError::new(last, "the maze has no start S"));
+        }
+        if maze.find_all(Field::END).is_empty() {
+            return Err(ParseError::new(last, "the maze has no end E"));
+        }
+        Ok(maze)
     }
-    None
-}
 
-fn dfs(board: &mut Vec<Vec<Node>>, x: i32, y: i32, score: usize, direction: Direction) {
-    let node = board[x as usize][y as usize];
-    if node.field == Field::BLOCKED {
-        return;
+    fn find_all(&self, field: Field) -> Vec<usize> {
+        (0..self.fields.len())
+            .filter(|&tile| self.fields[tile] == field)
+            .collect()
     }
-    if score < node.score {
-        board[x as usize][y as usize].score = score;
+
+    /// Row and column of `tile`.
+    fn coordinates(&self, tile: usize) -> (usize, usize) {
+        (tile / self.width, tile % self.width)
+    }
+
+    fn state(tile: usize, direction: Direction) -> usize {
+        tile * 4 + direction as usize
+    }
+
+    /// Tile next to `tile` in `direction`, if it is inside the maze and not blocked.
+    fn neighbour(&self, tile: usize, direction: Direction) -> Option<usize> {
         let (dx, dy) = direction.delta();
-        dfs(board, x + dx, y + dy, score + 1, direction);
-        let clockwise = direction.clockwise();
-        let (dx, dy) = clockwise.delta();
-        board[x as usize][y as usize].score = score + 1000;
-        dfs(board, x + dx, y + dy, score + 1 + 1000, clockwise);
-        let counter_clockwise = direction.counter_clockwise();
-        let (dx, dy) = counter_clockwise.delta();
-        dfs(board, x + dx, y + dy, score + 1 + 1000, counter_clockwise);
+        let x = (tile / self.width) as i32 + dx;
+        let y = (tile % self.width) as i32 + dy;
+        if x < 0 || y < 0 || x >= self.height as i32 || y >= self.width as i32 {
+            return None;
+        }
+        let next = x as usize * self.width + y as usize;
+        (self.fields[next] != Field::BLOCKED).then_some(next)
+    }
+
+    /// States reachable from `state` with one move and their cost. With `reverse` the states
+    /// `state` is reachable from are returned instead. A step costs the weight of the tile
+    /// entered, so going back over a weighted tile costs the weight of `state`'s own tile.
+    fn moves(
+        &self,
+        state: usize,
+        costs: Costs,
+        reverse: bool,
+    ) -> Result<Vec<(usize, usize)>, String> {
+        let (tile, direction) = (state / 4, DIRECTIONS[state % 4]);
+        let mut moves = vec![
+            (Self::state(tile, direction.clockwise()), costs.turn),
+            (Self::state(tile, direction.counter_clockwise()), costs.turn),
+        ];
+        let forward = match reverse {
+            true => self.neighbour(tile, direction.clockwise().clockwise()),
+            false => self.neighbour(tile, direction),
+        };
+        if let Some(next) = forward {
+            let entered = if reverse { tile } else { next };
+            let cost = costs
+                .step
+                .checked_mul(self.fields[entered].weight())
+                .ok_or(TOO_EXPENSIVE)?;
+            moves.push((Self::state(next, direction), cost));
+        }
+        Ok(moves)
+    }
+
+    /// Lowest score of every state when starting in any of `sources`, `usize::MAX` if unreachable.
+    /// Fails when a reachable score does not fit below `usize::MAX`.
+    fn dijkstra(
+        &self,
+        sources: &[usize],
+        costs: Costs,
+        reverse: bool,
+    ) -> Result<Vec<usize>, String> {
+        let mut scores = vec![usize::MAX; self.fields.len() * 4];
+        let mut queue = BinaryHeap::new();
+        for &source in sources {
+            scores[source] = 0;
+            queue.push(Reverse((0, source)));
+        }
+        while let Some(Reverse((score, state))) = queue.pop() {
+            if score > scores[state] {
+                continue;
+            }
+            for (next, cost) in self.moves(state, costs, reverse)? {
+                let total = score
+                    .checked_add(cost)
+                    .filter(|&total| total != usize::MAX)
+                    .ok_or(TOO_EXPENSIVE)?;
+                if total < scores[next] {
+                    scores[next] = total;
+                    queue.push(Reverse((total, next)));
+                }
+            }
+        }
+        Ok(scores)
+    }
+
+    /// Every start facing east.
+    fn starts(&self) -> Vec<usize> {
+        self.find_all(Field::START)
+            .into_iter()
+            .map(|tile| Self::state(tile, Direction::RIGHT))
+            .collect()
+    }
+
+    /// States that finish a route: an end tile, in the required `facing` if there is one.
+    fn ends(&self, facing: Option<Direction>) -> Vec<usize> {
+        self.find_all(Field::END)
+            .into_iter()
+            .flat_map(|tile| DIRECTIONS.map(|direction| Self::state(tile, direction)))
+            .filter(|&state| facing.is_none_or(|facing| DIRECTIONS[state % 4] == facing))
+            .collect()
+    }
+
+    /// Lowest score from any start to any end, and the scores of all states.
+    fn best_score(
+        &self,
+        costs: Costs,
+        facing: Option<Direction>,
+    ) -> Result<Option<(usize, Vec<usize>)>, String> {
+        let scores = self.dijkstra(&self.starts(), costs, false)?;
+        let best = self
+            .ends(facing)
+            .iter()
+            .map(|&state| scores[state])
+            .min()
+            .filter(|&best| best != usize::MAX);
+        Ok(best.map(|best| (best, scores)))
+    }
+
+    /// The best routes from any start to any end, `None` if there is no route.
+    fn best_routes(
+        &self,
+        costs: Costs,
+        facing: Option<Direction>,
+    ) -> Result<Option<BestRoutes<'_>>, String> {
+        let Some((best, forward)) = self.best_score(costs, facing)? else {
+            return Ok(None);
+        };
+        let ends: Vec<usize> = self
+            .ends(facing)
+            .into_iter()
+            .filter(|&state| forward[state] == best)
+            .collect();
+        let backward = self.dijkstra(&ends, costs, true)?;
+        let mut is_end = vec![false; forward.len()];
+        for &state in &ends {
+            is_end[state] = true;
+        }
+        Ok(Some(BestRoutes {
+            maze: self,
+            costs,
+            best,
+            starts: self.starts(),
+            ends,
+            is_end,
+            forward,
+            backward,
+        }))
     }
 }
 
-fn backtrack(
-    board: &mut Vec<Vec<Node>>,
-    x: i32,
-    y: i32,
-    score: usize,
-    direction: Direction,
-) -> bool {
-    let node = board[x as usize][y as usize];
-    if node.field == Field::BLOCKED {
-        return false;
-    } else if node.field == Field::END {
-        return true;
-    }
-
-    if score <= node.score {
-        let mut found_goal = false;
-        let (dx, dy) = direction.delta();
-        found_goal |= backtrack(board, x + dx, y + dy, score + 1, direction);
-        let clockwise = direction.clockwise();
-        let (dx, dy) = clockwise.delta();
-        found_goal |= backtrack(board, x + dx, y + dy, score + 1 + 1000, clockwise);
-        let counter_clockwise = direction.counter_clockwise();
-        let (dx, dy) = counter_clockwise.delta();
-        found_goal |= backtrack(board, x + dx, y + dy, score + 1 + 1000, counter_clockwise);
+/// The states on a route with the lowest score, linked by the moves that keep it optimal. With
+/// positive costs these moves form a DAG from the start to the end states.
+struct BestRoutes<'a> {
+    maze: &'a Maze,
+    costs: Costs,
+    best: usize,
+    starts: Vec<usize>,
+    /// End states reached with the best score.
+    ends: Vec<usize>,
+    /// Per state whether it is one of `ends`, counting and listing look this up for every state.
+    is_end: Vec<bool>,
+    forward: Vec<usize>,
+    backward: Vec<usize>,
+}
+
+impl BestRoutes<'_> {
+    /// States where the score from the start and the score to the end add up to the best one.
+    fn on_route(&self, state: usize) -> bool {
+        self.forward[state]
+            .checked_add(self.backward[state])
+            .is_some_and(|total| total == self.best)
+    }
+
+    /// A start and an end state with the best score between them. The end is picked first and
+    /// the start found with a search back from it alone, since another end may be closer to it.
+    fn pairing(&self) -> Result<(usize, usize), String> {
+        let end = self.ends[0];
+        let backward = self.maze.dijkstra(&[end], self.costs, true)?;
+        let start = self
+            .starts
+            .iter()
+            .find(|&&start| backward[start] == self.best);
+        Ok((*start.expect("an end with the best score has a start"), end))
+    }
 
-        if found_goal {
-            board[x as usize][y as usize].field = Field::BEST;
+    /// Moves from `state` that stay on a best route, labelled `F`orward, `L`eft or `R`ight.
+    fn successors(&self, state: usize) -> Vec<(char, usize)> {
+        let mut successors: Vec<(char, usize)> = self
+            .maze
+            .moves(state, self.costs, false)
+            .expect("the search already scored these moves")
+            .into_iter()
+            .filter(|&(next, cost)| {
+                self.on_route(next)
+                    && self.forward[state].checked_add(cost) == Some(self.forward[next])
+            })
+            .map(|(next, _)| {
+                let label = if next / 4 != state / 4 {
+                    'F'
+                } else if DIRECTIONS[next % 4] == DIRECTIONS[state % 4].counter_clockwise() {
+                    'L'
+                } else {
+                    'R'
+                };
+                (label, next)
+            })
+            .collect();
+        successors.sort();
+        successors
+    }
+
+    /// Tiles on at least one best route.
+    fn tiles(&self) -> Vec<usize> {
+        (0..self.maze.fields.len())
+            .filter(|&tile| {
+                DIRECTIONS
+                    .iter()
+                    .any(|&direction| self.on_route(Maze::state(tile, direction)))
+            })
+            .collect()
+    }
+
+    /// Number of distinct best routes, counted over the states in order of their score.
+    fn count(&self) -> BigUint {
+        let mut states: Vec<usize> = (0..self.forward.len())
+            .filter(|&state| self.on_route(state))
+            .collect();
+        states.sort_by_key(|&state| self.forward[state]);
+        let mut counts = vec![BigUint::from(0); self.forward.len()];
+        for &start in &self.starts {
+            counts[start] = BigUint::from(1);
+        }
+        let mut total = BigUint::from(0);
+        for state in states {
+            if self.is_end[state] {
+                total = &total + &counts[state];
+            }
+            for (_, next) in self.successors(state) {
+                counts[next] = &counts[next] + &counts[state];
+            }
+        }
+        total
+    }
+
+    /// The first `limit` best routes with their start, as move strings in lexicographic order
+    /// per start. The search keeps its own stack since a route can be as long as the maze is big.
+    fn first(&self, limit: usize) -> Vec<(usize, String)> {
+        let mut routes = Vec::new();
+        for &start in self.starts.iter().filter(|&&start| self.on_route(start)) {
+            let mut moves = String::new();
+            let mut stack = vec![self.successors(start)];
+            stack[0].reverse();
+            while let Some(pending) = stack.last_mut() {
+                if routes.len() == limit {
+                    return routes;
+                }
+                match pending.pop() {
+                    Some((label, next)) => {
+                        moves.push(label);
+                        if self.is_end[next] {
+                            routes.push((start, moves.clone()));
+                        }
+                        let mut successors = self.successors(next);
+                        successors.reverse();
+                        stack.push(successors);
+                    }
+                    None => {
+                        stack.pop();
+                        moves.pop();
+                    }
+                }
+            }
         }
-        found_goal
-    } else {
-        false
+        routes
+    }
+
+    /// The maze with every tile on a best route drawn as `O`.
+    fn render(&self) -> String {
+        let tiles = self.tiles();
+        let mut output = String::new();
+        for (tile, field) in self.maze.fields.iter().enumerate() {
+            match field {
+                Field::FREE | Field::Weighted(_) if tiles.binary_search(&tile).is_ok() => {
+                    output.push('O')
+                }
+                _ => output.push_str(&format!("{field:?}")),
+            }
+            if (tile + 1) % self.maze.width == 0 {
+                output.push('\n');
+            }
+        }
+        output
     }
 }
 
+/// Scores a maze with other move costs for `maze <file> [--step <n>] [--turn <n>]
+/// [--facing <direction>] [--routes <n>] [--render]`. Routes are only counted and listed with
+/// positive costs, turning on the spot for free would give endless routes.
+pub fn scores(
+    input: &str,
+    step: usize,
+    turn: usize,
+    facing: Option<Direction>,
+    routes: usize,
+    render: bool,
+) -> String {
+    let maze = match Maze::new(input) {
+        Ok(maze) => maze,
+        Err(err) => return format!("{err}\n"),
+    };
+    let costs = Costs { step, turn };
+    let best_routes = match maze.best_routes(costs, facing) {
+        Ok(Some(best_routes)) => best_routes,
+        Ok(None) => return format!("{NO_ROUTE}\n"),
+        Err(err) => return format!("{err}\n"),
+    };
+    let (start, end) = match best_routes.pairing() {
+        Ok(pairing) => pairing,
+        Err(err) => return format!("{err}\n"),
+    };
+    let mut output = format!(
+        "lowest score {}, {} tiles on best routes\n",
+        best_routes.best,
+        best_routes.tiles().len()
+    );
+    output.push_str(&format!(
+        "best pairing S {:?} to E {:?} facing {:?}\n",
+        maze.coordinates(start / 4),
+        maze.coordinates(end / 4),
+        DIRECTIONS[end % 4]
+    ));
+    if step > 0 && turn > 0 {
+        output.push_str(&format!("best route count {}\n", best_routes.count()));
+        let several = maze.starts().len() > 1;
+        for (start, route) in best_routes.first(routes) {
+            match several {
+                true => output.push_str(&format!("{:?} {route}\n", maze.coordinates(start / 4))),
+                false => output.push_str(&format!("{route}\n")),
+            }
+        }
+    } else if routes > 0 {
+        output.push_str("routes are only listed with positive costs\n");
+    }
+    if render {
+        output.push_str(&best_routes.render());
+    }
+    output
+}
+
 pub struct Puzzle16 {}
 
 impl Aoc2024 for Puzzle16 {
@@ -162,38 +517,209 @@ impl Aoc2024 for Puzzle16 {
     }
 
     fn solve_a(&self, input: &String) -> String {
-        let mut board: Vec<Vec<Node>> = input
-            .lines()
-            .map(str::trim)
-            .map(|line| line.bytes().map(Node::from).collect())
-            .collect();
+        let maze = match Maze::new(input) {
+            Ok(maze) => maze,
+            Err(err) => return err.to_string(),
+        };
+        let costs = Costs {
+            step: STEP_COST,
+            turn: TURN_COST,
+        };
+        match maze.best_score(costs, None) {
+            Ok(Some((best, _))) => best.to_string(),
+            Ok(None) => NO_ROUTE.to_string(),
+            Err(err) => err,
+        }
+    }
 
-        let (x, y) = get_position(&board, Field::START).expect("START not found");
+    fn solve_b(&self, input: &String) -> String {
+        let maze = match Maze::new(input) {
+            Ok(maze) => maze,
+            Err(err) => return err.to_string(),
+        };
+        let costs = Costs {
+            step: STEP_COST,
+            turn: TURN_COST,
+        };
+        match maze.best_routes(costs, None) {
+            Ok(Some(best_routes)) => best_routes.tiles().len().to_string(),
+            Ok(None) => NO_ROUTE.to_string(),
+            Err(err) => err,
+        }
+    }
+
+    fn parse(&self, input: &String) -> Result<(), ParseError> {
+        Maze::new(input).map(|_| ())
+    }
+}
 
-        dfs(&mut board, x, y, 0, Direction::RIGHT);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate;
 
-        let (x, y) = get_position(&board, Field::END).expect("END not found");
+    const FIRST_EXAMPLE: &str = "\
+###############\n\
+#.......#....E#\n\
+#.#.###.#.###.#\n\
+#.....#.#...#.#\n\
+#.###.#####.#.#\n\
+#.#.#.......#.#\n\
+#.#####.###.#.#\n\
+#...........#.#\n\
+###.#.#####.#.#\n\
+#...#.....#.#.#\n\
+#.#.#.###.#.#.#\n\
+#.....#...#.#.#\n\
+#.###.#.#.#.#.#\n\
+#S..#.....#...#\n\
+###############\n";
 
-        let end_score = board[x as usize][y as usize].score;
-        end_score.to_string()
+    const SECOND_EXAMPLE: &str = "\
+#################\n\
+#...#...#...#..E#\n\
+#.#.#.#.#.#.#.#.#\n\
+#.#.#.#...#...#.#\n\
+#.#.#.#.###.#.#.#\n\
+#...#.#.#.....#.#\n\
+#.#.#.#.#.#####.#\n\
+#.#...#.#.#.....#\n\
+#.#.#####.#.###.#\n\
+#.#.#.......#...#\n\
+#.#.###.#####.###\n\
+#.#.#...#.....#.#\n\
+#.#.#.#####.###.#\n\
+#.#.#.........#.#\n\
+#.#.#.#########.#\n\
+#S#.............#\n\
+#################\n";
+
+    fn puzzle_routes(maze: &Maze) -> BestRoutes<'_> {
+        let costs = Costs {
+            step: STEP_COST,
+            turn: TURN_COST,
+        };
+        maze.best_routes(costs, None).unwrap().unwrap()
     }
 
-    fn solve_b(&self, input: &String) -> String {
-        let mut board: Vec<Vec<Node>> = input
-            .lines()
-            .map(str::trim)
-            .map(|line| line.bytes().map(Node::from).collect())
-            .collect();
+    #[test]
+    fn parses_generated_input() {
+        let input = generate::generate(16, generate::default_size(16), 1).unwrap();
+        assert_eq!(Puzzle16 {}.parse(&input), Ok(()));
+    }
 
-        let (x, y) = get_position(&board, Field::START).expect("START not found");
+    #[test]
+    fn rejects_broken_input() {
+        let inputs = [
+            // an unknown tile
+            ("#####\n#S.E#\n###x#\n", 2),
+            // a maze without an end
+            ("#####\n#S..#\n#####\n", 2),
+        ];
+        for (input, line) in inputs {
+            let result = Puzzle16 {}.parse(&input.to_string());
+            assert_eq!(result.map_err(|err| err.line), Err(line), "{input:?}");
+        }
+    }
 
-        dfs(&mut board, x, y, 0, Direction::RIGHT);
-        backtrack(&mut board, x, y, 0, Direction::RIGHT);
+    #[test]
+    fn solves_the_examples() {
+        for (input, score, tiles) in [
+            (FIRST_EXAMPLE, "7036", "45"),
+            (SECOND_EXAMPLE, "11048", "64"),
+        ] {
+            let input = input.to_string();
+            assert_eq!(Puzzle16 {}.solve_a(&input), score);
+            assert_eq!(Puzzle16 {}.solve_b(&input), tiles);
+        }
+    }
+
+    #[test]
+    fn counts_and_lists_the_best_routes() {
+        let maze = Maze::new(FIRST_EXAMPLE).unwrap();
+        let best_routes = puzzle_routes(&maze);
+        assert_eq!(best_routes.count().to_string(), "3");
+        let routes: Vec<String> = best_routes
+            .first(10)
+            .into_iter()
+            .map(|(_, route)| route)
+            .collect();
+        assert_eq!(
+            routes,
+            [
+                "LFFFFRFFLFFRFFFFFFFFRFFFFFFLFFLFFFFFFFFFFFF",
+                "LFFRFFFFLFFFFRFFFFFFRFFFFFFLFFLFFFFFFFFFFFF",
+                "LFFRFFLFFFFRFFFFFFFFRFFFFFFLFFLFFFFFFFFFFFF",
+            ]
+        );
+        assert_eq!(best_routes.first(2).len(), 2);
+
+        let maze = Maze::new(SECOND_EXAMPLE).unwrap();
+        let best_routes = puzzle_routes(&maze);
+        assert_eq!(best_routes.count().to_string(), "2");
+        assert_eq!(best_routes.first(10).len(), 2);
+    }
 
-        let best_places: usize = board
+    #[test]
+    fn weighs_digit_tiles() {
+        let input = "#########\n#S..9..E#\n#.#####.#\n#S..1...#\n#########\n";
+        let lines: Vec<String> = [(1, 1000), (1, 1), (2, 1)]
             .iter()
-            .map(|row| row.iter().filter(|node| node.field == Field::BEST).count())
-            .sum();
-        (best_places + 1).to_string()
+            .map(|&(step, turn)| scores(input, step, turn, None, 0, false))
+            .map(|output| output.lines().next().unwrap().to_string())
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "lowest score 14, 7 tiles on best routes",
+                "lowest score 9, 9 tiles on best routes",
+                "lowest score 17, 9 tiles on best routes",
+            ]
+        );
+        assert!(Puzzle16 {}
+            .parse(&"#####\n#S0E#\n#####\n".to_string())
+            .is_err());
+    }
+
+    #[test]
+    fn pairs_several_starts_and_ends_with_a_facing() {
+        let input = "#########\n#S..9..E#\n#.#####.#\n#S..1..E#\n#########\n";
+        let facings = [
+            (None, "lowest score 6", "RIGHT", "(3, 1) FFFFFF\n"),
+            (
+                Some(Direction::UP),
+                "lowest score 1006",
+                "UP",
+                "(3, 1) FFFFFFL\n",
+            ),
+            (
+                Some(Direction::LEFT),
+                "lowest score 2006",
+                "LEFT",
+                "(3, 1) FFFFFFLL\n(3, 1) FFFFFFRR\n",
+            ),
+        ];
+        for (facing, score, end, routes) in facings {
+            let output = scores(input, 1, 1000, facing, 5, false);
+            assert!(output.starts_with(score), "{output}");
+            let pairing = format!("best pairing S (3, 1) to E (3, 7) facing {end}\n");
+            assert!(output.contains(&pairing), "{output}");
+            assert!(output.ends_with(routes), "{output}");
+        }
+    }
+
+    #[test]
+    fn rejects_scores_that_do_not_fit() {
+        let input = "#####\n#S.E#\n#####\n";
+        let output = scores(input, usize::MAX, 1, None, 0, false);
+        assert_eq!(output, format!("{TOO_EXPENSIVE}\n"));
+        let output = scores(input, 1, usize::MAX - 1, Some(Direction::UP), 0, false);
+        assert_eq!(output, format!("{TOO_EXPENSIVE}\n"));
+        let walled = "#####\n#S#E#\n#####\n";
+        assert_eq!(Puzzle16 {}.solve_a(&walled.to_string()), NO_ROUTE);
+        assert_eq!(
+            scores(walled, 1, 1, None, 0, false),
+            format!("{NO_ROUTE}\n")
+        );
     }
 }
@@ -1,23 +1,27 @@
-use crate::prelude::Aoc2024;
-use std::fmt::{Debug, Formatter};
+use crate::prelude::{Aoc2024, ParseError};
+use crate::symbolic;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::{Debug, Display, Formatter};
 use std::str::Lines;
 
 type Literal = u8;
 
 #[derive(Debug, Clone, Copy)]
-enum Instruction {
+pub(crate) enum Instruction {
     Adv(Combo),
     Bxl(Literal),
     Bst(Combo),
+    /// Jumps to a program address, instructions take two numbers so that is instruction `lit / 2`.
     Jnz(Literal),
-    Bxc,
+    /// The operand is read but ignored, it is kept so a program encodes back to the same numbers.
+    Bxc(Literal),
     Out(Combo),
     Bdv(Combo),
     Cdv(Combo),
 }
 
 #[derive(Debug, Clone, Copy)]
-enum Combo {
+pub(crate) enum Combo {
     Literal(u8),
     RegA,
     RegB,
@@ -25,149 +29,584 @@ enum Combo {
     Reserved,
 }
 
-impl From<u8> for Combo {
-    fn from(value: u8) -> Self {
+/// Instructions executed before a run is aborted, unless another budget is given.
+pub(crate) const STEP_BUDGET: usize = 1_000_000;
+
+/// Reasons the machine cannot load or run a program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum VmError {
+    /// Opcode that is not a 3-bit number, at the given program address.
+    BadOpcode {
+        address: usize,
+        opcode: u8,
+    },
+    /// Operand that is not a 3-bit number, or the reserved combo operand 7 being used.
+    InvalidOperand {
+        address: usize,
+        operand: u8,
+    },
+    /// Every instruction needs an opcode and an operand.
+    OddProgramLength(usize),
+    /// Jump into the middle of an instruction.
+    MisalignedJump {
+        address: usize,
+        target: u8,
+    },
+    NegativeShift {
+        address: usize,
+        amount: i64,
+    },
+    StepLimitExceeded(usize),
+    /// Symbolic execution built more expression nodes than the given limit.
+    NodeLimitExceeded(usize),
+}
+
+impl Display for VmError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VmError::BadOpcode { address, opcode } => {
+                write!(f, "bad opcode {opcode} at address {address}")
+            }
+            VmError::InvalidOperand { address, operand } => {
+                write!(f, "invalid operand {operand} at address {address}")
+            }
+            VmError::OddProgramLength(length) => {
+                write!(
+                    f,
+                    "program length {length} is odd, the last opcode has no operand"
+                )
+            }
+            VmError::MisalignedJump { address, target } => {
+                write!(
+                    f,
+                    "jump at address {address} into the middle of an instruction at {target}"
+                )
+            }
+            VmError::NegativeShift { address, amount } => {
+                write!(f, "shift by negative amount {amount} at address {address}")
+            }
+            VmError::StepLimitExceeded(budget) => {
+                write!(f, "step limit of {budget} instructions exceeded")
+            }
+            VmError::NodeLimitExceeded(limit) => {
+                write!(f, "symbolic expressions grew past {limit} nodes")
+            }
+        }
+    }
+}
+
+impl TryFrom<u8> for Combo {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
-            4 => Combo::RegA,
-            5 => Combo::RegB,
-            6 => Combo::RegC,
-            7 => Combo::Reserved,
-            lit => Combo::Literal(lit),
+            0..=3 => Ok(Combo::Literal(value)),
+            4 => Ok(Combo::RegA),
+            5 => Ok(Combo::RegB),
+            6 => Ok(Combo::RegC),
+            7 => Ok(Combo::Reserved),
+            _ => Err(value),
         }
     }
 }
 
-impl From<(u8, u8)> for Instruction {
-    fn from(value: (u8, u8)) -> Self {
-        let (opcode, operand) = value;
-        let combo = Combo::from(operand);
+impl Instruction {
+    fn decode(address: usize, opcode: u8, operand: u8) -> Result<Self, VmError> {
+        let combo = Combo::try_from(operand)
+            .map_err(|operand| VmError::InvalidOperand { address, operand })?;
         let literal = operand;
-        match opcode {
+        Ok(match opcode {
             0 => Instruction::Adv(combo),
             1 => Instruction::Bxl(literal),
             2 => Instruction::Bst(combo),
             3 => Instruction::Jnz(literal),
-            4 => Instruction::Bxc,
+            4 => Instruction::Bxc(literal),
             5 => Instruction::Out(combo),
             6 => Instruction::Bdv(combo),
             7 => Instruction::Cdv(combo),
-            _ => unreachable!(),
+            _ => return Err(VmError::BadOpcode { address, opcode }),
+        })
+    }
+}
+
+impl Combo {
+    fn encode(self) -> u8 {
+        match self {
+            Combo::Literal(lit) => lit,
+            Combo::RegA => 4,
+            Combo::RegB => 5,
+            Combo::RegC => 6,
+            Combo::Reserved => 7,
+        }
+    }
+}
+
+impl Display for Combo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Combo::Literal(lit) => write!(f, "{lit}"),
+            Combo::RegA => f.write_str("a"),
+            Combo::RegB => f.write_str("b"),
+            Combo::RegC => f.write_str("c"),
+            Combo::Reserved => f.write_str("7"),
+        }
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Instruction::Adv(combo) => write!(f, "adv {combo}"),
+            Instruction::Bxl(lit) => write!(f, "bxl {lit}"),
+            Instruction::Bst(combo) => write!(f, "bst {combo}"),
+            Instruction::Jnz(lit) => write!(f, "jnz {lit}"),
+            Instruction::Bxc(0) => f.write_str("bxc"),
+            Instruction::Bxc(lit) => write!(f, "bxc {lit}"),
+            Instruction::Out(combo) => write!(f, "out {combo}"),
+            Instruction::Bdv(combo) => write!(f, "bdv {combo}"),
+            Instruction::Cdv(combo) => write!(f, "cdv {combo}"),
+        }
+    }
+}
+
+impl Instruction {
+    /// Opcode and operand of the instruction.
+    fn encode(self) -> [u8; 2] {
+        match self {
+            Instruction::Adv(combo) => [0, combo.encode()],
+            Instruction::Bxl(lit) => [1, lit],
+            Instruction::Bst(combo) => [2, combo.encode()],
+            Instruction::Jnz(lit) => [3, lit],
+            Instruction::Bxc(lit) => [4, lit],
+            Instruction::Out(combo) => [5, combo.encode()],
+            Instruction::Bdv(combo) => [6, combo.encode()],
+            Instruction::Cdv(combo) => [7, combo.encode()],
+        }
+    }
+
+    /// C like statement with the same effect.
+    fn to_c(self) -> String {
+        let value = |combo: Combo| match combo {
+            Combo::Reserved => "<reserved>".to_string(),
+            combo => combo.to_string(),
+        };
+        let low_bits = |combo: Combo| match combo {
+            Combo::Literal(lit) => lit.to_string(),
+            combo => format!("{} & 7", value(combo)),
+        };
+        match self {
+            Instruction::Adv(combo) => format!("a >>= {};", value(combo)),
+            Instruction::Bxl(lit) => format!("b ^= {lit};"),
+            Instruction::Bst(combo) => format!("b = {};", low_bits(combo)),
+            Instruction::Jnz(lit) => format!("if (a != 0) goto L{lit};"),
+            Instruction::Bxc(_) => "b ^= c;".to_string(),
+            Instruction::Out(combo) => format!("out({});", low_bits(combo)),
+            Instruction::Bdv(combo) => format!("b = a >> {};", value(combo)),
+            Instruction::Cdv(combo) => format!("c = a >> {};", value(combo)),
         }
     }
 }
 
 #[derive(Clone)]
-struct Machine {
-    a: i64,
-    b: i64,
-    c: i64,
+pub(crate) struct Machine {
+    pub(crate) a: i64,
+    pub(crate) b: i64,
+    pub(crate) c: i64,
 
-    ip: usize,
+    pub(crate) ip: usize,
 
-    instructions: Vec<Instruction>,
+    pub(crate) program: Vec<u8>,
+    pub(crate) instructions: Vec<Instruction>,
 }
 
 impl Machine {
-    fn resolve(&self, combo: &Combo) -> i64 {
+    fn resolve(&self, combo: &Combo) -> Result<i64, VmError> {
         match combo {
-            Combo::Literal(lit) => *lit as i64,
-            Combo::RegA => self.a,
-            Combo::RegB => self.b,
-            Combo::RegC => self.c,
-            Combo::Reserved => unreachable!(),
+            Combo::Literal(lit) => Ok(*lit as i64),
+            Combo::RegA => Ok(self.a),
+            Combo::RegB => Ok(self.b),
+            Combo::RegC => Ok(self.c),
+            Combo::Reserved => Err(VmError::InvalidOperand {
+                address: 2 * self.ip,
+                operand: 7,
+            }),
         }
     }
 
-    fn step(&mut self, output: &mut Vec<u8>) -> bool {
-        let instruction = &self.instructions[self.ip];
+    /// `a / 2^combo`, shifts beyond the register width leave only the sign.
+    fn divide(&self, combo: &Combo) -> Result<i64, VmError> {
+        match self.resolve(combo)? {
+            amount if amount < 0 => Err(VmError::NegativeShift {
+                address: 2 * self.ip,
+                amount,
+            }),
+            amount => Ok(self.a >> amount.min(63)),
+        }
+    }
+
+    /// Executes one instruction, returns false once the program halted.
+    pub(crate) fn step(&mut self, output: &mut Vec<u8>) -> Result<bool, VmError> {
+        let Some(instruction) = self.instructions.get(self.ip) else {
+            return Ok(false);
+        };
         match instruction {
             Instruction::Adv(combo) => {
-                self.a >>= self.resolve(combo);
+                self.a = self.divide(combo)?;
             }
             Instruction::Bxl(lit) => {
                 self.b = self.b ^ *lit as i64;
             }
             Instruction::Bst(combo) => {
-                self.b = self.resolve(combo) % 8;
+                self.b = self.resolve(combo)?.rem_euclid(8);
             }
             Instruction::Jnz(lit) => {
                 if self.a != 0 {
-                    self.ip = *lit as usize;
-                    return true;
+                    if lit % 2 != 0 {
+                        return Err(VmError::MisalignedJump {
+                            address: 2 * self.ip,
+                            target: *lit,
+                        });
+                    }
+                    // jump targets are program addresses, every instruction takes two of them
+                    self.ip = *lit as usize / 2;
+                    return Ok(self.ip < self.instructions.len());
                 }
             }
-            Instruction::Bxc => {
+            Instruction::Bxc(_) => {
                 self.b = self.b ^ self.c;
             }
-            Instruction::Out(combo) => output.push((self.resolve(combo) % 8) as u8),
+            Instruction::Out(combo) => output.push(self.resolve(combo)?.rem_euclid(8) as u8),
             Instruction::Bdv(combo) => {
-                self.b = self.a >> self.resolve(combo);
+                self.b = self.divide(combo)?;
             }
             Instruction::Cdv(combo) => {
-                self.c = self.a >> self.resolve(combo);
+                self.c = self.divide(combo)?;
             }
         }
         self.ip += 1;
-        self.ip < self.instructions.len()
+        Ok(self.ip < self.instructions.len())
     }
-    pub fn exec(&mut self) -> Vec<u8> {
+
+    /// Runs the program until it halts, failing once `budget` instructions were executed.
+    pub(crate) fn run(&mut self, budget: usize) -> Result<Vec<u8>, VmError> {
         let mut output = Vec::new();
-        while self.step(&mut output) {}
-        output
+        for _ in 0..budget {
+            if !self.step(&mut output)? {
+                return Ok(output);
+            }
+        }
+        match self.ip < self.instructions.len() {
+            true => Err(VmError::StepLimitExceeded(budget)),
+            false => Ok(output),
+        }
+    }
+
+    pub fn exec(&mut self) -> Result<Vec<u8>, VmError> {
+        self.run(STEP_BUDGET)
+    }
+
+    /// Whether the program is a single loop ending in `jnz 0` with one `out` and one `adv 3`,
+    /// so every iteration prints one value and shifts A right by three bits.
+    fn is_shift_loop(&self) -> bool {
+        let count = |shape: fn(&Instruction) -> bool| {
+            self.instructions
+                .iter()
+                .filter(|&instruction| shape(instruction))
+                .count()
+        };
+        matches!(self.instructions.last(), Some(Instruction::Jnz(0)))
+            && count(|instruction| matches!(instruction, Instruction::Jnz(_))) == 1
+            && count(|instruction| matches!(instruction, Instruction::Out(_))) == 1
+            && count(|instruction| matches!(instruction, Instruction::Adv(_))) == 1
+            && count(|instruction| matches!(instruction, Instruction::Adv(Combo::Literal(3)))) == 1
+    }
+
+    /// Lowest initial register A for which the program outputs itself, `None` for programs that
+    /// are not a shift loop.
+    ///
+    /// In a shift loop the last output only depends on the highest three bits of A.
+    /// Candidates are built from the end of the program, trying all 3-bit extensions and
+    /// keeping those that reproduce the tail of the program.
+    fn find_quine(&self) -> Option<i64> {
+        if !self.is_shift_loop() {
+            return None;
+        }
+        let mut candidates = vec![0_i64];
+        for start in (0..self.program.len()).rev() {
+            let tail = &self.program[start..];
+            candidates = candidates
+                .iter()
+                .filter(|&&a| a <= i64::MAX >> 3)
+                .flat_map(|&a| (0..8).map(move |bits| a << 3 | bits))
+                .filter(|&a| {
+                    // programs of this shape run a few instructions per output
+                    let mut machine = Machine { a, ..self.clone() };
+                    machine
+                        .run(STEP_BUDGET / 100)
+                        .is_ok_and(|output| output == tail)
+                })
+                .collect();
+        }
+        candidates.into_iter().filter(|&a| a > 0).min()
+    }
+
+    /// Program addresses that are targets of a jump and start an instruction.
+    fn jump_targets(&self) -> BTreeSet<usize> {
+        self.instructions
+            .iter()
+            .filter_map(|instruction| match instruction {
+                Instruction::Jnz(lit) => Some(*lit as usize),
+                _ => None,
+            })
+            .filter(|&target| target % 2 == 0 && target < self.program.len())
+            .collect()
+    }
+
+    /// Readable assembly with labelled jump targets, followed by a pseudo-C version of the program.
+    /// Everything besides the assembly is a comment, so the listing can be assembled again.
+    pub(crate) fn disassemble(&self) -> String {
+        let targets = self.jump_targets();
+        let mut lines = vec![format!("; A = {}, B = {}, C = {}", self.a, self.b, self.c)];
+        for (i, instruction) in self.instructions.iter().enumerate() {
+            let address = 2 * i;
+            if targets.contains(&address) {
+                lines.push(format!("L{address}:"));
+            }
+            let assembly = match instruction {
+                Instruction::Jnz(lit) if targets.contains(&(*lit as usize)) => {
+                    format!("jnz L{lit}")
+                }
+                instruction => instruction.to_string(),
+            };
+            lines.push(format!(
+                "    {assembly:<10} ; {address:>2}: {}",
+                instruction.to_c()
+            ));
+        }
+
+        lines.push(";".to_string());
+        lines.push("; pseudo-C".to_string());
+        let jumps = self
+            .instructions
+            .iter()
+            .filter(|instruction| matches!(instruction, Instruction::Jnz(_)))
+            .count();
+        match self.instructions.last() {
+            // the common shape: a single loop over the whole program
+            Some(Instruction::Jnz(0)) if jumps == 1 => {
+                lines.push("; do {".to_string());
+                for instruction in &self.instructions[..self.instructions.len() - 1] {
+                    lines.push(format!(";     {}", instruction.to_c()));
+                }
+                lines.push("; } while (a != 0);".to_string());
+            }
+            _ => {
+                for (i, instruction) in self.instructions.iter().enumerate() {
+                    if targets.contains(&(2 * i)) {
+                        lines.push(format!("; L{}:", 2 * i));
+                    }
+                    lines.push(format!(";     {}", instruction.to_c()));
+                }
+            }
+        }
+        lines.join("\n") + "\n"
     }
 }
 
-fn reg_line_to_value(lines: &mut Lines) -> i64 {
+fn reg_line_to_value(lines: &mut Lines, line: usize) -> Result<i64, ParseError> {
     lines
         .next()
-        .unwrap()
-        .split(':')
-        .skip(1)
-        .next()
-        .unwrap()
-        .trim()
-        .parse::<i64>()
-        .unwrap()
+        .and_then(|value| value.split(':').nth(1))
+        .and_then(|value| value.trim().parse::<i64>().ok())
+        .ok_or_else(|| ParseError::new(line, "expected `Register X: <number>`"))
 }
 
-impl From<&String> for Machine {
-    fn from(value: &String) -> Self {
+impl TryFrom<&String> for Machine {
+    type Error = ParseError;
+
+    fn try_from(value: &String) -> Result<Self, Self::Error> {
         let mut lines = value.lines();
-        let a = reg_line_to_value(&mut lines);
-        let b = reg_line_to_value(&mut lines);
-        let c = reg_line_to_value(&mut lines);
+        let a = reg_line_to_value(&mut lines, 0)?;
+        let b = reg_line_to_value(&mut lines, 1)?;
+        let c = reg_line_to_value(&mut lines, 2)?;
         lines.next();
-        let mut target: Vec<u8> = lines
+        let program = lines
             .next()
-            .unwrap()
+            .and_then(|line| line.strip_prefix("Program: "))
+            .ok_or_else(|| ParseError::new(4, "expected `Program: <numbers>`"))?;
+        Machine::new(a, b, c, program).map_err(|message| ParseError::new(4, message))
+    }
+}
+
+impl Machine {
+    /// Parses and decodes a comma separated program.
+    fn new(a: i64, b: i64, c: i64, program: &str) -> Result<Self, String> {
+        let target: Vec<u8> = program
             .split(",")
-            .map(|el| el.strip_prefix("Program: ").unwrap_or(el))
-            .filter_map(|el| el.parse::<u8>().ok())
-            .collect();
+            .map(|el| el.trim().parse::<u8>())
+            .collect::<Result<_, _>>()
+            .map_err(|err| err.to_string())?;
+        Machine::decode(a, b, c, target).map_err(|err| err.to_string())
+    }
 
-        let mut instructions = Vec::new();
-        for i in 0..target.len() / 2 {
-            let opcode = target[2 * i];
-            let operand = target[2 * i + 1];
-            instructions.push(Instruction::from((opcode, operand)));
+    fn decode(a: i64, b: i64, c: i64, target: Vec<u8>) -> Result<Self, VmError> {
+        if !target.len().is_multiple_of(2) {
+            return Err(VmError::OddProgramLength(target.len()));
         }
+        let instructions = target
+            .chunks(2)
+            .enumerate()
+            .map(|(i, pair)| Instruction::decode(2 * i, pair[0], pair[1]))
+            .collect::<Result<_, _>>()?;
 
-        target.reverse();
-
-        Self {
+        Ok(Self {
             a,
             b,
             c,
+            program: target,
             instructions,
             ip: 0,
+        })
+    }
+}
+
+/// Loads a program file, either a complete puzzle input or just the program numbers.
+pub(crate) fn load(input: &String) -> Result<Machine, ParseError> {
+    if input.trim_start().starts_with("Register") {
+        Machine::try_from(input)
+    } else {
+        let program = input.trim();
+        let program = program.strip_prefix("Program:").unwrap_or(program);
+        Machine::new(0, 0, 0, program).map_err(|message| ParseError::new(0, message))
+    }
+}
+
+/// Disassembles a program file into labelled assembly followed by a pseudo-C version.
+pub fn disassemble(input: &String) -> Result<String, ParseError> {
+    load(input).map(|machine| machine.disassemble())
+}
+
+fn parse_combo(operand: &str) -> Result<Combo, String> {
+    match operand {
+        "a" | "A" => Ok(Combo::RegA),
+        "b" | "B" => Ok(Combo::RegB),
+        "c" | "C" => Ok(Combo::RegC),
+        _ => match operand.parse::<u8>() {
+            Ok(lit @ 0..=3) => Ok(Combo::Literal(lit)),
+            Ok(4..=6) => Err(format!(
+                "write combo operand {operand} as register a, b or c"
+            )),
+            Ok(7) => Err("combo operand 7 is reserved".to_string()),
+            _ => Err(format!("invalid combo operand `{operand}`")),
+        },
+    }
+}
+
+fn parse_literal(operand: &str) -> Result<Literal, String> {
+    operand
+        .parse::<u8>()
+        .ok()
+        .filter(|&lit| lit < 8)
+        .ok_or_else(|| format!("invalid literal operand `{operand}`, expected 0 to 7"))
+}
+
+fn assemble_instruction(
+    mnemonic: &str,
+    operand: Option<&str>,
+    labels: &HashMap<&str, usize>,
+) -> Result<Instruction, String> {
+    let required = || operand.ok_or_else(|| format!("`{mnemonic}` needs an operand"));
+    Ok(match mnemonic {
+        "adv" => Instruction::Adv(parse_combo(required()?)?),
+        "bxl" => Instruction::Bxl(parse_literal(required()?)?),
+        "bst" => Instruction::Bst(parse_combo(required()?)?),
+        "jnz" => {
+            let target = required()?;
+            match labels.get(target) {
+                Some(&address) if address < 8 => Instruction::Jnz(address as Literal),
+                Some(address) => {
+                    return Err(format!(
+                        "label `{target}` at address {address} does not fit in a 3-bit operand"
+                    ))
+                }
+                None => Instruction::Jnz(
+                    parse_literal(target).map_err(|_| format!("unknown label `{target}`"))?,
+                ),
+            }
+        }
+        // the operand of bxc is ignored, but may be given to reproduce a program exactly
+        "bxc" => Instruction::Bxc(operand.map(parse_literal).transpose()?.unwrap_or(0)),
+        "out" => Instruction::Out(parse_combo(required()?)?),
+        "bdv" => Instruction::Bdv(parse_combo(required()?)?),
+        "cdv" => Instruction::Cdv(parse_combo(required()?)?),
+        _ => return Err(format!("unknown mnemonic `{mnemonic}`")),
+    })
+}
+
+/// Assembles mnemonic source, one instruction per line.
+/// Comments start with `;`, labels end with `:` and may precede an instruction on the same line.
+fn assemble_program(source: &str) -> Result<Vec<Instruction>, ParseError> {
+    let mut labels: HashMap<&str, usize> = HashMap::new();
+    let mut statements = Vec::new();
+    for (i, line) in source.lines().enumerate() {
+        let mut line = line.split(';').next().unwrap_or_default().trim();
+        if let Some((label, rest)) = line.split_once(':') {
+            let label = label.trim();
+            if label.is_empty() || !label.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                return Err(ParseError::new(i, format!("invalid label `{label}`")));
+            }
+            if labels.insert(label, 2 * statements.len()).is_some() {
+                return Err(ParseError::new(i, format!("label `{label}` defined twice")));
+            }
+            line = rest.trim();
         }
+        let mut words = line.split_whitespace();
+        let Some(mnemonic) = words.next() else {
+            continue;
+        };
+        let operand = words.next();
+        if words.next().is_some() {
+            return Err(ParseError::new(i, "expected at most one operand"));
+        }
+        statements.push((i, mnemonic, operand));
     }
+
+    statements
+        .into_iter()
+        .map(|(i, mnemonic, operand)| {
+            assemble_instruction(mnemonic, operand, &labels)
+                .map_err(|message| ParseError::new(i, message))
+        })
+        .collect()
+}
+
+/// Assembles a program and returns it as puzzle input with the given initial registers.
+pub fn assemble(source: &str, registers: [i64; 3]) -> Result<String, ParseError> {
+    let program: Vec<String> = assemble_program(source)?
+        .into_iter()
+        .flat_map(Instruction::encode)
+        .map(|value| value.to_string())
+        .collect();
+    let [a, b, c] = registers;
+    Ok(format!(
+        "Register A: {a}\nRegister B: {b}\nRegister C: {c}\n\nProgram: {}",
+        program.join(",")
+    ))
 }
 
 impl Debug for Machine {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.write_fmt(format_args!("A: {}, B: {}, C: {}", self.a, self.b, self.c))?;
-        f.write_fmt(format_args!(" IP: {} | {:?}", self.ip, self.instructions))
+        let instructions: Vec<String> = self
+            .instructions
+            .iter()
+            .map(Instruction::to_string)
+            .collect();
+        f.write_fmt(format_args!(
+            " IP: {} | {}",
+            self.ip,
+            instructions.join("; ")
+        ))
     }
 }
 
@@ -187,14 +626,204 @@ impl Aoc2024 for Puzzle17 {
     }
 
     fn solve_a(&self, input: &String) -> String {
-        let machine = Machine::from(input);
+        let machine = match Machine::try_from(input) {
+            Ok(machine) => machine,
+            Err(err) => return err.to_string(),
+        };
+
+        match machine.clone().exec() {
+            Ok(output) => output_to_string(output),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    fn solve_b(&self, input: &String) -> String {
+        let machine = match Machine::try_from(input) {
+            Ok(machine) => machine,
+            Err(err) => return err.to_string(),
+        };
+
+        // the bitwise search is fast for the usual loop, symbolic execution handles any other shape
+        let quine = match machine.find_quine() {
+            Some(a) => Ok(Some(a)),
+            None => symbolic::solve(&machine, &machine.program),
+        };
+        match quine {
+            Ok(Some(a)) => a.to_string(),
+            Ok(None) => "No value of register A makes the program output itself".to_string(),
+            Err(err) => format!("Symbolic execution gave up: {err}"),
+        }
+    }
+
+    fn parse(&self, input: &String) -> Result<(), ParseError> {
+        Machine::try_from(input).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate;
+
+    #[test]
+    fn jumps_to_program_addresses() {
+        // `jnz 2` resumes at the second instruction, `adv 1`, not at the third one
+        let input = "Register A: 4\nRegister B: 0\nRegister C: 0\n\nProgram: 5,4,0,1,5,4,3,2\n";
+        assert_eq!(Puzzle17 {}.solve_a(&input.to_string()), "4, 2, 1, 0");
+    }
 
-        let output = machine.clone().exec();
-        output_to_string(output)
+    #[test]
+    fn rejects_bad_programs() {
+        let decode = |program: Vec<u8>| Machine::decode(0, 0, 0, program).map(|_| ());
+        assert_eq!(
+            decode(vec![0, 1, 8, 0]),
+            Err(VmError::BadOpcode {
+                address: 2,
+                opcode: 8
+            })
+        );
+        assert_eq!(decode(vec![0, 1, 5]), Err(VmError::OddProgramLength(3)));
+        assert_eq!(
+            decode(vec![5, 9]),
+            Err(VmError::InvalidOperand {
+                address: 0,
+                operand: 9
+            })
+        );
+        let input = "Register A: 0\nRegister B: 0\nRegister C: 0\n\nProgram: 0,1,5\n".to_string();
+        assert_eq!(Puzzle17 {}.parse(&input).map_err(|err| err.line), Err(4));
+    }
+
+    #[test]
+    fn reports_runtime_errors() {
+        // the reserved combo operand decodes, but fails once it is used
+        let mut machine = Machine::decode(8, 0, 0, vec![0, 1, 5, 7]).unwrap();
+        assert_eq!(
+            machine.exec(),
+            Err(VmError::InvalidOperand {
+                address: 2,
+                operand: 7
+            })
+        );
+        let mut machine = Machine::decode(1, 0, 0, vec![3, 1]).unwrap();
+        assert_eq!(
+            machine.exec(),
+            Err(VmError::MisalignedJump {
+                address: 0,
+                target: 1
+            })
+        );
+        let mut machine = Machine::decode(1, -1, 0, vec![0, 5]).unwrap();
+        assert_eq!(
+            machine.exec(),
+            Err(VmError::NegativeShift {
+                address: 0,
+                amount: -1
+            })
+        );
+    }
+
+    #[test]
+    fn stops_programs_that_never_halt() {
+        // `jnz 0` never changes A
+        let mut machine = Machine::decode(1, 0, 0, vec![3, 0]).unwrap();
+        assert_eq!(machine.exec(), Err(VmError::StepLimitExceeded(STEP_BUDGET)));
+        // the budget is not exceeded by a program halting on its last allowed step
+        let mut machine = Machine::decode(3, 0, 0, vec![0, 1, 3, 0]).unwrap();
+        assert_eq!(machine.run(4), Ok(Vec::new()));
+        let mut machine = Machine::decode(3, 0, 0, vec![0, 1, 3, 0]).unwrap();
+        assert_eq!(machine.run(3), Err(VmError::StepLimitExceeded(3)));
+    }
+
+    #[test]
+    fn finds_the_quine_of_a_shift_loop() {
+        let input = "Register A: 2024\nRegister B: 0\nRegister C: 0\n\nProgram: 0,3,5,4,3,0\n";
+        let machine = Machine::try_from(&input.to_string()).unwrap();
+        assert!(machine.is_shift_loop());
+        assert_eq!(machine.find_quine(), Some(117440));
+        assert_eq!(Puzzle17 {}.solve_b(&input.to_string()), "117440");
+    }
+
+    #[test]
+    fn falls_back_for_other_shapes() {
+        // shifting by one bit per output, the windows of A overlap and contradict each other
+        let input = "Register A: 0\nRegister B: 0\nRegister C: 0\n\nProgram: 0,1,5,4,3,0\n";
+        let machine = Machine::try_from(&input.to_string()).unwrap();
+        assert!(!machine.is_shift_loop());
+        assert_eq!(machine.find_quine(), None);
+        assert_eq!(
+            Puzzle17 {}.solve_b(&input.to_string()),
+            "No value of register A makes the program output itself"
+        );
+        // two outputs per iteration
+        let machine = load(&"0,3,5,4,5,4,3,0".to_string()).unwrap();
+        assert!(!machine.is_shift_loop());
+    }
+
+    #[test]
+    fn assembles_what_it_disassembles() {
+        let source = "\
+; prints A three bits at a time
+loop:   adv 3     ; drop the printed bits
+        out a
+        jnz loop
+";
+        let input = assemble(source, [2024, 0, 0]).unwrap();
+        assert_eq!(
+            input,
+            "Register A: 2024\nRegister B: 0\nRegister C: 0\n\nProgram: 0,3,5,4,3,0"
+        );
+        let listing = disassemble(&input).unwrap();
+        assert_eq!(assemble(&listing, [2024, 0, 0]), Ok(input));
+
+        for seed in 1..=3 {
+            let input = generate::generate(17, generate::default_size(17), seed).unwrap();
+            let machine = load(&input).unwrap();
+            let listing = disassemble(&input).unwrap();
+            let assembled = assemble(&listing, [machine.a, machine.b, machine.c]).unwrap();
+            assert_eq!(
+                load(&assembled).unwrap().program,
+                machine.program,
+                "{listing}"
+            );
+        }
+    }
+
+    #[test]
+    fn reports_assembler_errors() {
+        let errors = [
+            ("adv 3\n1st-loop: out a\n", 1, "invalid label `1st-loop`"),
+            ("top: adv 3\ntop: out a\n", 1, "label `top` defined twice"),
+            ("adv 3\njnz nowhere\n", 1, "unknown label `nowhere`"),
+            (
+                "adv 1\nadv 1\nadv 1\nadv 1\nfar: out a\njnz far\n",
+                5,
+                "label `far` at address 8 does not fit in a 3-bit operand",
+            ),
+            ("out 7\n", 0, "combo operand 7 is reserved"),
+            ("out 4\n", 0, "write combo operand 4 as register a, b or c"),
+            ("bxl 8\n", 0, "invalid literal operand `8`, expected 0 to 7"),
+            ("; comment\nmul 3\n", 1, "unknown mnemonic `mul`"),
+            ("adv\n", 0, "`adv` needs an operand"),
+            (
+                "adv 3 ; out a\nout a b\n",
+                1,
+                "expected at most one operand",
+            ),
+        ];
+        for (source, line, message) in errors {
+            assert_eq!(
+                assemble(source, [0, 0, 0]),
+                Err(ParseError::new(line, message)),
+                "{source:?}"
+            );
+        }
     }
 
-    fn solve_b(&self, _input: &String) -> String {
-        // use dynamic programming TODO
-        "Not implmented".to_string()
+    #[test]
+    fn keeps_the_bxc_operand() {
+        let listing = disassemble(&"4,5,5,5".to_string()).unwrap();
+        let input = assemble(&listing, [0, 0, 0]).unwrap();
+        assert!(input.ends_with("Program: 4,5,5,5"), "{input}");
     }
 }
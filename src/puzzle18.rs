@@ -1,5 +1,5 @@
-use crate::prelude::Aoc2024;
-use std::cmp::Ordering;
+use crate::prelude::{Aoc2024, ParseError};
+use std::collections::VecDeque;
 use std::fmt::{Debug, Formatter, Write};
 
 #[derive(Hash, Eq, PartialEq, Debug, Clone)]
@@ -41,44 +41,65 @@ struct Node {
     pub score: usize,
 }
 
+/// Side length of the puzzle's memory grid.
+const SIZE: usize = 71;
+/// Number of fallen bytes part a looks at in the puzzle.
+const BYTES: usize = 1024;
+/// Largest grid area accepted, the searches keep a node per cell.
+const MAX_CELLS: usize = 1 << 20;
+
 #[derive(PartialEq, Eq, Clone)]
 struct Board {
     grid: Vec<Vec<Node>>,
-    size: usize,
+    width: usize,
+    height: usize,
 }
 
 impl Board {
-    pub fn new(positions: &Vec<Position>, step: usize, size: usize) -> Self {
+    /// Grid with the first `step` bytes fallen, a byte at `X,Y` blocks column `X` of row `Y`.
+    pub fn new(memory: &Memory, step: usize) -> Self {
         let mut grid: Vec<Vec<Node>> = Vec::new();
         let mut row: Vec<Node> = Vec::new();
-        row.resize(size, Node::new(Field::FREE));
-        grid.resize(size, row);
-        for position in positions.iter().take(step) {
-            grid[position.x][position.y] = Node::new(Field::BLOCKED);
+        row.resize(memory.width, Node::new(Field::FREE));
+        grid.resize(memory.height, row);
+        for position in memory.positions.iter().take(step) {
+            grid[position.y][position.x] = Node::new(Field::BLOCKED);
         }
         grid[0][0] = Node::new(Field::START);
-        grid[size - 1][size - 1] = Node::new(Field::END);
-        Self { grid, size }
+        grid[memory.height - 1][memory.width - 1] = Node::new(Field::END);
+        Self {
+            grid,
+            width: memory.width,
+            height: memory.height,
+        }
     }
 
-    fn dfs(&mut self, x: i32, y: i32, score: usize) {
+    /// Scores every cell with its distance from the cell in row `x` and column `y`. Breadth first,
+    /// so large grids neither revisit cells nor run out of stack.
+    fn bfs(&mut self, x: i32, y: i32) {
         let directions = [
             Direction::UP,
             Direction::RIGHT,
             Direction::LEFT,
             Direction::DOWN,
         ];
-        if x < 0 || x >= self.size as i32 || y < 0 || y >= self.size as i32 {
-            return;
-        }
-        let node = self.grid[x as usize][y as usize];
-
-        if score < node.score {
-            self.grid[x as usize][y as usize].score = score;
-            if node.field == Field::FREE || node.field == Field::START {
-                for direction in directions {
-                    let (dx, dy) = direction.delta();
-                    self.dfs(x + dx, y + dy, score + 1);
+        self.grid[x as usize][y as usize].score = 0;
+        let mut queue = VecDeque::from([(x, y)]);
+        while let Some((x, y)) = queue.pop_front() {
+            let node = self.grid[x as usize][y as usize];
+            if node.field != Field::FREE && node.field != Field::START {
+                continue;
+            }
+            for direction in directions {
+                let (dx, dy) = direction.delta();
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < 0 || nx >= self.height as i32 || ny < 0 || ny >= self.width as i32 {
+                    continue;
+                }
+                let next = &mut self.grid[nx as usize][ny as usize];
+                if next.field != Field::BLOCKED && next.score == usize::MAX {
+                    next.score = node.score + 1;
+                    queue.push_back((nx, ny));
                 }
             }
         }
@@ -142,13 +163,109 @@ impl Direction {
     }
 }
 
-impl From<&str> for Position {
-    fn from(value: &str) -> Self {
-        let mut parts = value.split(",").filter_map(|num| num.parse::<usize>().ok());
-        Self {
-            x: parts.next().unwrap(),
-            y: parts.next().unwrap(),
+impl Position {
+    fn parse(value: &str) -> Option<Self> {
+        let (x, y) = value.split_once(',')?;
+        Some(Self {
+            x: x.trim().parse().ok()?,
+            y: y.trim().parse().ok()?,
+        })
+    }
+}
+
+/// Parses a grid size given as `<width>x<height>`, or a single number for a square grid. The
+/// grid needs room for a start and an exit apart from it and at most `MAX_CELLS` cells.
+pub fn parse_size(value: &str) -> Option<(usize, usize)> {
+    let (width, height) = value.split_once('x').unwrap_or((value, value));
+    let size: (usize, usize) = (width.trim().parse().ok()?, height.trim().parse().ok()?);
+    let area = size.0.checked_mul(size.1)?;
+    (2..=MAX_CELLS).contains(&area).then_some(size)
+}
+
+/// The memory space: its size, how many bytes part a lets fall and where the bytes fall.
+struct Memory {
+    width: usize,
+    height: usize,
+    bytes: usize,
+    positions: Vec<Position>,
+}
+
+impl Memory {
+    /// Reads the falling bytes, optionally preceded by `size <width>x<height>` and `bytes <n>`
+    /// header lines. `size` and `bytes` override the header, the puzzle's 71x71 grid and 1024
+    /// bytes are used when neither gives them.
+    fn parse(
+        input: &String,
+        size: Option<(usize, usize)>,
+        bytes: Option<usize>,
+    ) -> Result<Self, ParseError> {
+        let (mut width, mut height, mut header_bytes) = (SIZE, SIZE, BYTES);
+        let mut positions = Vec::new();
+        let mut lines = Vec::new();
+        for (index, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let header = line.strip_prefix("size ").map(|value| ("size", value));
+            let header = header.or(line.strip_prefix("bytes ").map(|value| ("bytes", value)));
+            match header {
+                Some(_) if !positions.is_empty() => {
+                    return Err(ParseError::new(
+                        index,
+                        "header lines must precede the bytes",
+                    ));
+                }
+                Some(("size", value)) => {
+                    (width, height) = parse_size(value).ok_or_else(|| {
+                        ParseError::new(
+                            index,
+                            format!("invalid grid size `{value}`, it needs 2 to {MAX_CELLS} cells"),
+                        )
+                    })?;
+                }
+                Some((_, value)) => {
+                    header_bytes = value.trim().parse().map_err(|_| {
+                        ParseError::new(index, format!("invalid byte count `{value}`"))
+                    })?;
+                }
+                None => {
+                    let position = Position::parse(line).ok_or_else(|| {
+                        ParseError::new(index, format!("expected `X,Y` but got `{line}`"))
+                    })?;
+                    positions.push(position);
+                    lines.push(index);
+                }
+            }
+        }
+        let (width, height) = size.unwrap_or((width, height));
+        for (position, &index) in positions.iter().zip(&lines) {
+            if position.x >= width || position.y >= height {
+                return Err(ParseError::new(
+                    index,
+                    format!(
+                        "byte at {},{} is outside the {width}x{height} grid",
+                        position.x, position.y
+                    ),
+                ));
+            }
         }
+        Ok(Self {
+            width,
+            height,
+            bytes: bytes.unwrap_or(header_bytes),
+            positions,
+        })
+    }
+
+    /// Steps from the top left to the bottom right once the first `step` bytes have fallen.
+    fn shortest_path(&self, step: usize) -> Option<usize> {
+        let mut board = Board::new(self, step);
+        let (x, y) = get_position(&board.grid, Field::START).expect("START not found");
+        board.bfs(x, y);
+        let (end_x, end_y) = get_position(&board.grid, Field::END).expect("END not found");
+        let end_score = board.grid[end_x as usize][end_y as usize].score;
+        (end_score != usize::MAX).then_some(end_score)
     }
 }
 
@@ -163,13 +280,152 @@ fn get_position(board: &Vec<Vec<Node>>, field: Field) -> Option<(i32, i32)> {
     None
 }
 
-fn get_positions(input: &String) -> Vec<Position> {
-    input
-        .lines()
-        .map(|line| line.trim())
-        .filter(|&arg| !str::is_empty(arg))
-        .map(Position::from)
-        .collect()
+/// Union-find over the grid cells with path halving and union by size.
+struct DisjointSet {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+        }
+    }
+
+    fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        if self.size[a] < self.size[b] {
+            (a, b) = (b, a);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+    }
+}
+
+/// Free cells of the grid joined into their connected areas. Cells can only be freed, which is
+/// why the bytes are replayed backwards.
+struct Connectivity {
+    width: usize,
+    height: usize,
+    free: Vec<bool>,
+    sets: DisjointSet,
+}
+
+impl Connectivity {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            free: vec![false; width * height],
+            sets: DisjointSet::new(width * height),
+        }
+    }
+
+    fn free(&mut self, cell: usize) {
+        self.free[cell] = true;
+        let (x, y) = (cell / self.width, cell % self.width);
+        for direction in [
+            Direction::UP,
+            Direction::RIGHT,
+            Direction::DOWN,
+            Direction::LEFT,
+        ] {
+            let (dx, dy) = direction.delta();
+            let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+            if nx < 0 || nx >= self.height as i32 || ny < 0 || ny >= self.width as i32 {
+                continue;
+            }
+            let neighbour = nx as usize * self.width + ny as usize;
+            if self.free[neighbour] {
+                self.sets.union(cell, neighbour);
+            }
+        }
+    }
+
+    /// Whether the top left start and the bottom right exit are in the same area.
+    fn exit_reachable(&mut self) -> bool {
+        let exit = self.width * self.height - 1;
+        self.sets.find(0) == self.sets.find(exit)
+    }
+}
+
+/// Whether the exit is reachable once the first `k` bytes have fallen, for every `k` from 0 to
+/// the number of bytes. Starting with all bytes fallen, the bytes are taken back in reverse order
+/// and the cells they free joined with their neighbours, so all answers come from one near linear
+/// pass. Like `Board::new` the start and the exit stay free even if a byte falls on them.
+fn reachability(memory: &Memory) -> Vec<bool> {
+    let cells = memory.width * memory.height;
+    let cell = |position: &Position| position.y * memory.width + position.x;
+    let mut first_byte = vec![None; cells];
+    for (index, position) in memory.positions.iter().enumerate().rev() {
+        first_byte[cell(position)] = Some(index);
+    }
+    first_byte[0] = None;
+    first_byte[cells - 1] = None;
+
+    let mut connectivity = Connectivity::new(memory.width, memory.height);
+    for free in (0..cells).filter(|&free| first_byte[free].is_none()) {
+        connectivity.free(free);
+    }
+    let mut reachable = vec![false; memory.positions.len() + 1];
+    reachable[memory.positions.len()] = connectivity.exit_reachable();
+    for (index, position) in memory.positions.iter().enumerate().rev() {
+        if first_byte[cell(position)] == Some(index) {
+            connectivity.free(cell(position));
+        }
+        reachable[index] = connectivity.exit_reachable();
+    }
+    reachable
+}
+
+/// Explores a memory space for `bytes <file> [--size <width>x<height>] [--bytes <n>]
+/// [--after <k>]`: the shortest path after the byte cutoff, the first byte cutting off the exit
+/// and with `after` whether the exit can still be reached once that many bytes have fallen.
+pub fn explore(
+    input: &String,
+    size: Option<(usize, usize)>,
+    bytes: Option<usize>,
+    after: Option<usize>,
+) -> Result<String, ParseError> {
+    let memory = Memory::parse(input, size, bytes)?;
+    let mut output = match memory.shortest_path(memory.bytes) {
+        Some(steps) => format!("{steps} steps to the exit after {} bytes\n", memory.bytes),
+        None => format!("no path to the exit after {} bytes\n", memory.bytes),
+    };
+    let reachable = reachability(&memory);
+    output.push_str(&match reachable.iter().position(|&reachable| !reachable) {
+        Some(k) => format!(
+            "exit reachable for up to {} bytes, byte {k} at {},{} blocks it\n",
+            k - 1,
+            memory.positions[k - 1].x,
+            memory.positions[k - 1].y
+        ),
+        None => format!(
+            "exit reachable after all {} bytes\n",
+            memory.positions.len()
+        ),
+    });
+    if let Some(k) = after {
+        output.push_str(&match reachable.get(k) {
+            Some(true) => format!("after {k} bytes the exit is reachable\n"),
+            Some(false) => format!("after {k} bytes the exit is blocked\n"),
+            None => format!("only {} bytes fall\n", memory.positions.len()),
+        });
+    }
+    Ok(output)
 }
 
 pub struct Puzzle18 {}
@@ -180,43 +436,134 @@ impl Aoc2024 for Puzzle18 {
     }
 
     fn solve_a(&self, input: &String) -> String {
-        let positions = get_positions(input);
+        let memory = match Memory::parse(input, None, None) {
+            Ok(memory) => memory,
+            Err(err) => return err.to_string(),
+        };
+        match memory.shortest_path(memory.bytes) {
+            Some(steps) => steps.to_string(),
+            None => "No path to the exit".to_string(),
+        }
+    }
 
-        let mut board = Board::new(&positions, 1024, 71);
+    fn solve_b(&self, input: &String) -> String {
+        let memory = match Memory::parse(input, None, None) {
+            Ok(memory) => memory,
+            Err(err) => return err.to_string(),
+        };
+        let reachable = reachability(&memory);
 
-        let (x, y) = get_position(&board.grid, Field::START).expect("START not found");
+        // the start and the exit are always free, so with no bytes the exit is reachable
+        match reachable.iter().position(|&reachable| !reachable) {
+            Some(k) => format!(
+                "{},{}",
+                memory.positions[k - 1].x,
+                memory.positions[k - 1].y
+            ),
+            None => "None found".to_string(),
+        }
+    }
+
+    fn parse(&self, input: &String) -> Result<(), ParseError> {
+        Memory::parse(input, None, None)?;
+        Ok(())
+    }
+}
 
-        board.dfs(x, y, 0);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate;
 
-        let (end_x, end_y) = get_position(&board.grid, Field::END).expect("END not found");
+    /// The bytes of the puzzle's 7x7 example.
+    const EXAMPLE: &str = "\
+5,4\n4,2\n4,5\n3,0\n2,1\n6,3\n2,4\n1,5\n\
+0,6\n3,3\n2,6\n5,1\n1,2\n5,5\n2,5\n6,5\n\
+1,4\n0,4\n6,4\n1,1\n6,1\n1,0\n0,5\n1,6\n\
+2,0\n";
 
-        let end_score = board.grid[end_x as usize][end_y as usize].score;
-        end_score.to_string()
+    #[test]
+    fn solves_the_example() {
+        let input = format!("size 7x7\nbytes 12\n{EXAMPLE}");
+        assert_eq!(Puzzle18 {}.solve_a(&input), "22");
+        assert_eq!(Puzzle18 {}.solve_b(&input), "6,1");
     }
 
-    fn solve_b(&self, input: &String) -> String {
-        let positions = get_positions(input);
-        let pos_index: Vec<usize> = (0..positions.len()).collect();
+    #[test]
+    fn parses_generated_input() {
+        let input = generate::generate(18, generate::default_size(18), 1).unwrap();
+        assert_eq!(Puzzle18 {}.parse(&input), Ok(()));
+    }
 
-        let board = Board::new(&positions, 1024, 71);
-        let (end_x, end_y) = get_position(&board.grid, Field::END).expect("END not found");
+    #[test]
+    fn rejects_broken_input() {
+        let inputs = [
+            // a byte outside the grid from the header
+            ("size 7x7\n5,4\n7,2\n", 2),
+            // a byte outside the puzzle's 71x71 grid
+            ("5,4\n70,70\n3,71\n", 2),
+            // a header after the bytes
+            ("5,4\nsize 7x7\n", 1),
+            // a grid with room for the start only
+            ("size 1x1\n0,0\n", 0),
+            // a grid whose area overflows
+            ("bytes 3\nsize 9999999999x9999999999\n0,0\n", 1),
+        ];
+        for (input, line) in inputs {
+            let result = Puzzle18 {}.parse(&input.to_string());
+            assert_eq!(result.map_err(|err| err.line), Err(line), "{input:?}");
+        }
+        let result = Memory::parse(&"6,6\n7,0\n".to_string(), Some((7, 7)), None);
+        assert_eq!(
+            result.map(|_| ()),
+            Err(ParseError::new(1, "byte at 7,0 is outside the 7x7 grid"))
+        );
+    }
 
-        let target_index = pos_index.binary_search_by(|&index| {
-            let mut board = Board::new(&positions, index, 71);
-            board.dfs(0, 0, 0);
-            let new_end_score = board.grid[end_x as usize][end_y as usize].score;
-            if new_end_score < usize::MAX {
-                Ordering::Less
-            } else {
-                Ordering::Greater
-            }
-        });
+    #[test]
+    fn parses_sizes() {
+        assert_eq!(parse_size("7x5"), Some((7, 5)));
+        assert_eq!(parse_size("71"), Some((71, 71)));
+        assert_eq!(parse_size(" 2 x 1 "), Some((2, 1)));
+        assert_eq!(parse_size("1024x1024"), Some((1024, 1024)));
+        let invalid = [
+            "",
+            "0",
+            "1",
+            "1x1",
+            "0x9",
+            "9x0",
+            "-3x3",
+            "7x",
+            "x7",
+            "7x7x7",
+            "ax7",
+            // more cells than the searches are given room for, or than fit in a usize
+            "1025x1024",
+            "4294967296x4294967296",
+        ];
+        for size in invalid {
+            assert_eq!(parse_size(size), None, "{size:?}");
+        }
+    }
 
-        if let Err(target_index) = target_index {
-            let position = &positions[target_index - 1];
-            format!("{},{}", position.x, position.y)
-        } else {
-            "None found".to_string()
+    #[test]
+    fn finds_a_path_in_generated_grids() {
+        for seed in 1..20 {
+            let input = generate::generate(18, 12, seed).unwrap();
+            let steps = Puzzle18 {}.solve_a(&input);
+            assert!(steps.parse::<usize>().is_ok(), "seed {seed}: {steps}");
         }
     }
+
+    #[test]
+    fn explores_the_example_with_options() {
+        let output = explore(&EXAMPLE.to_string(), Some((7, 7)), Some(12), Some(20)).unwrap();
+        assert_eq!(
+            output,
+            "22 steps to the exit after 12 bytes\n\
+             exit reachable for up to 20 bytes, byte 21 at 6,1 blocks it\n\
+             after 20 bytes the exit is reachable\n"
+        );
+    }
 }
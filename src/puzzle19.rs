@@ -1,4 +1,4 @@
-use crate::prelude::Aoc2024;
+use crate::prelude::{Aoc2024, ParseError};
 use std::collections::HashMap;
 
 struct PatternStore<'a> {
@@ -31,6 +31,39 @@ impl<'a> PatternStore<'a> {
     }
 }
 
+fn is_stripes(value: &str) -> bool {
+    !value.is_empty() && value.bytes().all(|c| c.is_ascii_lowercase())
+}
+
+/// Parses the comma separated towels on the first line and the designs after the blank line,
+/// both are made of lowercase stripe colors.
+fn get_towels_and_designs(input: &str) -> Result<(Vec<&str>, Vec<&str>), ParseError> {
+    let mut lines = input.lines().map(str::trim).enumerate();
+    let towels: Vec<&str> = match lines.next() {
+        Some((_, line)) => line.split(',').map(str::trim).collect(),
+        None => return Err(ParseError::new(0, "expected a list of towels")),
+    };
+    if !towels.iter().all(|towel| is_stripes(towel)) {
+        return Err(ParseError::new(0, "expected towels like `r, wr, b`"));
+    }
+    if let Some((i, line)) = lines.next() {
+        if !line.is_empty() {
+            return Err(ParseError::new(i, "expected a blank line after the towels"));
+        }
+    }
+    let mut designs = Vec::new();
+    for (i, line) in lines.filter(|(_, line)| !line.is_empty()) {
+        if !is_stripes(line) {
+            return Err(ParseError::new(
+                i,
+                "expected a design of lowercase stripe colors",
+            ));
+        }
+        designs.push(line);
+    }
+    Ok((towels, designs))
+}
+
 pub struct Puzzle19 {}
 
 impl Aoc2024 for Puzzle19 {
@@ -39,9 +72,10 @@ impl Aoc2024 for Puzzle19 {
     }
 
     fn solve_a(&self, input: &String) -> String {
-        let mut parts = input.split("\n\n");
-        let towels: Vec<&str> = parts.next().unwrap().split(",").map(str::trim).collect();
-        let designs: Vec<&str> = parts.next().unwrap().split("\n").map(str::trim).collect();
+        let (towels, designs) = match get_towels_and_designs(input) {
+            Ok(parts) => parts,
+            Err(err) => return err.to_string(),
+        };
         let mut pattern_store = PatternStore::new(towels);
 
         let mut possible_patterns = 0;
@@ -54,9 +88,10 @@ impl Aoc2024 for Puzzle19 {
     }
 
     fn solve_b(&self, input: &String) -> String {
-        let mut parts = input.split("\n\n");
-        let towels: Vec<&str> = parts.next().unwrap().split(",").map(str::trim).collect();
-        let designs: Vec<&str> = parts.next().unwrap().split("\n").map(str::trim).collect();
+        let (towels, designs) = match get_towels_and_designs(input) {
+            Ok(parts) => parts,
+            Err(err) => return err.to_string(),
+        };
         let mut pattern_store = PatternStore::new(towels);
 
         let mut possible_designs = 0;
@@ -65,4 +100,34 @@ impl Aoc2024 for Puzzle19 {
         }
         possible_designs.to_string()
     }
+
+    fn parse(&self, input: &String) -> Result<(), ParseError> {
+        get_towels_and_designs(input).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate;
+
+    #[test]
+    fn parses_generated_input() {
+        let input = generate::generate(19, generate::default_size(19), 1).unwrap();
+        assert_eq!(Puzzle19 {}.parse(&input), Ok(()));
+    }
+
+    #[test]
+    fn rejects_broken_input() {
+        let inputs = [
+            // noise used to unwrap the missing designs
+            ("\u{c}7Or\u{19}<'srh\u{1b}Q", 0),
+            // a design with an unknown color
+            ("r, wr, b\n\nbwu-r\n", 2),
+        ];
+        for (input, line) in inputs {
+            let result = Puzzle19 {}.parse(&input.to_string());
+            assert_eq!(result.map_err(|err| err.line), Err(line), "{input:?}");
+        }
+    }
 }
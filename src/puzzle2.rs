@@ -1,4 +1,4 @@
-use crate::prelude::Aoc2024;
+use crate::prelude::{Aoc2024, ParseError};
 
 fn is_safe<'a, I>(levels: I) -> bool
 where
@@ -52,14 +52,19 @@ fn is_safe_with_dampener(levels: &[i64]) -> bool {
 pub struct Puzzle2 {}
 
 impl Puzzle2 {
-    fn get_reports(input: &String) -> Vec<Vec<i64>> {
+    /// Levels of every report, 32 bit numbers so the differences between them fit.
+    fn get_reports(input: &str) -> Result<Vec<Vec<i64>>, ParseError> {
         input
             .trim()
             .lines()
-            .map(|line| {
+            .enumerate()
+            .map(|(i, line)| {
                 line.split_whitespace()
-                    .filter_map(|num| num.parse::<i64>().ok())
-                    .collect()
+                    .map(|num| num.parse::<i32>().map(i64::from))
+                    .collect::<Result<Vec<i64>, _>>()
+                    .ok()
+                    .filter(|levels| !levels.is_empty())
+                    .ok_or_else(|| ParseError::new(i, "expected a list of levels"))
             })
             .collect()
     }
@@ -71,7 +76,10 @@ impl Aoc2024 for Puzzle2 {
     }
 
     fn solve_a(&self, input: &String) -> String {
-        let reports = Self::get_reports(input);
+        let reports = match Self::get_reports(input) {
+            Ok(reports) => reports,
+            Err(err) => return err.to_string(),
+        };
 
         // Part 1: Count safe reports without the Problem Dampener
         let safe_count_part1 = reports
@@ -82,7 +90,10 @@ impl Aoc2024 for Puzzle2 {
     }
 
     fn solve_b(&self, input: &String) -> String {
-        let reports = Self::get_reports(input);
+        let reports = match Self::get_reports(input) {
+            Ok(reports) => reports,
+            Err(err) => return err.to_string(),
+        };
 
         // Part 2: Count safe reports with the Problem Dampener
         let safe_count_part2 = reports
@@ -91,4 +102,34 @@ impl Aoc2024 for Puzzle2 {
             .count();
         safe_count_part2.to_string()
     }
+
+    fn parse(&self, input: &String) -> Result<(), ParseError> {
+        Self::get_reports(input).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate;
+
+    #[test]
+    fn parses_generated_input() {
+        let input = generate::generate(2, generate::default_size(2), 1).unwrap();
+        assert_eq!(Puzzle2 {}.parse(&input), Ok(()));
+    }
+
+    #[test]
+    fn rejects_broken_input() {
+        let inputs = [
+            // a level that is not a number
+            ("7 6 4 2 1\n1 2 x 8\n", 1),
+            // a report without levels
+            ("7 6 4 2 1\n\t\n1 3 2\n", 1),
+        ];
+        for (input, line) in inputs {
+            let result = Puzzle2 {}.parse(&input.to_string());
+            assert_eq!(result.map_err(|err| err.line), Err(line), "{input:?}");
+        }
+    }
 }
@@ -1,4 +1,4 @@
-use crate::prelude::Aoc2024;
+use crate::prelude::{Aoc2024, ParseError};
 use std::cmp::{max, min, Ordering};
 use std::collections::BinaryHeap;
 use std::fmt::{Debug, Formatter, Write};
@@ -173,21 +173,26 @@ impl Debug for Node {
     }
 }
 
-impl From<u8> for Node {
-    fn from(value: u8) -> Self {
-        Self {
-            field: Field::from(value),
+impl TryFrom<u8> for Node {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(Self {
+            field: Field::try_from(value)?,
             score: usize::MAX,
-        }
+        })
     }
 }
-impl From<u8> for Field {
-    fn from(value: u8) -> Self {
+impl TryFrom<u8> for Field {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
-            b'#' => Field::BLOCKED,
-            b'E' => Field::END,
-            b'S' => Field::START,
-            _ => Field::FREE,
+            b'.' => Ok(Field::FREE),
+            b'#' => Ok(Field::BLOCKED),
+            b'E' => Ok(Field::END),
+            b'S' => Ok(Field::START),
+            _ => Err(format!("unknown field {:?}", value as char)),
         }
     }
 }
@@ -224,14 +229,52 @@ fn get_position(board: &Vec<Vec<Node>>, field: Field) -> Option<(usize, usize)>
     None
 }
 
-fn find_shortcuts_with_length(input: &String, length: usize) -> usize {
-    let board: Vec<Vec<Node>> = input
-        .lines()
-        .map(str::trim)
-        .map(|line| line.bytes().map(Node::from).collect())
-        .collect();
+/// Parses the racetrack, a square grid surrounded by walls with exactly one S and one E.
+fn get_grid(input: &str) -> Result<Vec<Vec<Node>>, ParseError> {
+    let lines: Vec<&str> = input.trim_end().lines().map(str::trim).collect();
+    let size = lines.len();
+    let mut grid = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let row = line
+            .bytes()
+            .map(Node::try_from)
+            .collect::<Result<Vec<Node>, String>>()
+            .map_err(|err| ParseError::new(i, err))?;
+        if row.len() != size {
+            return Err(ParseError::new(
+                i,
+                format!("expected {size} fields in a row"),
+            ));
+        }
+        let border = i == 0 || i == size - 1;
+        if row
+            .iter()
+            .enumerate()
+            .any(|(j, node)| (border || j == 0 || j == size - 1) && node.field != Field::BLOCKED)
+        {
+            return Err(ParseError::new(
+                i,
+                "the racetrack must be surrounded by walls",
+            ));
+        }
+        grid.push(row);
+    }
+    for (field, name) in [(Field::START, "S"), (Field::END, "E")] {
+        let count = grid
+            .iter()
+            .flatten()
+            .filter(|node| node.field == field)
+            .count();
+        if count != 1 {
+            let message = format!("expected exactly one {name}, found {count}");
+            return Err(ParseError::new(size.saturating_sub(1), message));
+        }
+    }
+    Ok(grid)
+}
 
-    let mut board = Board::new(board);
+fn find_shortcuts_with_length(input: &String, length: usize) -> Result<usize, ParseError> {
+    let mut board = Board::new(get_grid(input)?);
 
     let (x, y) = get_position(&board.grid, Field::END).expect("START not found");
 
@@ -239,7 +282,7 @@ fn find_shortcuts_with_length(input: &String, length: usize) -> usize {
 
     let shortcuts = board.find_shortcuts(length);
 
-    shortcuts.iter().filter(|&&x| x >= 100).count()
+    Ok(shortcuts.iter().filter(|&&x| x >= 100).count())
 }
 
 pub struct Puzzle20 {}
@@ -250,10 +293,46 @@ impl Aoc2024 for Puzzle20 {
     }
 
     fn solve_a(&self, input: &String) -> String {
-        find_shortcuts_with_length(input, 2).to_string()
+        match find_shortcuts_with_length(input, 2) {
+            Ok(count) => count.to_string(),
+            Err(err) => err.to_string(),
+        }
     }
 
     fn solve_b(&self, input: &String) -> String {
-        find_shortcuts_with_length(input, 20).to_string()
+        match find_shortcuts_with_length(input, 20) {
+            Ok(count) => count.to_string(),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    fn parse(&self, input: &String) -> Result<(), ParseError> {
+        get_grid(input).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate;
+
+    #[test]
+    fn parses_generated_input() {
+        let input = generate::generate(20, generate::default_size(20), 1).unwrap();
+        assert_eq!(Puzzle20 {}.parse(&input), Ok(()));
+    }
+
+    #[test]
+    fn rejects_broken_input() {
+        let inputs = [
+            // a truncated last row
+            ("#####\n#S.E#\n#...#\n#...#\n####\n", 4),
+            // a track open to the outside
+            ("#####\n#S.E.\n#####\n#####\n#####\n", 1),
+        ];
+        for (input, line) in inputs {
+            let result = Puzzle20 {}.parse(&input.to_string());
+            assert_eq!(result.map_err(|err| err.line), Err(line), "{input:?}");
+        }
     }
 }
@@ -1,8 +1,15 @@
-use crate::prelude::Aoc2024;
-use std::collections::HashMap;
+use crate::generate::Rng;
+use crate::number_theory::BigUint;
+use crate::prelude::{Aoc2024, ParseError};
+use std::collections::{HashMap, VecDeque};
 use std::fmt::{Debug, Formatter};
 use std::hash::Hash;
 
+/// Longest human press sequence `presses` reconstructs, deeper stacks only report the cost.
+const MAX_SEQUENCE: usize = 1_000_000;
+/// Mismatches `check-keypads` prints in full.
+const MAX_REPORTED: usize = 10;
+
 #[derive(Copy, Clone, Eq, PartialEq, Hash)]
 enum NumKey {
     Empty,
@@ -21,7 +28,6 @@ enum NumKey {
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash)]
 enum DirKey {
-    Empty,
     A,
     Left,
     Right,
@@ -29,6 +35,30 @@ enum DirKey {
     Down,
 }
 
+impl DirKey {
+    fn symbol(&self) -> char {
+        match self {
+            DirKey::A => 'A',
+            DirKey::Left => '<',
+            DirKey::Right => '>',
+            DirKey::Up => '^',
+            DirKey::Down => 'v',
+        }
+    }
+
+    fn from_symbol(symbol: char) -> Option<Self> {
+        [
+            DirKey::A,
+            DirKey::Left,
+            DirKey::Right,
+            DirKey::Up,
+            DirKey::Down,
+        ]
+        .into_iter()
+        .find(|key| key.symbol() == symbol)
+    }
+}
+
 impl From<char> for NumKey {
     fn from(value: char) -> Self {
         match value {
@@ -51,60 +81,151 @@ impl From<char> for NumKey {
 trait Control<KeyType> {
     fn cost(&mut self, target_key: KeyType) -> usize;
     fn reset(&mut self);
+    /// Presses `target_key` like `cost` and records an optimal sequence of presses for every
+    /// layer below, the closest one first.
+    fn press(&mut self, target_key: KeyType, layers: &mut [Vec<DirKey>]);
 }
 
-trait PadPosition {
-    fn default_key() -> Self;
-    fn crash_key() -> Self;
-    fn position(&self) -> (i32, i32);
+/// Door keypad of the puzzle, rows from top to bottom with a space for the gap.
+const NUMERIC_PAD: &str = "789\n456\n123\n 0A";
+/// Keypad of the robots.
+const DIRECTIONAL_PAD: &str = " ^A\n<v>";
+
+/// Keys of a keypad at their (row, column). Cells without a key are gaps the arm must not cross.
+#[derive(Clone)]
+struct Layout<KeyType> {
+    positions: HashMap<KeyType, (i32, i32)>,
+    default_key: KeyType,
 }
 
-impl PadPosition for DirKey {
-    fn default_key() -> Self {
-        DirKey::A
+impl<KeyType> Layout<KeyType>
+where
+    KeyType: Copy + Eq + Hash,
+{
+    /// Reads rows of key symbols like `789\n456\n123\n 0A`, spaces are gaps. The arm starts on `A`.
+    fn parse(text: &str, key: impl Fn(char) -> Option<KeyType>) -> Result<Self, ParseError> {
+        let mut positions = HashMap::new();
+        for (row, line) in text.lines().enumerate() {
+            for (column, symbol) in line.chars().enumerate().filter(|(_, c)| *c != ' ') {
+                let found = key(symbol)
+                    .ok_or_else(|| ParseError::new(row, format!("unknown key {symbol:?}")))?;
+                if positions
+                    .insert(found, (row as i32, column as i32))
+                    .is_some()
+                {
+                    return Err(ParseError::new(
+                        row,
+                        format!("key {symbol:?} appears twice"),
+                    ));
+                }
+            }
+        }
+        let default_key = key('A')
+            .filter(|key| positions.contains_key(key))
+            .ok_or_else(|| ParseError::new(0, "the keypad has no A key"))?;
+        Ok(Self {
+            positions,
+            default_key,
+        })
     }
 
-    fn crash_key() -> Self {
-        DirKey::Empty
+    fn position(&self, key: KeyType) -> (i32, i32) {
+        self.positions[&key]
     }
 
-    fn position(&self) -> (i32, i32) {
-        match self {
-            DirKey::Empty => (0, 0),
-            DirKey::A => (0, 2),
-            DirKey::Left => (1, 0),
-            DirKey::Right => (1, 2),
-            DirKey::Up => (0, 1),
-            DirKey::Down => (1, 1),
+    fn key_at(&self, position: (i32, i32)) -> Option<KeyType> {
+        self.positions
+            .iter()
+            .find(|(_, &at)| at == position)
+            .map(|(&key, _)| key)
+    }
+
+    /// Whether every cell on the straight lines from `from` over `corner` to `to` holds a key.
+    fn is_clear(&self, from: (i32, i32), corner: (i32, i32), to: (i32, i32)) -> bool {
+        [(from, corner), (corner, to)].iter().all(|&(start, end)| {
+            let steps = (end.0 - start.0).abs().max((end.1 - start.1).abs());
+            let step = ((end.0 - start.0).signum(), (end.1 - start.1).signum());
+            (0..=steps).all(|i| {
+                self.key_at((start.0 + i * step.0, start.1 + i * step.1))
+                    .is_some()
+            })
+        })
+    }
+
+    /// Presses moving the arm from `from` to `to` and pressing it without crossing a gap: all
+    /// horizontal moves first, all vertical ones first, then the zig-zag paths. Zig-zags only
+    /// win when the straight moves are blocked on this pad or make a lower pad cross a gap.
+    /// Detours that move away from `to` are left out, there are exponentially many of them.
+    fn options(&self, from: KeyType, to: KeyType) -> Vec<Vec<DirKey>> {
+        let ((x, y), target) = (self.position(from), self.position(to));
+        let (dx, dy) = (target.0 - x, target.1 - y);
+        let vertical =
+            vec![if dx < 0 { DirKey::Up } else { DirKey::Down }; dx.unsigned_abs() as usize];
+        let horizontal =
+            vec![if dy < 0 { DirKey::Left } else { DirKey::Right }; dy.unsigned_abs() as usize];
+
+        let mut options = Vec::new();
+        if self.is_clear((x, y), (x, y + dy), target) {
+            options.push([&horizontal[..], &vertical, &[DirKey::A]].concat());
         }
+        if self.is_clear((x, y), (x + dx, y), target) && dx != 0 && dy != 0 {
+            options.push([&vertical[..], &horizontal, &[DirKey::A]].concat());
+        }
+        for path in monotone_paths(self, from, to) {
+            if !options.contains(&path) {
+                options.push(path);
+            }
+        }
+        options
     }
 }
 
-impl PadPosition for NumKey {
-    fn default_key() -> Self {
-        NumKey::A
-    }
+fn numeric_layout() -> Layout<NumKey> {
+    Layout::parse(NUMERIC_PAD, |c| {
+        Some(NumKey::from(c)).filter(|&key| key != NumKey::Empty)
+    })
+    .expect("built-in layout is valid")
+}
 
-    fn crash_key() -> Self {
-        NumKey::Empty
+/// Reads the layout of a keypad the robots use, it needs all five direction keys.
+fn directional_layout(text: &str) -> Result<Layout<DirKey>, ParseError> {
+    let layout = Layout::parse(text, DirKey::from_symbol)?;
+    for symbol in "A<>^v".chars() {
+        let key = DirKey::from_symbol(symbol).unwrap();
+        if !layout.positions.contains_key(&key) {
+            return Err(ParseError::new(
+                0,
+                format!("the keypad has no {symbol} key"),
+            ));
+        }
     }
+    Ok(layout)
+}
 
-    fn position(&self) -> (i32, i32) {
-        match self {
-            NumKey::Empty => (3, 0),
-            NumKey::D0 => (3, 1),
-            NumKey::A => (3, 2),
-            NumKey::D1 => (2, 0),
-            NumKey::D2 => (2, 1),
-            NumKey::D3 => (2, 2),
-            NumKey::D4 => (1, 0),
-            NumKey::D5 => (1, 1),
-            NumKey::D6 => (1, 2),
-            NumKey::D7 => (0, 0),
-            NumKey::D8 => (0, 1),
-            NumKey::D9 => (0, 2),
+/// Runs presses on a directional pad forward and returns the keys pressed on the pad it controls.
+/// Fails when the arm would pass over a gap or leave the pad.
+fn simulate_pad<KeyType>(
+    layout: &Layout<KeyType>,
+    presses: &[DirKey],
+) -> Result<Vec<KeyType>, String>
+where
+    KeyType: Copy + Eq + Hash,
+{
+    let mut position = layout.position(layout.default_key);
+    let mut pressed = Vec::new();
+    for (i, press) in presses.iter().enumerate() {
+        match press {
+            DirKey::Left => position.1 -= 1,
+            DirKey::Right => position.1 += 1,
+            DirKey::Up => position.0 -= 1,
+            DirKey::Down => position.0 += 1,
+            DirKey::A => pressed.push(layout.key_at(position).unwrap()),
+        }
+        if layout.key_at(position).is_none() {
+            return Err(format!("press {i} moves the arm over a gap"));
         }
     }
+    Ok(pressed)
 }
 
 struct TargetCode<'a> {
@@ -119,21 +240,25 @@ impl<'a> Debug for TargetCode<'a> {
     }
 }
 
-impl<'a> From<&'a str> for TargetCode<'a> {
-    fn from(value: &'a str) -> Self {
+impl<'a> TargetCode<'a> {
+    /// Parses a door code of up to three digits followed by `A`, like `029A`.
+    fn parse(value: &'a str) -> Option<Self> {
         let input = value.trim();
-        let number = input[0..input.len() - 1].parse().unwrap();
-        let code = input.chars().map(NumKey::from).collect();
-        Self {
-            input,
-            code,
-            number,
+        let digits = input.strip_suffix('A')?;
+        if digits.is_empty() || digits.len() > 3 || !digits.bytes().all(|c| c.is_ascii_digit()) {
+            return None;
         }
+        Some(Self {
+            input,
+            code: input.chars().map(NumKey::from).collect(),
+            number: digits.parse().ok()?,
+        })
     }
 }
 
 struct Pad<KeyType> {
     prior: Box<dyn Control<DirKey>>,
+    layout: Layout<KeyType>,
     cost_map: HashMap<(KeyType, KeyType), usize>,
     current_key: KeyType,
     misses: usize,
@@ -148,36 +273,43 @@ impl Control<DirKey> for Human {
     }
 
     fn reset(&mut self) {}
+
+    fn press(&mut self, _: DirKey, _: &mut [Vec<DirKey>]) {}
 }
 
 impl<KeyType> Pad<KeyType>
 where
-    KeyType: PadPosition,
+    KeyType: Copy + Eq + Hash,
 {
-    pub fn new(prior: Box<dyn Control<DirKey>>) -> Self {
+    pub fn new(prior: Box<dyn Control<DirKey>>, layout: Layout<KeyType>) -> Self {
         Self {
             prior,
             cost_map: HashMap::new(),
-            current_key: KeyType::default_key(),
+            current_key: layout.default_key,
+            layout,
             misses: 0,
         }
     }
 
-    pub fn path_cost(&mut self, dx: i32, dy: i32, x_key: DirKey, y_key: DirKey) -> usize {
-        let mut cost = 0;
-        for _ in 0..dx.abs() {
-            cost += self.prior.cost(x_key);
-        }
-        for _ in 0..dy.abs() {
-            cost += self.prior.cost(y_key);
+    /// Cheapest presses on the prior pad that move from the current key to `target_key` and
+    /// press it. The cost is `usize::MAX` if every path crosses a gap.
+    fn moves(&mut self, target_key: KeyType) -> (Vec<DirKey>, usize) {
+        let mut best = (Vec::new(), usize::MAX);
+        for presses in self.layout.options(self.current_key, target_key) {
+            let cost = presses
+                .iter()
+                .fold(0, |cost, &key| self.prior.cost(key).saturating_add(cost));
+            if cost < best.1 {
+                best = (presses, cost);
+            }
         }
-        cost + self.prior.cost(DirKey::A)
+        best
     }
 }
 
 impl<KeyType> Control<KeyType> for Pad<KeyType>
 where
-    KeyType: Copy + PadPosition + Eq + Hash,
+    KeyType: Copy + Eq + Hash,
 {
     fn cost(&mut self, target_key: KeyType) -> usize {
         if let Some(&cost) = self.cost_map.get(&(self.current_key, target_key)) {
@@ -185,27 +317,7 @@ where
             cost
         } else {
             self.misses += 1;
-            let (x, y) = self.current_key.position();
-            let (target_x, target_y) = target_key.position();
-            let dy = target_y - y;
-            let dx = target_x - x;
-            let y_key = if dy < 0 { DirKey::Left } else { DirKey::Right };
-            let x_key = if dx < 0 { DirKey::Up } else { DirKey::Down };
-
-            // option horizontal, vertical
-            let hv_cost = if KeyType::crash_key().position() != (x + dx, y) {
-                self.path_cost(dx, dy, x_key, y_key)
-            } else {
-                usize::MAX
-            };
-
-            let vh_cost = if KeyType::crash_key().position() != (x, y + dy) {
-                self.path_cost(dy, dx, y_key, x_key)
-            } else {
-                usize::MAX
-            };
-
-            let cost = if vh_cost > hv_cost { hv_cost } else { vh_cost };
+            let (_, cost) = self.moves(target_key);
             self.cost_map.insert((self.current_key, target_key), cost);
             self.current_key = target_key;
             cost
@@ -213,18 +325,37 @@ where
     }
 
     fn reset(&mut self) {
-        self.current_key = KeyType::default_key();
+        self.current_key = self.layout.default_key;
+    }
+
+    fn press(&mut self, target_key: KeyType, layers: &mut [Vec<DirKey>]) {
+        let (presses, _) = self.moves(target_key);
+        self.current_key = target_key;
+        if let Some((layer, below)) = layers.split_first_mut() {
+            for key in presses {
+                layer.push(key);
+                self.prior.press(key, below);
+            }
+        }
     }
 }
 
-fn get_robot_stack(stack_size: usize) -> Pad<NumKey> {
+/// Stacks `stack_size` robots with directional pads between the human and the `top` keypad.
+fn get_robot_stack<KeyType>(
+    top: Layout<KeyType>,
+    directional: &Layout<DirKey>,
+    stack_size: usize,
+) -> Pad<KeyType>
+where
+    KeyType: Copy + Eq + Hash,
+{
     let human_control = Human {};
 
     let mut last_layer: Box<dyn Control<DirKey>> = Box::new(human_control);
     for _ in 0..stack_size {
-        last_layer = Box::new(Pad::<DirKey>::new(last_layer));
+        last_layer = Box::new(Pad::<DirKey>::new(last_layer, directional.clone()));
     }
-    Pad::<NumKey>::new(last_layer)
+    Pad::new(last_layer, top)
 }
 
 fn evaluate_target_cost(targets: &Vec<TargetCode>, control_pad: &mut Pad<NumKey>) -> Vec<usize> {
@@ -241,19 +372,427 @@ fn evaluate_target_cost(targets: &Vec<TargetCode>, control_pad: &mut Pad<NumKey>
         .collect()
 }
 
-fn simulate_stack(input: &String, depth: usize) -> usize {
-    let targets: Vec<TargetCode> = input
+fn get_targets(input: &str) -> Result<Vec<TargetCode<'_>>, ParseError> {
+    input
         .lines()
-        .filter(|&line| !line.is_empty())
-        .map(TargetCode::from)
-        .collect();
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            TargetCode::parse(line).ok_or_else(|| ParseError::new(i, "expected a code like `029A`"))
+        })
+        .collect()
+}
+
+fn simulate_stack(input: &String, depth: usize) -> Result<usize, ParseError> {
+    let targets = get_targets(input)?;
 
-    let mut control_pad = get_robot_stack(depth);
+    let directional = directional_layout(DIRECTIONAL_PAD).expect("built-in layout is valid");
+    let mut control_pad = get_robot_stack(numeric_layout(), &directional, depth);
 
     let total_cost: usize = evaluate_target_cost(&targets, &mut control_pad)
         .iter()
         .sum();
-    total_cost
+    Ok(total_cost)
+}
+
+/// Reads the keypad and directional layouts given as options, the puzzle's are the default.
+fn load_layouts(
+    keypad: Option<&String>,
+    directional: Option<&String>,
+) -> Result<(Layout<char>, Layout<DirKey>), ParseError> {
+    let layout_error = |name: &'static str| {
+        move |err: ParseError| ParseError::new(err.line, format!("{name} layout: {}", err.message))
+    };
+    let top = Layout::parse(keypad.map_or(NUMERIC_PAD, String::as_str), Some)
+        .map_err(layout_error("keypad"))?;
+    let directional = directional_layout(directional.map_or(DIRECTIONAL_PAD, String::as_str))
+        .map_err(layout_error("directional"))?;
+    Ok((top, directional))
+}
+
+/// Index of the line, the line and the keys of every code.
+type Codes<'a> = Vec<(usize, &'a str, Vec<char>)>;
+
+/// Codes typed on a keypad given by its layout, one per line.
+fn get_codes<'a>(input: &'a str, top: &Layout<char>) -> Result<Codes<'a>, ParseError> {
+    let mut codes = Vec::new();
+    for (i, line) in input.lines().map(str::trim).enumerate() {
+        let code: Vec<char> = line.chars().collect();
+        if let Some(key) = code.iter().find(|key| !top.positions.contains_key(key)) {
+            return Err(ParseError::new(
+                i,
+                format!("key {key:?} is not on the keypad"),
+            ));
+        }
+        if !code.is_empty() {
+            codes.push((i, line, code));
+        }
+    }
+    Ok(codes)
+}
+
+/// The number formed by the digits of a code, which its complexity is multiplied with.
+fn code_number(line: usize, input: &str, code: &[char]) -> Result<u64, ParseError> {
+    let digits: String = code.iter().filter(|c| c.is_ascii_digit()).collect();
+    if digits.is_empty() {
+        return Err(ParseError::new(
+            line,
+            format!("code `{input}` has no digits for its complexity"),
+        ));
+    }
+    digits.parse().map_err(|_| {
+        ParseError::new(
+            line,
+            format!("the number in code `{input}` does not fit in 64 bits"),
+        )
+    })
+}
+
+/// Exact cost of pressing the second key after the first one on a directional pad, `None` if it
+/// can not be reached without crossing a gap.
+type PairCosts = HashMap<(DirKey, DirKey), Option<BigUint>>;
+
+/// Cost of the cheapest moves from `from` to `to` on a pad controlled by a layer with
+/// the given costs. The chosen moves are a row of the transition matrix between the layers:
+/// how often each key pair is pressed below.
+fn transition_cost<KeyType>(
+    layout: &Layout<KeyType>,
+    from: KeyType,
+    to: KeyType,
+    costs: &PairCosts,
+) -> Option<BigUint>
+where
+    KeyType: Copy + Eq + Hash,
+{
+    let mut best: Option<BigUint> = None;
+    for presses in layout.options(from, to) {
+        let mut row: HashMap<(DirKey, DirKey), u64> = HashMap::new();
+        for (&from, &to) in std::iter::once(&DirKey::A).chain(&presses).zip(&presses) {
+            *row.entry((from, to)).or_insert(0) += 1;
+        }
+        let cost = row
+            .iter()
+            .try_fold(BigUint::from(0), |sum, (pair, &count)| {
+                Some(&sum + &(costs[pair].as_ref()? * count))
+            });
+        if let Some(cost) = cost.filter(|cost| best.as_ref().is_none_or(|best| cost < best)) {
+            best = Some(cost);
+        }
+    }
+    best
+}
+
+/// Exact press counts for every code at depths 0, `step`, 2 `step`, ... up to `depth` for
+/// `costs <file> --depth <n> [--step <s>] [--keypad <file>] [--directional <file>]`.
+/// Every robot layer multiplies the pair costs with a transition matrix, so deep stacks stay fast.
+pub fn cost_table(
+    input: &String,
+    depth: usize,
+    step: usize,
+    keypad: Option<&String>,
+    directional: Option<&String>,
+) -> Result<String, ParseError> {
+    let (top, directional) = load_layouts(keypad, directional)?;
+    let codes = get_codes(input, &top)?;
+    let numbers = codes
+        .iter()
+        .map(|(line, input, code)| code_number(*line, input, code))
+        .collect::<Result<Vec<u64>, ParseError>>()?;
+    let keys: Vec<DirKey> = "A<>^v".chars().filter_map(DirKey::from_symbol).collect();
+
+    // the human presses every key directly
+    let mut costs: PairCosts = keys
+        .iter()
+        .flat_map(|&from| {
+            keys.iter()
+                .map(move |&to| ((from, to), Some(BigUint::from(1))))
+        })
+        .collect();
+    let mut header = vec!["depth".to_string()];
+    header.extend(codes.iter().map(|(_, input, _)| input.to_string()));
+    header.push("complexity".to_string());
+    let mut lines = vec![header.join("  ")];
+    for layer in 0..=depth {
+        if layer % step.max(1) == 0 || layer == depth {
+            let mut row = vec![format!("{layer:>5}")];
+            let mut complexity = Some(BigUint::from(0));
+            for ((_, _, code), &number) in codes.iter().zip(&numbers) {
+                let cost = std::iter::once(&top.default_key)
+                    .chain(code)
+                    .zip(code)
+                    .try_fold(BigUint::from(0), |sum, (&from, &to)| {
+                        Some(&sum + &transition_cost(&top, from, to, &costs)?)
+                    });
+                complexity = complexity
+                    .zip(cost.as_ref())
+                    .map(|(sum, cost)| &sum + &(cost * number));
+                row.push(cost.map_or("-".to_string(), |cost| cost.to_string()));
+            }
+            row.push(complexity.map_or("-".to_string(), |complexity| complexity.to_string()));
+            lines.push(row.join("  "));
+        }
+        if layer < depth {
+            costs = costs
+                .keys()
+                .map(|&(from, to)| ((from, to), transition_cost(&directional, from, to, &costs)))
+                .collect();
+        }
+    }
+    Ok(lines.join("\n") + "\n")
+}
+
+/// Every monotone path of presses from `from` to `to` that stays on keys, ending with a press:
+/// each ordering of the horizontal and vertical moves that avoids the gaps.
+fn monotone_paths<KeyType>(layout: &Layout<KeyType>, from: KeyType, to: KeyType) -> Vec<Vec<DirKey>>
+where
+    KeyType: Copy + Eq + Hash,
+{
+    fn walk<KeyType: Copy + Eq + Hash>(
+        layout: &Layout<KeyType>,
+        position: (i32, i32),
+        target: (i32, i32),
+        path: &mut Vec<DirKey>,
+        paths: &mut Vec<Vec<DirKey>>,
+    ) {
+        if position == target {
+            paths.push([&path[..], &[DirKey::A]].concat());
+            return;
+        }
+        let vertical = (target.0 - position.0).signum();
+        let horizontal = (target.1 - position.1).signum();
+        let steps = [
+            (
+                vertical,
+                0,
+                if vertical < 0 {
+                    DirKey::Up
+                } else {
+                    DirKey::Down
+                },
+            ),
+            (
+                0,
+                horizontal,
+                if horizontal < 0 {
+                    DirKey::Left
+                } else {
+                    DirKey::Right
+                },
+            ),
+        ];
+        for (dx, dy, key) in steps {
+            let next = (position.0 + dx, position.1 + dy);
+            if (dx, dy) != (0, 0) && layout.key_at(next).is_some() {
+                path.push(key);
+                walk(layout, next, target, path, paths);
+                path.pop();
+            }
+        }
+    }
+
+    let mut paths = Vec::new();
+    let (start, target) = (layout.position(from), layout.position(to));
+    walk(layout, start, target, &mut Vec::new(), &mut paths);
+    paths
+}
+
+/// Reference for `Pad::cost`: a breadth first search over the human's presses that simulates the
+/// arm of every keypad in the stack, `usize::MAX` if `code` can not be typed. It makes no
+/// assumption about which paths are worth taking, so it checks the path choice of the solver.
+fn reference_cost(
+    top: &Layout<char>,
+    directional: &Layout<DirKey>,
+    robots: usize,
+    code: &[char],
+) -> usize {
+    // arms from the top keypad down to the pad the human presses on, and the keys typed so far
+    let mut arms = vec![directional.position(directional.default_key); robots + 1];
+    arms[0] = top.position(top.default_key);
+    let start = (arms, 0);
+    let mut distances: HashMap<(Vec<(i32, i32)>, usize), usize> = HashMap::new();
+    distances.insert(start.clone(), 0);
+    let mut queue = VecDeque::from([start]);
+    while let Some(state) = queue.pop_front() {
+        let distance = distances[&state];
+        if state.1 == code.len() {
+            return distance;
+        }
+        for mut press in "A<>^v".chars().filter_map(DirKey::from_symbol) {
+            let (mut arms, mut typed) = state.clone();
+            let mut layer = robots;
+            // an `A` presses the key below the arm, which acts on the pad one layer up
+            let valid = loop {
+                let arm = &mut arms[layer];
+                match press {
+                    DirKey::Left => arm.1 -= 1,
+                    DirKey::Right => arm.1 += 1,
+                    DirKey::Up => arm.0 -= 1,
+                    DirKey::Down => arm.0 += 1,
+                    DirKey::A if layer == 0 => {
+                        let pressed = top.key_at(*arm);
+                        typed += 1;
+                        break pressed == Some(code[typed - 1]);
+                    }
+                    DirKey::A => match directional.key_at(*arm) {
+                        Some(key) => {
+                            press = key;
+                            layer -= 1;
+                            continue;
+                        }
+                        None => break false,
+                    },
+                }
+                break match layer {
+                    0 => top.key_at(*arm).is_some(),
+                    _ => directional.key_at(*arm).is_some(),
+                };
+            };
+            let next = (arms, typed);
+            if valid && !distances.contains_key(&next) {
+                distances.insert(next.clone(), distance + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+    usize::MAX
+}
+
+/// Draws a random keypad with the given keys, the rest of the grid are gaps.
+fn random_layout(rng: &mut Rng, keys: &[char], rows: usize, columns: usize) -> String {
+    let mut cells = vec![' '; rows * columns];
+    cells[..keys.len()].copy_from_slice(keys);
+    rng.shuffle(&mut cells);
+    let lines: Vec<String> = cells
+        .chunks(columns)
+        .map(|row| row.iter().collect::<String>().trim_end().to_string())
+        .collect();
+    lines.join("\n")
+}
+
+/// Compares the cached solver with the breadth first search on random keypads, codes and stack
+/// depths for `check-keypads [--cases <n>] [--seed <s>]` and lists the cases where they differ.
+/// The solver only tries monotone paths, so keypads needing a detour around a gap show up as
+/// unreachable.
+pub fn check_solver(cases: usize, seed: u64) -> String {
+    let mut rng = Rng::new(seed);
+    let mut mismatches = Vec::new();
+    for _ in 0..cases {
+        let (rows, columns) = (1 + rng.below(4), 2 + rng.below(3));
+        let mut keys: Vec<char> = "0123456789BCDEF".chars().collect();
+        rng.shuffle(&mut keys);
+        keys.truncate(1 + rng.below(rows * columns - 1));
+        keys.push('A');
+        let keypad = random_layout(&mut rng, &keys, rows, columns);
+        let directional = match rng.chance(50) {
+            true => DIRECTIONAL_PAD.to_string(),
+            false => {
+                let rows = 2 + rng.below(2);
+                random_layout(&mut rng, &['A', '<', '>', '^', 'v'], rows, 3)
+            }
+        };
+        let top = Layout::parse(&keypad, Some).expect("random layouts are valid");
+        let pad = directional_layout(&directional).expect("random layouts are valid");
+        let code: Vec<char> = (0..1 + rng.below(4)).map(|_| *rng.pick(&keys)).collect();
+        let robots = rng.below(4);
+
+        let mut control_pad = get_robot_stack(top.clone(), &pad, robots);
+        let fast = code.iter().fold(0, |cost: usize, &key| {
+            control_pad.cost(key).saturating_add(cost)
+        });
+        let reference = reference_cost(&top, &pad, robots, &code);
+        if fast != reference {
+            mismatches.push((keypad, directional, code, robots, fast, reference));
+        }
+    }
+
+    let show = |cost: usize| match cost {
+        usize::MAX => "unreachable".to_string(),
+        cost => cost.to_string(),
+    };
+    let mut lines = vec![format!(
+        "{cases} cases (seed {seed}): {} mismatches",
+        mismatches.len()
+    )];
+    for (keypad, directional, code, robots, fast, reference) in mismatches.iter().take(MAX_REPORTED)
+    {
+        let code: String = code.iter().collect();
+        lines.push(format!(
+            "code {code} with {robots} robots: cached {}, reference {}",
+            show(*fast),
+            show(*reference)
+        ));
+        for (name, layout) in [("keypad", keypad), ("directional", directional)] {
+            lines.push(format!("  {name}:"));
+            lines.extend(layout.lines().map(|row| format!("    |{row}")));
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Rebuilds optimal presses on every layer for `presses <file> [--robots <n>] [--keypad <file>]
+/// [--directional <file>]` and checks them by running the human presses forward through all
+/// keypads. Without layout files the puzzle's keypads are used.
+pub fn sequences(
+    input: &String,
+    robots: usize,
+    keypad: Option<&String>,
+    directional: Option<&String>,
+) -> Result<String, ParseError> {
+    let (top, directional) = load_layouts(keypad, directional)?;
+    let codes = get_codes(input, &top)?;
+    let mut control_pad = get_robot_stack(top.clone(), &directional, robots);
+    let mut lines = Vec::new();
+    for (_, input, code) in &codes {
+        control_pad.reset();
+        let cost = code.iter().fold(0, |cost: usize, &key| {
+            control_pad.cost(key).saturating_add(cost)
+        });
+        if cost == usize::MAX {
+            lines.push(format!("{input}: can not be typed without crossing a gap"));
+            continue;
+        }
+        lines.push(format!("{input}: {cost} presses"));
+        if cost > MAX_SEQUENCE {
+            lines.push(format!(
+                "  longer than {MAX_SEQUENCE} presses, not reconstructed"
+            ));
+            continue;
+        }
+
+        control_pad.reset();
+        let mut layers = vec![Vec::new(); robots + 1];
+        for &key in code {
+            control_pad.press(key, &mut layers);
+        }
+        for (i, layer) in layers.iter().enumerate() {
+            let name = if i == robots {
+                "human".to_string()
+            } else {
+                format!("robot {}", i + 1)
+            };
+            let presses: String = layer.iter().map(DirKey::symbol).collect();
+            lines.push(format!("  {name:>8}: {presses}"));
+        }
+
+        // replay from the human presses up to the numeric pad
+        let mut check = Ok(layers[robots].clone());
+        for layer in (0..robots).rev() {
+            check = check
+                .and_then(|presses| simulate_pad(&directional, &presses))
+                .and_then(|pressed| match pressed == layers[layer] {
+                    true => Ok(pressed),
+                    false => Err(format!("layer {} differs from the replay", layer + 1)),
+                });
+        }
+        let verdict = match check.and_then(|presses| simulate_pad(&top, &presses)) {
+            Ok(typed) if typed != *code => "replay types a different code".to_string(),
+            Ok(_) if layers[robots].len() != cost => "replay is not optimal".to_string(),
+            Ok(_) => "verified".to_string(),
+            Err(err) => format!("replay failed: {err}"),
+        };
+        lines.push(format!("  {verdict}"));
+    }
+    Ok(lines.join("\n") + "\n")
 }
 
 pub struct Puzzle21 {}
@@ -264,10 +803,96 @@ impl Aoc2024 for Puzzle21 {
     }
 
     fn solve_a(&self, input: &String) -> String {
-        simulate_stack(input, 2).to_string()
+        match simulate_stack(input, 2) {
+            Ok(cost) => cost.to_string(),
+            Err(err) => err.to_string(),
+        }
     }
 
     fn solve_b(&self, input: &String) -> String {
-        simulate_stack(input, 25).to_string()
+        match simulate_stack(input, 25) {
+            Ok(cost) => cost.to_string(),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    fn parse(&self, input: &String) -> Result<(), ParseError> {
+        get_targets(input).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate;
+
+    #[test]
+    fn parses_generated_input() {
+        let input = generate::generate(21, generate::default_size(21), 1).unwrap();
+        assert_eq!(Puzzle21 {}.parse(&input), Ok(()));
+    }
+
+    #[test]
+    fn rejects_broken_input() {
+        let inputs = [
+            // an `A` inside the code used to unwrap a failed number parse
+            ("169A\n644A\n956A\n7A45A\r\n", 3),
+            // a code with four digits
+            ("029A\n1234A\n", 1),
+        ];
+        for (input, line) in inputs {
+            let result = Puzzle21 {}.parse(&input.to_string());
+            assert_eq!(result.map_err(|err| err.line), Err(line), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn solver_only_misses_detours() {
+        let report = check_solver(200, 1);
+        assert!(
+            report.starts_with("200 cases (seed 1): 6 mismatches"),
+            "{report}"
+        );
+        for line in report.lines().filter(|line| line.starts_with("code ")) {
+            assert!(line.contains("cached unreachable"), "{report}");
+        }
+    }
+
+    #[test]
+    fn only_the_search_detours_around_a_gap_in_the_row() {
+        let top = Layout::parse("0 2F\n1 79\n36DB\n45AE", Some).unwrap();
+        let pad = directional_layout(" <\n A\n>^v").unwrap();
+        let code: Vec<char> = "199E".chars().collect();
+        let mut control_pad = get_robot_stack(top.clone(), &pad, 1);
+        let cost = code.iter().fold(0, |cost: usize, &key| {
+            control_pad.cost(key).saturating_add(cost)
+        });
+        assert_eq!(cost, usize::MAX);
+        assert_eq!(reference_cost(&top, &pad, 1, &code), 29);
+    }
+
+    #[test]
+    fn costs_large_keypads() {
+        let keypad = "abcdefg\nhij lmn\nopqrstu\nvwx z01\n2345678\n9BCDEFG\nHIJKLMA".to_string();
+        let table = cost_table(&"0A\n".to_string(), 2, 1, Some(&keypad), None).unwrap();
+        assert_eq!(table.lines().count(), 4, "{table}");
+    }
+
+    #[test]
+    fn rejects_codes_without_a_number() {
+        for (codes, message) in [
+            ("029A\nBA\n", "code `BA` has no digits for its complexity"),
+            (
+                "029A\n\n99999999999999999999A\n",
+                "the number in code `99999999999999999999A` does not fit in 64 bits",
+            ),
+        ] {
+            let keypad = "789\n456\n123\n 0A\n BC".to_string();
+            let result = cost_table(&codes.to_string(), 1, 1, Some(&keypad), None);
+            assert_eq!(
+                result,
+                Err(ParseError::new(codes.lines().count() - 1, message))
+            );
+        }
     }
 }
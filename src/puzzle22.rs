@@ -1,4 +1,4 @@
-use crate::prelude::Aoc2024;
+use crate::prelude::{Aoc2024, ParseError};
 use std::collections::vec_deque::VecDeque;
 use std::collections::HashMap;
 
@@ -71,6 +71,19 @@ fn map(mut secret: usize, steps: usize) -> HashMap<[i8; 4], u8> {
     map
 }
 
+/// One initial secret per line, 32 bit so the shifts in `evolve` can't overflow.
+fn get_secrets(input: &str) -> Result<Vec<usize>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| match line.trim().parse::<u32>() {
+            Ok(secret) => Ok(secret as usize),
+            Err(_) => Err(ParseError::new(i, "expected a secret number")),
+        })
+        .collect()
+}
+
 pub struct Puzzle22 {}
 
 impl Aoc2024 for Puzzle22 {
@@ -79,11 +92,10 @@ impl Aoc2024 for Puzzle22 {
     }
 
     fn solve_a(&self, input: &String) -> String {
-        let secrets: Vec<usize> = input
-            .lines()
-            .filter(|&line| !line.is_empty())
-            .filter_map(|num| num.parse().ok())
-            .collect();
+        let secrets = match get_secrets(input) {
+            Ok(secrets) => secrets,
+            Err(err) => return err.to_string(),
+        };
 
         let mut sum = 0;
         for &secret in &secrets {
@@ -93,11 +105,10 @@ impl Aoc2024 for Puzzle22 {
     }
 
     fn solve_b(&self, input: &String) -> String {
-        let secrets: Vec<usize> = input
-            .lines()
-            .filter(|&line| !line.is_empty())
-            .filter_map(|num| num.parse().ok())
-            .collect();
+        let secrets = match get_secrets(input) {
+            Ok(secrets) => secrets,
+            Err(err) => return err.to_string(),
+        };
 
         let mut rewards = Vec::new();
         for &secret in &secrets {
@@ -123,4 +134,34 @@ impl Aoc2024 for Puzzle22 {
         }
         result.to_string()
     }
+
+    fn parse(&self, input: &String) -> Result<(), ParseError> {
+        get_secrets(input).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate;
+
+    #[test]
+    fn parses_generated_input() {
+        let input = generate::generate(22, generate::default_size(22), 1).unwrap();
+        assert_eq!(Puzzle22 {}.parse(&input), Ok(()));
+    }
+
+    #[test]
+    fn rejects_broken_input() {
+        let inputs = [
+            // a secret that is not a number
+            ("1\n10\nabc\n", 2),
+            // a secret above 32 bits
+            ("1\n4294967296\n", 1),
+        ];
+        for (input, line) in inputs {
+            let result = Puzzle22 {}.parse(&input.to_string());
+            assert_eq!(result.map_err(|err| err.line), Err(line), "{input:?}");
+        }
+    }
 }
@@ -1,4 +1,5 @@
-use crate::prelude::Aoc2024;
+use crate::graph::Graph as ExportGraph;
+use crate::prelude::{Aoc2024, ParseError};
 use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Display, Formatter, Write};
 
@@ -22,20 +23,15 @@ impl Debug for Triple {
     }
 }
 
-impl From<&str> for NameType {
-    fn from(value: &str) -> Self {
-        Self {
-            0: value
-                .bytes()
-                .take(2)
-                .collect::<Vec<u8>>()
-                .try_into()
-                .expect("Name must have exactly 2 characters"),
-        }
+impl NameType {
+    /// Names are two lowercase letters.
+    fn parse(value: &str) -> Option<Self> {
+        let name: [u8; 2] = value.as_bytes().try_into().ok()?;
+        name.iter()
+            .all(|c| c.is_ascii_lowercase())
+            .then_some(Self(name))
     }
-}
 
-impl NameType {
     pub fn as_str(&self) -> String {
         self.0.iter().map(|&el| char::from(el)).collect()
     }
@@ -90,14 +86,34 @@ impl Graph {
         }
     }
 
-    pub fn parse_connection(&mut self, input: &str) {
-        let parts: Vec<&str> = input.trim().split('-').collect();
-        if parts.len() != 2 {
-            panic!("Input must be in the format 'XX-YY' but is {input}");
+    /// Parses all connections, one `xx-yy` pair per line.
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut graph = Self::new();
+        for (i, line) in input.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            graph
+                .parse_connection(line)
+                .map_err(|err| ParseError::new(i, err))?;
         }
+        Ok(graph)
+    }
 
-        let name_a = NameType::from(parts[0]);
-        let name_b = NameType::from(parts[1]);
+    pub fn parse_connection(&mut self, input: &str) -> Result<(), String> {
+        let parts: Vec<&str> = input.trim().split('-').collect();
+        let names = match parts[..] {
+            [a, b] => NameType::parse(a).zip(NameType::parse(b)),
+            _ => None,
+        };
+        let Some((name_a, name_b)) = names else {
+            return Err(format!(
+                "expected a connection like `kh-tc` but got {input:?}"
+            ));
+        };
+        if name_a == name_b {
+            return Err(format!("{name_a} can not be connected to itself"));
+        }
 
         // Ensure both nodes exist in the graph
         self.nodes
@@ -118,6 +134,7 @@ impl Graph {
             .unwrap()
             .neighbours
             .insert(name_a);
+        Ok(())
     }
 
     pub fn find_largest_clique(&self) -> HashSet<NameType> {
@@ -179,10 +196,10 @@ impl Aoc2024 for Puzzle23 {
     }
 
     fn solve_a(&self, input: &String) -> String {
-        let mut graph = Graph::new();
-        for line in input.lines() {
-            graph.parse_connection(line);
-        }
+        let graph = match Graph::parse(input) {
+            Ok(graph) => graph,
+            Err(err) => return err.to_string(),
+        };
 
         let mut triples: HashSet<Triple> = HashSet::new();
 
@@ -213,10 +230,10 @@ impl Aoc2024 for Puzzle23 {
     }
 
     fn solve_b(&self, input: &String) -> String {
-        let mut graph = Graph::new();
-        for line in input.lines() {
-            graph.parse_connection(line);
-        }
+        let graph = match Graph::parse(input) {
+            Ok(graph) => graph,
+            Err(err) => return err.to_string(),
+        };
         // Find the largest clique with bron kerbosch
         let largest_clique = graph.find_largest_clique();
 
@@ -224,4 +241,46 @@ impl Aoc2024 for Puzzle23 {
         answer.sort();
         answer.join(",")
     }
+
+    fn graph(&self, input: &String) -> Option<ExportGraph<String>> {
+        let graph = Graph::parse(input).ok()?;
+        let mut export = ExportGraph::new(false);
+        for node in graph.nodes.values() {
+            export.add_node(node.name.to_string());
+            for neighbour in &node.neighbours {
+                export.connect(node.name.to_string(), neighbour.to_string());
+            }
+        }
+        Some(export)
+    }
+
+    fn parse(&self, input: &String) -> Result<(), ParseError> {
+        Graph::parse(input).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate;
+
+    #[test]
+    fn parses_generated_input() {
+        let input = generate::generate(23, generate::default_size(23), 1).unwrap();
+        assert_eq!(Puzzle23 {}.parse(&input), Ok(()));
+    }
+
+    #[test]
+    fn rejects_broken_input() {
+        let inputs = [
+            // a mangled connection used to panic
+            ("et-jq\nmv-km\nup>iw\ndt-up\n", 2),
+            // a computer connected to itself
+            ("et-jq\nmv-mv\n", 1),
+        ];
+        for (input, line) in inputs {
+            let result = Puzzle23 {}.parse(&input.to_string());
+            assert_eq!(result.map_err(|err| err.line), Err(line), "{input:?}");
+        }
+    }
 }
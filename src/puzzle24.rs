@@ -1,9 +1,15 @@
-use crate::prelude::Aoc2024;
-use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
-use std::rc::{Rc, Weak};
+use crate::generate::Rng;
+use crate::graph::Graph;
+use crate::prelude::{Aoc2024, ParseError};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write;
 
-#[derive(Debug, PartialEq, Eq)]
+/// Upper bound for the number of swapped output pairs the adder repair looks for.
+const MAX_SWAPS: usize = 8;
+/// Random additions used to confirm a repaired adder that is too wide for exhaustive tests.
+const CONFIRM_SAMPLES: usize = 1000;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 enum NodeType {
     AND,
     OR,
@@ -12,6 +18,15 @@ enum NodeType {
 }
 
 impl NodeType {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "AND" => Some(NodeType::AND),
+            "OR" => Some(NodeType::OR),
+            "XOR" => Some(NodeType::XOR),
+            _ => None,
+        }
+    }
+
     fn apply(&self, a: &NodeState, b: &NodeState) -> NodeState {
         match self {
             NodeType::AND => {
@@ -52,169 +67,1192 @@ impl NodeType {
             NodeType::Input => NodeState::Floating,
         }
     }
-}
 
-impl From<&str> for NodeType {
-    fn from(value: &str) -> Self {
-        match value {
-            "AND" => NodeType::AND,
-            "OR" => NodeType::OR,
-            "XOR" => NodeType::XOR,
-            _ => unreachable!(),
+    /// Simplifies a gate whose result is a constant or one of its operands.
+    fn fold(&self, a: Term, b: Term) -> Option<Term> {
+        match (self, a, b) {
+            (NodeType::AND, Term::Const(false), _) | (NodeType::AND, _, Term::Const(false)) => {
+                Some(Term::Const(false))
+            }
+            (NodeType::OR, Term::Const(true), _) | (NodeType::OR, _, Term::Const(true)) => {
+                Some(Term::Const(true))
+            }
+            (NodeType::XOR, Term::Const(a), Term::Const(b)) => Some(Term::Const(a ^ b)),
+            (NodeType::AND, Term::Const(true), term)
+            | (NodeType::AND, term, Term::Const(true))
+            | (NodeType::OR, Term::Const(false), term)
+            | (NodeType::OR, term, Term::Const(false))
+            | (NodeType::XOR, Term::Const(false), term)
+            | (NodeType::XOR, term, Term::Const(false)) => Some(term),
+            (NodeType::XOR, a, b) if a == b => Some(Term::Const(false)),
+            (NodeType::AND | NodeType::OR, a, b) if a == b => Some(a),
+            _ => None,
         }
     }
 }
 
+/// What a wire computes after constant folding: a constant or the first wire computing the same.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+enum Term {
+    Const(bool),
+    Wire(usize),
+}
+
+/// Netlist after constant folding and merging of duplicate gates, see `Netlist::simplify`.
+struct Simplified {
+    terms: Vec<Term>,
+    /// Gate inputs rewired to the first wire computing the same value.
+    inputs: Vec<Option<[usize; 2]>>,
+    /// Gates an output still depends on after rewiring.
+    live: Vec<bool>,
+}
+
 #[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Clone, Copy)]
 enum NodeState {
     Floating,
     Set(bool),
 }
 
-//#[derive(Eq, PartialEq, Ord, PartialOrd, Hash)]
-#[derive(Debug)]
-struct Node {
-    state: NodeState,
+#[derive(Debug, Clone)]
+struct Wire {
     name: String,
-    inputs: Option<[Weak<RefCell<Node>>; 2]>,
-    outputs: Vec<Weak<RefCell<Node>>>,
     operator: NodeType,
+    /// Wires driving the gate, `None` for inputs.
+    inputs: Option<[usize; 2]>,
+    /// Value given in the input section.
+    initial: NodeState,
 }
 
-impl Node {
-    fn evaluate(&mut self) -> NodeState {
-        if self.state == NodeState::Floating && self.inputs.is_some() {
-            match &self.inputs {
-                Some(inputs) => {
-                    let state_a = inputs[0].upgrade().unwrap().borrow_mut().evaluate();
-                    let state_b = inputs[1].upgrade().unwrap().borrow_mut().evaluate();
-                    self.state = self.operator.apply(&state_a, &state_b);
-                }
-                _ => {}
-            }
+impl Wire {
+    /// Wire name, gates are suffixed with their operator.
+    fn label(&self) -> String {
+        match self.operator {
+            NodeType::Input => self.name.clone(),
+            _ => format!("{} {:?}", self.name, self.operator),
         }
-        self.state
     }
 }
 
-struct Machine {
-    nodes: HashMap<String, Rc<RefCell<Node>>>,
+/// Index based netlist. Gates are sorted topologically once, so the circuit can be
+/// evaluated repeatedly for different inputs without recursion.
+#[derive(Clone, Default)]
+struct Netlist {
+    wires: Vec<Wire>,
+    index: HashMap<String, usize>,
+    /// Gate outputs in evaluation order.
+    order: Vec<usize>,
+    values: Vec<NodeState>,
 }
 
-impl Machine {
-    pub fn parse(content: &String) -> Self {
-        let mut parts = content.split("\n\n");
+impl Netlist {
+    fn wire(&mut self, name: &str) -> usize {
+        if let Some(&id) = self.index.get(name) {
+            return id;
+        }
+        self.wires.push(Wire {
+            name: name.to_owned(),
+            operator: NodeType::Input,
+            inputs: None,
+            initial: NodeState::Floating,
+        });
+        self.index.insert(name.to_owned(), self.wires.len() - 1);
+        self.wires.len() - 1
+    }
+
+    /// Connects a gate driving `output`, fails if the wire already has a value or a driver.
+    fn add_gate(&mut self, operator: NodeType, a: &str, b: &str, output: &str) -> Option<usize> {
+        let inputs = [self.wire(a), self.wire(b)];
+        let id = self.wire(output);
+        let wire = &mut self.wires[id];
+        if wire.inputs.is_some() || wire.initial != NodeState::Floating {
+            return None;
+        }
+        wire.inputs = Some(inputs);
+        wire.operator = operator;
+        Some(id)
+    }
+
+    /// Parses either the puzzle format or a structural Verilog module.
+    pub fn load(content: &String) -> Result<Self, ParseError> {
+        if content.contains("endmodule") {
+            Self::from_verilog(content)
+        } else {
+            Self::parse(content)
+        }
+    }
+
+    pub fn parse(content: &String) -> Result<Self, ParseError> {
+        let mut netlist = Self::default();
+        let mut lines = content.lines().map(str::trim).enumerate();
 
         // Parse inputs
-        let inputs_part = parts.next().unwrap();
-        let mut nodes: HashMap<String, Rc<RefCell<Node>>> = inputs_part
-            .lines()
-            .map(|line| {
-                let mut parts = line.split(":").map(str::trim);
-                let name = parts.next().unwrap().to_owned();
-                let state = parts.next().unwrap().parse::<u8>().unwrap() == 1;
-                (
-                    name.clone(),
-                    Rc::new(RefCell::new(Node {
-                        state: NodeState::Set(state),
-                        outputs: Vec::new(),
-                        inputs: None,
-                        operator: NodeType::Input,
-                        name,
-                    })),
-                )
-            })
+        for (i, line) in lines.by_ref() {
+            if line.is_empty() {
+                break;
+            }
+            let (name, state) = line
+                .split_once(':')
+                .ok_or_else(|| ParseError::new(i, "expected `<wire>: <0|1>`"))?;
+            let state = match state.trim() {
+                "0" => false,
+                "1" => true,
+                _ => return Err(ParseError::new(i, "input values must be 0 or 1")),
+            };
+            let id = netlist.wire(name.trim());
+            if netlist.wires[id].initial != NodeState::Floating {
+                return Err(ParseError::new(i, format!("input {name} given twice")));
+            }
+            netlist.wires[id].initial = NodeState::Set(state);
+        }
+
+        // Parse gates
+        let mut defined_in = HashMap::new();
+        for (i, line) in lines.filter(|(_, line)| !line.is_empty()) {
+            let elements: Vec<&str> = line.split_whitespace().collect();
+            let [name_a, operator, name_b, "->", output] = elements[..] else {
+                return Err(ParseError::new(
+                    i,
+                    "expected `<wire> <AND|OR|XOR> <wire> -> <wire>`",
+                ));
+            };
+            let operator = NodeType::parse(operator)
+                .ok_or_else(|| ParseError::new(i, format!("unknown gate {operator}")))?;
+            let id = netlist
+                .add_gate(operator, name_a, name_b, output)
+                .ok_or_else(|| ParseError::new(i, format!("wire {output} has two drivers")))?;
+            defined_in.insert(id, i);
+        }
+        netlist.finish(&defined_in)
+    }
+
+    /// Sorts the parsed gates, a combinational loop is reported at the first gate on it.
+    fn finish(mut self, defined_in: &HashMap<usize, usize>) -> Result<Self, ParseError> {
+        if let Err(stuck) = self.sort() {
+            let line = stuck.iter().filter_map(|id| defined_in.get(id)).min();
+            let names: Vec<&str> = stuck.iter().map(|&id| self.name(id)).collect();
+            return Err(ParseError::new(
+                line.copied().unwrap_or_default(),
+                format!(
+                    "combinational loop, wires {} can not be evaluated",
+                    names.join(", ")
+                ),
+            ));
+        }
+        self.reset();
+        Ok(self)
+    }
+
+    /// Imports the Verilog subset written by `to_verilog`: one module with `input`, `output` and
+    /// `wire` declarations, two input `and`/`or`/`xor` primitives and `assign a = b & c;`
+    /// style assignments with `&`, `|` or `^`. Declared inputs start at 0.
+    fn from_verilog(source: &str) -> Result<Self, ParseError> {
+        let mut tokens = Tokens {
+            tokens: verilog_tokens(source)?,
+            position: 0,
+        };
+        let mut netlist = Self::default();
+        let mut outputs = Vec::new();
+        let mut defined_in = HashMap::new();
+
+        tokens.expect("module")?;
+        tokens.identifier()?;
+        if tokens.peek() == Some("(") {
+            tokens.next()?;
+            let mut direction = None;
+            while tokens.peek() != Some(")") {
+                if let Some(keyword @ ("input" | "output" | "wire")) = tokens.peek() {
+                    direction = Some(keyword.to_owned());
+                    tokens.next()?;
+                }
+                let line = tokens.line();
+                let name = tokens.identifier()?;
+                netlist.declare(direction.as_deref(), &name, line, &mut outputs)?;
+                if tokens.peek() != Some(")") {
+                    tokens.expect(",")?;
+                }
+            }
+            tokens.next()?;
+        }
+        tokens.expect(";")?;
+
+        loop {
+            let line = tokens.line();
+            let statement = tokens.next()?;
+            match statement.as_str() {
+                "endmodule" => break,
+                "input" | "output" | "wire" => loop {
+                    let name = tokens.identifier()?;
+                    netlist.declare(Some(&statement), &name, line, &mut outputs)?;
+                    match tokens.next()?.as_str() {
+                        ";" => break,
+                        "," => {}
+                        _ => return Err(ParseError::new(tokens.line(), "expected `,` or `;`")),
+                    }
+                },
+                "and" | "or" | "xor" => {
+                    if tokens.peek() != Some("(") {
+                        tokens.identifier()?;
+                    }
+                    tokens.expect("(")?;
+                    let output = tokens.identifier()?;
+                    tokens.expect(",")?;
+                    let a = tokens.identifier()?;
+                    tokens.expect(",")?;
+                    let b = tokens.identifier()?;
+                    if tokens.peek() == Some(",") {
+                        return Err(ParseError::new(line, "only two input gates are supported"));
+                    }
+                    tokens.expect(")")?;
+                    tokens.expect(";")?;
+                    let operator = NodeType::parse(&statement.to_uppercase()).unwrap();
+                    let id = netlist.add_gate(operator, &a, &b, &output).ok_or_else(|| {
+                        ParseError::new(line, format!("wire {output} has two drivers"))
+                    })?;
+                    defined_in.insert(id, line);
+                }
+                "assign" => {
+                    let output = tokens.identifier()?;
+                    tokens.expect("=")?;
+                    let a = tokens.identifier()?;
+                    let operator = match tokens.next()?.as_str() {
+                        "&" => NodeType::AND,
+                        "|" => NodeType::OR,
+                        "^" => NodeType::XOR,
+                        _ => return Err(ParseError::new(line, "expected `&`, `|` or `^`")),
+                    };
+                    let b = tokens.identifier()?;
+                    tokens.expect(";")?;
+                    let id = netlist.add_gate(operator, &a, &b, &output).ok_or_else(|| {
+                        ParseError::new(line, format!("wire {output} has two drivers"))
+                    })?;
+                    defined_in.insert(id, line);
+                }
+                _ => {
+                    return Err(ParseError::new(
+                        line,
+                        format!("unsupported statement `{statement}`"),
+                    ))
+                }
+            }
+        }
+        if tokens.peek().is_some() {
+            return Err(ParseError::new(
+                tokens.line(),
+                "only one module is supported",
+            ));
+        }
+        for (line, id) in outputs {
+            if netlist.wires[id].inputs.is_none() {
+                let name = netlist.name(id);
+                return Err(ParseError::new(
+                    line,
+                    format!("output {name} is not driven"),
+                ));
+            }
+        }
+        netlist.finish(&defined_in)
+    }
+
+    /// Applies a port or wire declaration of the Verilog importer.
+    fn declare(
+        &mut self,
+        direction: Option<&str>,
+        name: &str,
+        line: usize,
+        outputs: &mut Vec<(usize, usize)>,
+    ) -> Result<(), ParseError> {
+        let id = self.wire(name);
+        match direction {
+            Some("input") if self.wires[id].inputs.is_some() => {
+                return Err(ParseError::new(
+                    line,
+                    format!("wire {name} has two drivers"),
+                ))
+            }
+            Some("input") => self.wires[id].initial = NodeState::Set(false),
+            Some("output") => outputs.push((line, id)),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Structural Verilog module with the undriven wires as inputs and the z wires as outputs.
+    /// Input values are not part of the module, `simulate` takes them as options.
+    fn to_verilog(&self) -> String {
+        let ids = self.sorted_ids();
+        let driven = |id: &&usize| self.wires[**id].inputs.is_some();
+        let is_output = |id: &&usize| self.name(**id).starts_with('z');
+
+        let mut ports: Vec<String> = ids
+            .iter()
+            .filter(|id| !driven(id))
+            .map(|&id| format!("input {}", verilog_name(self.name(id))))
             .collect();
+        ports.extend(
+            ids.iter()
+                .filter(|id| driven(id) && is_output(id))
+                .map(|&id| format!("output {}", verilog_name(self.name(id)))),
+        );
+        let mut out = String::from("module day24 (\n");
+        for (i, port) in ports.iter().enumerate() {
+            let separator = if i + 1 < ports.len() { "," } else { "" };
+            writeln!(out, "    {port}{separator}").unwrap();
+        }
+        out.push_str(");\n");
+        for &id in ids.iter().filter(|id| driven(id) && !is_output(id)) {
+            writeln!(out, "    wire {};", verilog_name(self.name(id))).unwrap();
+        }
+        for (gate, &id) in self.order.iter().enumerate() {
+            let [a, b] = self.wires[id].inputs.unwrap();
+            let primitive = match self.wires[id].operator {
+                NodeType::AND => "and",
+                NodeType::OR => "or",
+                NodeType::XOR => "xor",
+                NodeType::Input => continue,
+            };
+            writeln!(
+                out,
+                "    {primitive} g{gate} ({}, {}, {});",
+                verilog_name(self.name(id)),
+                verilog_name(self.name(a)),
+                verilog_name(self.name(b))
+            )
+            .unwrap();
+        }
+        out.push_str("endmodule\n");
+        out
+    }
 
-        if let Some(connections_part) = parts.next() {
-            for line in connections_part.lines() {
-                let elements: Vec<&str> = line.split_whitespace().collect();
-                let name_a = elements[0].to_owned();
-                let name_b = elements[2].to_owned();
-                let output = elements[4].to_owned();
-                let op_type = NodeType::from(elements[1]);
-
-                // Ensure all nodes exist in `nodes`
-                let node_a = nodes
-                    .entry(name_a.clone())
-                    .or_insert_with(|| {
-                        Rc::new(RefCell::new(Node {
-                            name: name_a.clone(),
-                            inputs: None,
-                            outputs: Vec::new(),
-                            state: NodeState::Floating,
-                            operator: NodeType::Input,
-                        }))
-                    })
-                    .clone();
-                let node_b = nodes
-                    .entry(name_b.clone())
-                    .or_insert_with(|| {
-                        Rc::new(RefCell::new(Node {
-                            name: name_b.clone(),
-                            inputs: None,
-                            outputs: Vec::new(),
-                            state: NodeState::Floating,
-                            operator: NodeType::Input,
-                        }))
-                    })
-                    .clone();
-                let node_out = nodes
-                    .entry(output.clone())
-                    .or_insert_with(|| {
-                        Rc::new(RefCell::new(Node {
-                            name: output.clone(),
-                            inputs: None,
-                            outputs: Vec::new(),
-                            state: NodeState::Floating,
-                            operator: NodeType::Input,
-                        }))
-                    })
-                    .clone();
-                // Add the output node to the outputs of node_a and node_b
-                node_a.borrow_mut().outputs.push(Rc::downgrade(&node_out));
-                node_b.borrow_mut().outputs.push(Rc::downgrade(&node_out));
-                node_out.borrow_mut().inputs =
-                    Some([Rc::downgrade(&node_a), Rc::downgrade(&node_b)]);
-                node_out.borrow_mut().operator = op_type;
+    /// Graphviz graph of the circuit with the wires coloured by the gate driving them.
+    fn to_dot(&self) -> String {
+        let mut out = String::from("digraph day24 {\n    node [style=filled];\n");
+        for (id, wire) in self.wires.iter().enumerate() {
+            let colour = match wire.operator {
+                NodeType::AND => "lightblue",
+                NodeType::OR => "palegreen",
+                NodeType::XOR => "lightsalmon",
+                NodeType::Input => "lightgrey",
+            };
+            let shape = match wire.inputs {
+                None => "box",
+                Some(_) if wire.name.starts_with('z') => "doublecircle",
+                Some(_) => "ellipse",
+            };
+            let label = wire.label().replace('\\', "\\\\").replace('"', "\\\"");
+            writeln!(
+                out,
+                "    n{id} [label=\"{label}\", fillcolor={colour}, shape={shape}];"
+            )
+            .unwrap();
+        }
+        for (id, wire) in self.wires.iter().enumerate() {
+            for input in wire.inputs.iter().flatten() {
+                writeln!(out, "    n{input} -> n{id};").unwrap();
             }
         }
+        out.push_str("}\n");
+        out
+    }
 
-        Self { nodes }
+    /// Orders the gates topologically. On a combinational loop the wires on or behind it are returned.
+    fn sort(&mut self) -> Result<(), Vec<usize>> {
+        let mut graph = Graph::new(true);
+        for (id, wire) in self.wires.iter().enumerate() {
+            graph.add_node(id);
+            for input in wire.inputs.iter().flatten() {
+                graph.connect(*input, id);
+            }
+        }
+        match graph.topological_sort() {
+            Ok(order) => {
+                self.order = order
+                    .into_iter()
+                    .map(|id| *graph.node(id))
+                    .filter(|&id| self.wires[id].inputs.is_some())
+                    .collect();
+                Ok(())
+            }
+            Err(stuck) => Err(stuck.into_iter().map(|id| *graph.node(id)).collect()),
+        }
     }
 
-    pub fn evaluate(&mut self) {
-        for node in self.nodes.values_mut() {
-            if node.borrow().name.starts_with('z') {
-                node.borrow_mut().evaluate();
+    /// Whether every gate still comes after the gates driving it.
+    fn is_ordered(&self) -> bool {
+        let mut ready: Vec<bool> = self
+            .wires
+            .iter()
+            .map(|wire| wire.inputs.is_none())
+            .collect();
+        for &id in &self.order {
+            let [a, b] = self.wires[id].inputs.unwrap();
+            if !ready[a] || !ready[b] {
+                return false;
             }
+            ready[id] = true;
         }
+        true
     }
 
-    pub fn variable(&self, var_name: char) -> usize {
-        let mut output_nodes: Vec<&Rc<RefCell<Node>>> = self
-            .nodes
-            .values()
-            .filter_map(|node| {
-                let name = &node.borrow().name;
-                if name.starts_with(var_name) {
-                    Some(node)
-                } else {
-                    None
+    fn name(&self, id: usize) -> &str {
+        &self.wires[id].name
+    }
+
+    fn sorted_ids(&self) -> Vec<usize> {
+        let mut ids: Vec<usize> = (0..self.wires.len()).collect();
+        ids.sort_by(|&a, &b| self.name(a).cmp(self.name(b)));
+        ids
+    }
+
+    fn is_output(&self, id: usize) -> bool {
+        self.wires[id].inputs.is_some() && self.name(id).starts_with('z')
+    }
+
+    /// Marks the wires the given gate inputs make the outputs depend on.
+    fn needed(&self, inputs: &[Option<[usize; 2]>]) -> Vec<bool> {
+        let mut needed: Vec<bool> = (0..self.wires.len()).map(|id| self.is_output(id)).collect();
+        for &id in self.order.iter().rev() {
+            if needed[id] {
+                for &input in inputs[id].iter().flatten() {
+                    needed[input] = true;
                 }
-            })
+            }
+        }
+        needed
+    }
+
+    /// Folds constants and merges gates computing the same as an earlier gate or one of their
+    /// inputs. Outputs keep their gate, operands that became constant keep their driver.
+    fn simplify(&self) -> Simplified {
+        let mut terms: Vec<Term> = (0..self.wires.len()).map(Term::Wire).collect();
+        let mut inputs = vec![None; self.wires.len()];
+        let mut gates = HashMap::new();
+        for &id in &self.order {
+            let wire = &self.wires[id];
+            let [a, b] = wire.inputs.unwrap();
+            let (term_a, term_b) = (terms[a], terms[b]);
+            let key = (wire.operator, term_a.min(term_b), term_a.max(term_b));
+            match wire.operator.fold(term_a, term_b) {
+                Some(term) => terms[id] = term,
+                None => match gates.get(&key) {
+                    Some(&first) => terms[id] = Term::Wire(first),
+                    None => {
+                        gates.insert(key, id);
+                    }
+                },
+            }
+            let rewire = |input: usize, term: Term| match term {
+                Term::Wire(first) => first,
+                Term::Const(_) => input,
+            };
+            inputs[id] = Some([rewire(a, term_a), rewire(b, term_b)]);
+        }
+        let live = self.needed(&inputs);
+        Simplified {
+            terms,
+            inputs,
+            live,
+        }
+    }
+
+    /// Restores the input values of the puzzle input and clears all gate outputs.
+    fn reset(&mut self) {
+        self.values = self.wires.iter().map(|wire| wire.initial).collect();
+    }
+
+    /// Wires of a number like `x00`, `x01`, ..., least significant bit first.
+    fn bits(&self, var_name: char) -> Vec<usize> {
+        let mut bits: Vec<usize> = (0..self.wires.len())
+            .filter(|&id| self.name(id).starts_with(var_name))
             .collect();
-        output_nodes.sort_by(|a, b| b.borrow().name.cmp(&a.borrow().name));
+        bits.sort_by(|&a, &b| self.name(a).cmp(self.name(b)));
+        bits
+    }
+
+    /// Sets the input wires of a number, bits without a wire are dropped.
+    pub fn set(&mut self, var_name: char, value: u64) {
+        self.set_bits(&self.bits(var_name), value);
+    }
+
+    fn set_bits(&mut self, bits: &[usize], value: u64) {
+        for (i, &id) in bits.iter().enumerate() {
+            self.values[id] = NodeState::Set(i < 64 && value >> i & 1 == 1);
+        }
+    }
 
-        let mut total: usize = 0;
-        for node in output_nodes {
+    pub fn evaluate(&mut self) {
+        for &id in &self.order {
+            let [a, b] = self.wires[id].inputs.unwrap();
+            self.values[id] = self.wires[id]
+                .operator
+                .apply(&self.values[a], &self.values[b]);
+        }
+    }
+
+    pub fn variable(&self, var_name: char) -> u64 {
+        self.read_bits(&self.bits(var_name))
+    }
+
+    fn read_bits(&self, bits: &[usize]) -> u64 {
+        let mut total = 0;
+        for &id in bits.iter().rev() {
             total <<= 1;
-            if node.borrow().state == NodeState::Set(true) {
+            if self.values[id] == NodeState::Set(true) {
                 total += 1;
             }
         }
         total
     }
+
+    /// Evaluates up to 64 input assignments at once, bit `k` of every word belongs to assignment `k`.
+    /// Wires that are neither given nor driven read as zero.
+    fn evaluate_lanes(&self, inputs: &[(usize, u64)]) -> Vec<u64> {
+        let mut lanes = vec![0; self.wires.len()];
+        for &(id, word) in inputs {
+            lanes[id] = word;
+        }
+        for &id in &self.order {
+            let [a, b] = self.wires[id].inputs.unwrap();
+            lanes[id] = match self.wires[id].operator {
+                NodeType::AND => lanes[a] & lanes[b],
+                NodeType::OR => lanes[a] | lanes[b],
+                NodeType::XOR => lanes[a] ^ lanes[b],
+                NodeType::Input => 0,
+            };
+        }
+        lanes
+    }
+
+    /// Computes up to 64 pairs at once, with `x` and `y` driving the given input bits and the
+    /// result read from the `z` bits. Bits beyond the 64th are ignored.
+    fn compute(&self, x: &[usize], y: &[usize], z: &[usize], pairs: &[(u64, u64)]) -> Vec<u64> {
+        let mut inputs = Vec::new();
+        for (bits, operand) in [(x, 0), (y, 1)] {
+            for (j, &id) in bits.iter().enumerate().take(64) {
+                let word = pairs.iter().enumerate().fold(0, |word, (k, pair)| {
+                    let value = if operand == 0 { pair.0 } else { pair.1 };
+                    word | (value >> j & 1) << k
+                });
+                inputs.push((id, word));
+            }
+        }
+        let lanes = self.evaluate_lanes(&inputs);
+        (0..pairs.len())
+            .map(|k| {
+                z.iter()
+                    .enumerate()
+                    .take(64)
+                    .fold(0, |z, (j, &id)| z | (lanes[id] >> k & 1) << j)
+            })
+            .collect()
+    }
+
+    /// Exchanges the drivers of two wires. Swaps that would create a loop are undone and rejected.
+    fn swap_outputs(&mut self, a: usize, b: usize) -> Result<(), Vec<usize>> {
+        let exchange = |wires: &mut Vec<Wire>| {
+            let (driver_a, driver_b) = (
+                (wires[a].operator, wires[a].inputs),
+                (wires[b].operator, wires[b].inputs),
+            );
+            (wires[a].operator, wires[a].inputs) = driver_b;
+            (wires[b].operator, wires[b].inputs) = driver_a;
+        };
+        exchange(&mut self.wires);
+        if self.is_ordered() {
+            return Ok(());
+        }
+        let sorted = self.sort();
+        if sorted.is_err() {
+            exchange(&mut self.wires);
+        }
+        sorted
+    }
+}
+
+/// Verilog keywords that can not be used as plain wire names.
+const VERILOG_KEYWORDS: [&str; 14] = [
+    "module",
+    "endmodule",
+    "input",
+    "output",
+    "wire",
+    "assign",
+    "and",
+    "or",
+    "xor",
+    "nand",
+    "nor",
+    "xnor",
+    "not",
+    "buf",
+];
+
+/// Wire name as Verilog identifier, keywords and other names are escaped.
+fn verilog_name(name: &str) -> String {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if valid && !VERILOG_KEYWORDS.contains(&name) {
+        name.to_owned()
+    } else {
+        format!("\\{name} ")
+    }
+}
+
+/// Splits Verilog source into tokens with their line, comments are dropped.
+/// Escaped identifiers keep their backslash so they never match a keyword.
+fn verilog_tokens(source: &str) -> Result<Vec<(usize, String)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    let mut line = 0;
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            c if c.is_whitespace() => {}
+            '/' if chars.peek() == Some(&'/') => while chars.next_if(|&c| c != '\n').is_some() {},
+            '/' if chars.peek() == Some(&'*') => {
+                let start = line;
+                let mut last = chars.next();
+                loop {
+                    match chars.next() {
+                        None => return Err(ParseError::new(start, "unterminated comment")),
+                        Some('/') if last == Some('*') => break,
+                        Some(c) => {
+                            line += (c == '\n') as usize;
+                            last = Some(c);
+                        }
+                    }
+                }
+            }
+            '\\' => {
+                let mut name = String::from(c);
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                    name.push(c);
+                }
+                if name.len() == 1 {
+                    return Err(ParseError::new(line, "empty escaped identifier"));
+                }
+                tokens.push((line, name));
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut name = String::from(c);
+                while let Some(c) =
+                    chars.next_if(|&c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+                {
+                    name.push(c);
+                }
+                tokens.push((line, name));
+            }
+            '(' | ')' | ',' | ';' | '=' | '&' | '|' | '^' => tokens.push((line, c.to_string())),
+            _ => return Err(ParseError::new(line, format!("unexpected character {c:?}"))),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Cursor over the tokens of a Verilog module.
+struct Tokens {
+    tokens: Vec<(usize, String)>,
+    position: usize,
+}
+
+impl Tokens {
+    /// Line of the next token, or of the last one at the end of the file.
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .or(self.tokens.last())
+            .map_or(0, |(line, _)| *line)
+    }
+
+    fn peek(&self) -> Option<&str> {
+        self.tokens
+            .get(self.position)
+            .map(|(_, token)| token.as_str())
+    }
+
+    fn next(&mut self) -> Result<String, ParseError> {
+        let line = self.line();
+        let (_, token) = self
+            .tokens
+            .get(self.position)
+            .ok_or_else(|| ParseError::new(line, "unexpected end of file"))?;
+        self.position += 1;
+        Ok(token.clone())
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), ParseError> {
+        let line = self.line();
+        match self.next()? {
+            token if token == expected => Ok(()),
+            token => Err(ParseError::new(
+                line,
+                format!("expected `{expected}`, found `{token}`"),
+            )),
+        }
+    }
+
+    /// A wire or module name, escaped identifiers are returned without their backslash.
+    fn identifier(&mut self) -> Result<String, ParseError> {
+        let line = self.line();
+        let token = self.next()?;
+        if let Some(escaped) = token.strip_prefix('\\') {
+            return Ok(escaped.to_owned());
+        }
+        let valid = token.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_');
+        if !valid || VERILOG_KEYWORDS.contains(&token.as_str()) {
+            return Err(ParseError::new(
+                line,
+                format!("expected an identifier, found `{token}`"),
+            ));
+        }
+        Ok(token)
+    }
+}
+
+/// Largest value with `width` bits.
+fn mask(width: usize) -> u64 {
+    if width >= 64 {
+        u64::MAX
+    } else {
+        (1 << width) - 1
+    }
+}
+
+/// Input pairs for comparing circuits: all of them if x and y have at most 16 bits together,
+/// otherwise a few edge cases followed by `samples` seeded random pairs.
+fn test_vectors(x_width: usize, y_width: usize, samples: usize, seed: u64) -> Vec<(u64, u64)> {
+    let (x_mask, y_mask) = (mask(x_width), mask(y_width));
+    if x_width + y_width <= 16 {
+        return (0..=x_mask)
+            .flat_map(|x| (0..=y_mask).map(move |y| (x, y)))
+            .collect();
+    }
+    let mut rng = Rng::new(seed);
+    let mut inputs = vec![(x_mask, 1 & y_mask), (x_mask, y_mask), (0, 0)];
+    inputs.extend((0..samples).map(|_| (rng.next_u64() & x_mask, rng.next_u64() & y_mask)));
+    inputs
+}
+
+/// Checks and repairs a netlist that should add x and y into z.
+struct AdderVerifier {
+    netlist: Netlist,
+    x: Vec<usize>,
+    y: Vec<usize>,
+    z: Vec<usize>,
+    width: usize,
+}
+
+impl AdderVerifier {
+    fn new(netlist: Netlist) -> Self {
+        let (x, y, z) = (netlist.bits('x'), netlist.bits('y'), netlist.bits('z'));
+        let width = x.len().min(63);
+        Self {
+            netlist,
+            x,
+            y,
+            z,
+            width,
+        }
+    }
+
+    fn mask(&self) -> u64 {
+        mask(self.width)
+    }
+
+    /// Adds up to 64 pairs at once with the circuit.
+    fn add(&self, pairs: &[(u64, u64)]) -> Vec<u64> {
+        self.netlist.compute(&self.x, &self.y, &self.z, pairs)
+    }
+
+    /// Tests sum bits up to `bit + 1` with all values of the inputs `bit - 1` and `bit`,
+    /// which covers the half adder, the incoming and the outgoing carry of the bit.
+    fn bit_ok(&self, bit: usize) -> bool {
+        self.sums_ok(bit, bit + 1)
+    }
+
+    /// Tests sum bits up to `top` with all values of the inputs `bit - 1` and `bit`.
+    fn sums_ok(&self, bit: usize, top: usize) -> bool {
+        let shift = bit.saturating_sub(1);
+        let mask = mask(top + 1);
+        let pairs: Vec<(u64, u64)> = (0..16)
+            .map(|v| {
+                (
+                    (v & 3) << shift & self.mask(),
+                    (v >> 2) << shift & self.mask(),
+                )
+            })
+            .collect();
+        self.add(&pairs)
+            .iter()
+            .zip(&pairs)
+            .all(|(z, (x, y))| (z ^ (x + y)) & mask == 0)
+    }
+
+    fn first_failing_bit(&self, from: usize) -> Option<usize> {
+        (from..self.width).find(|&bit| !self.bit_ok(bit))
+    }
+
+    /// Gates close to the full adder of `bit`: up to three gates behind the inputs of the
+    /// neighbouring bits and the sum outputs.
+    fn candidates(&self, bit: usize) -> Vec<usize> {
+        let netlist = &self.netlist;
+        let mut fanout = vec![Vec::new(); netlist.wires.len()];
+        for &id in &netlist.order {
+            for input in netlist.wires[id].inputs.unwrap() {
+                fanout[input].push(id);
+            }
+        }
+        let mut frontier: BTreeSet<usize> = (bit.saturating_sub(1)..=bit + 1)
+            .flat_map(|i| [self.x.get(i), self.y.get(i)])
+            .flatten()
+            .copied()
+            .collect();
+        let mut near: BTreeSet<usize> = BTreeSet::new();
+        for _ in 0..3 {
+            frontier = frontier.iter().flat_map(|&id| fanout[id].clone()).collect();
+            near.extend(&frontier);
+        }
+        near.extend([self.z.get(bit), self.z.get(bit + 1)].into_iter().flatten());
+        near.into_iter()
+            .filter(|&id| netlist.wires[id].inputs.is_some())
+            .collect()
+    }
+
+    /// Depth limited search for output swaps, fixing the lowest failing bit first. Swap sets
+    /// passing every bit but failing `confirm` are dropped.
+    ///
+    /// A swap normally has to make the bit and its carry out right. With `partial` it may leave
+    /// the carry wrong when the next bit is broken as well, as long as the next swap makes
+    /// the bit right, so two swaps in neighbouring bits are found together.
+    fn repair(
+        &mut self,
+        from: usize,
+        budget: usize,
+        partial: bool,
+        swaps: &mut Vec<(usize, usize)>,
+    ) -> bool {
+        let Some(bit) = self.first_failing_bit(from) else {
+            return self.confirm();
+        };
+        if budget == 0 {
+            return false;
+        }
+        let low = bit.saturating_sub(2);
+        // leaving the carry wrong only pays off if the bit itself is broken
+        let partial = partial && budget >= 2 && !(low..=bit).all(|low| self.sums_ok(low, bit));
+        let candidates = self.candidates(bit);
+        for (i, &a) in candidates.iter().enumerate() {
+            for &b in &candidates[i + 1..] {
+                if self.netlist.swap_outputs(a, b).is_err() {
+                    continue;
+                }
+                let fixed = (low..=bit).all(|bit| self.bit_ok(bit));
+                let carry_left = !fixed && partial && (low..=bit).all(|low| self.sums_ok(low, bit));
+                if fixed || carry_left {
+                    swaps.push((a, b));
+                    if self.repair(low, budget - 1, fixed, swaps) {
+                        return true;
+                    }
+                    swaps.pop();
+                }
+                self.netlist
+                    .swap_outputs(a, b)
+                    .expect("swapping back restores a sorted netlist");
+            }
+        }
+        false
+    }
+
+    /// Checks `x + y` for all inputs of narrow adders and random inputs otherwise.
+    fn confirm(&self) -> bool {
+        let inputs = test_vectors(self.width, self.width, CONFIRM_SAMPLES, self.width as u64);
+        inputs.chunks(64).all(|chunk| {
+            let sums = self.add(chunk);
+            chunk.iter().zip(&sums).all(|(&(x, y), &z)| z == x + y)
+        })
+    }
+}
+
+/// Evaluates the circuit in `simulate <file>` for the given x and y and lists the wire values.
+pub fn simulate(
+    input: &String,
+    x: Option<u64>,
+    y: Option<u64>,
+    wires: bool,
+) -> Result<String, ParseError> {
+    let mut netlist = Netlist::load(input)?;
+    if let Some(x) = x {
+        netlist.set('x', x);
+    }
+    if let Some(y) = y {
+        netlist.set('y', y);
+    }
+    netlist.evaluate();
+
+    let mut lines = vec![format!(
+        "x = {}, y = {}, z = {}",
+        netlist.variable('x'),
+        netlist.variable('y'),
+        netlist.variable('z')
+    )];
+    if wires {
+        for id in netlist.sorted_ids() {
+            let value = match netlist.values[id] {
+                NodeState::Set(value) => (value as u8).to_string(),
+                NodeState::Floating => "floating".to_string(),
+            };
+            lines.push(format!("{} = {value}", netlist.wires[id].label()));
+        }
+    }
+    Ok(lines.join("\n") + "\n")
+}
+
+/// Output formats of `export <file>`.
+pub enum CircuitFormat {
+    Verilog,
+    Dot,
+}
+
+impl CircuitFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "verilog" => Some(CircuitFormat::Verilog),
+            "dot" => Some(CircuitFormat::Dot),
+            _ => None,
+        }
+    }
+}
+
+/// Converts a circuit in the puzzle format or in Verilog for `export <file> [verilog|dot]`.
+pub fn export(input: &String, format: CircuitFormat) -> Result<String, ParseError> {
+    let netlist = Netlist::load(input)?;
+    Ok(match format {
+        CircuitFormat::Verilog => netlist.to_verilog(),
+        CircuitFormat::Dot => netlist.to_dot(),
+    })
+}
+
+/// Function a circuit is compared with in `equivalence <file> <reference>`.
+pub enum Reference {
+    Add,
+    And,
+    /// Another circuit, in the puzzle format or in Verilog.
+    Circuit(String),
+}
+
+impl Reference {
+    fn describe(&self) -> &str {
+        match self {
+            Reference::Add => "x + y",
+            Reference::And => "x & y",
+            Reference::Circuit(_) => "the reference circuit",
+        }
+    }
+}
+
+/// Compares the z output of a circuit with a reference for all inputs of small circuits, or for
+/// `samples` random inputs. Reports the first input where they differ and the differing z bits.
+pub fn check_equivalence(
+    input: &String,
+    reference: &Reference,
+    samples: usize,
+    seed: u64,
+) -> Result<String, ParseError> {
+    let netlist = Netlist::load(input)?;
+    let (x, y, z) = (netlist.bits('x'), netlist.bits('y'), netlist.bits('z'));
+    let other = match reference {
+        Reference::Circuit(content) => Some(Netlist::load(content).map_err(|err| {
+            ParseError::new(err.line, format!("reference circuit: {}", err.message))
+        })?),
+        _ => None,
+    };
+    let inputs = test_vectors(x.len().min(64), y.len().min(64), samples, seed);
+
+    for chunk in inputs.chunks(64) {
+        let actual = netlist.compute(&x, &y, &z, chunk);
+        let expected: Vec<u64> = match &other {
+            Some(other) => {
+                let bits = (other.bits('x'), other.bits('y'), other.bits('z'));
+                other.compute(&bits.0, &bits.1, &bits.2, chunk)
+            }
+            None => chunk
+                .iter()
+                .map(|&(x, y)| match reference {
+                    Reference::And => x & y,
+                    _ => x.wrapping_add(y),
+                })
+                .map(|value| value & mask(z.len()))
+                .collect(),
+        };
+        let mismatch = chunk
+            .iter()
+            .zip(actual.iter().zip(&expected))
+            .find(|(_, (a, e))| a != e);
+        if let Some((&(x, y), (&actual, &expected))) = mismatch {
+            let bits: Vec<String> = (0..64)
+                .filter(|bit| (actual ^ expected) >> bit & 1 == 1)
+                .map(|bit| format!("z{bit:02}"))
+                .collect();
+            return Ok(format!(
+                "x = {x}, y = {y}: circuit gives z = {actual}, {} gives {expected}\ndiffering bits: {}\n",
+                reference.describe(),
+                bits.join(", ")
+            ));
+        }
+    }
+    let tested = if x.len() + y.len() <= 16 {
+        format!("all {} inputs", inputs.len())
+    } else {
+        format!("{} random inputs (seed {seed})", inputs.len())
+    };
+    Ok(format!(
+        "circuit matches {} on {tested}\n",
+        reference.describe()
+    ))
+}
+
+/// Statistics of a circuit for `analyse <file> [--wires]`: gate counts, the depth of every
+/// output, fan-out, dead gates and what constant folding and merging duplicates would remove.
+pub fn analyse(input: &String, wires: bool) -> Result<String, ParseError> {
+    let netlist = Netlist::load(input)?;
+    let gates: Vec<usize> = netlist.order.clone();
+    let count = |operator| {
+        gates
+            .iter()
+            .filter(|&&id| netlist.wires[id].operator == operator)
+            .count()
+    };
+    let mut lines = vec![format!(
+        "{} gates: {} AND, {} OR, {} XOR; {} inputs",
+        gates.len(),
+        count(NodeType::AND),
+        count(NodeType::OR),
+        count(NodeType::XOR),
+        netlist.wires.len() - gates.len()
+    )];
+
+    let mut depth = vec![0; netlist.wires.len()];
+    for &id in &gates {
+        let [a, b] = netlist.wires[id].inputs.unwrap();
+        depth[id] = 1 + depth[a].max(depth[b]);
+    }
+    let outputs: Vec<usize> = netlist
+        .bits('z')
+        .into_iter()
+        .filter(|&id| netlist.is_output(id))
+        .collect();
+    if let Some(&deepest) = outputs.iter().max_by_key(|&&id| depth[id]) {
+        let mut path = vec![deepest];
+        while let Some([a, b]) = netlist.wires[*path.last().unwrap()].inputs {
+            path.push(if depth[a] >= depth[b] { a } else { b });
+        }
+        let names: Vec<&str> = path.iter().rev().map(|&id| netlist.name(id)).collect();
+        lines.push(format!(
+            "critical path, depth {}: {}",
+            depth[deepest],
+            names.join(" -> ")
+        ));
+    }
+    let depths: Vec<String> = outputs
+        .iter()
+        .map(|&id| format!("{} {}", netlist.name(id), depth[id]))
+        .collect();
+    lines.push(format!("output depths: {}", depths.join(", ")));
+
+    let mut fan_out = vec![0; netlist.wires.len()];
+    for &id in &gates {
+        let [a, b] = netlist.wires[id].inputs.unwrap();
+        fan_out[a] += 1;
+        if b != a {
+            fan_out[b] += 1;
+        }
+    }
+    let mut histogram = BTreeMap::new();
+    for &count in &fan_out {
+        *histogram.entry(count).or_insert(0) += 1;
+    }
+    let histogram: Vec<String> = histogram
+        .iter()
+        .map(|(fan_out, count)| format!("{count} wires drive {fan_out}"))
+        .collect();
+    lines.push(format!("fan-out: {}", histogram.join(", ")));
+
+    let original: Vec<Option<[usize; 2]>> = netlist.wires.iter().map(|wire| wire.inputs).collect();
+    let needed = netlist.needed(&original);
+    let simplified = netlist.simplify();
+    let list = |ids: Vec<String>| match ids.is_empty() {
+        true => "none".to_string(),
+        false => ids.join(", "),
+    };
+    let dead = gates
+        .iter()
+        .filter(|&&id| !needed[id])
+        .map(|&id| netlist.name(id).to_string())
+        .collect();
+    lines.push(format!("dead gates: {}", list(dead)));
+    let constants = gates
+        .iter()
+        .filter_map(|&id| match simplified.terms[id] {
+            Term::Const(value) => Some(format!("{} = {}", netlist.name(id), value as u8)),
+            Term::Wire(_) => None,
+        })
+        .collect();
+    lines.push(format!("constant wires: {}", list(constants)));
+    let redundant = gates
+        .iter()
+        .filter(|&&id| needed[id] && !simplified.live[id])
+        .map(|&id| match simplified.terms[id] {
+            Term::Wire(first) => format!("{} = {}", netlist.name(id), netlist.name(first)),
+            Term::Const(value) => format!("{} = {}", netlist.name(id), value as u8),
+        })
+        .collect();
+    lines.push(format!("redundant gates: {}", list(redundant)));
+    let remaining = gates.iter().filter(|&&id| simplified.live[id]).count();
+    lines.push(format!(
+        "optimised: {remaining} of {} gates remain",
+        gates.len()
+    ));
+
+    if wires {
+        for id in netlist.sorted_ids() {
+            lines.push(format!(
+                "{}: depth {}, fan-out {}",
+                netlist.wires[id].label(),
+                depth[id],
+                fan_out[id]
+            ));
+        }
+    }
+    Ok(lines.join("\n") + "\n")
+}
+
+/// Rewrites a circuit for `optimise <file>` in the puzzle format without dead, constant and
+/// duplicate gates. Outputs and input values are kept.
+pub fn optimise(input: &String) -> Result<String, ParseError> {
+    let netlist = Netlist::load(input)?;
+    let simplified = netlist.simplify();
+    let mut out = String::new();
+    for id in netlist.sorted_ids() {
+        if let NodeState::Set(value) = netlist.wires[id].initial {
+            writeln!(out, "{}: {}", netlist.name(id), value as u8).unwrap();
+        }
+    }
+    out.push('\n');
+    for &id in netlist.order.iter().filter(|&&id| simplified.live[id]) {
+        let [a, b] = simplified.inputs[id].unwrap();
+        writeln!(
+            out,
+            "{} {:?} {} -> {}",
+            netlist.name(a),
+            netlist.wires[id].operator,
+            netlist.name(b),
+            netlist.name(id)
+        )
+        .unwrap();
+    }
+    Ok(out)
 }
 
 pub struct Puzzle24 {}
@@ -225,93 +1263,249 @@ impl Aoc2024 for Puzzle24 {
     }
 
     fn solve_a(&self, input: &String) -> String {
-        let mut machine = Machine::parse(input);
-        machine.evaluate();
-        machine.variable('z').to_string()
+        let mut netlist = match Netlist::parse(input) {
+            Ok(netlist) => netlist,
+            Err(err) => return err.to_string(),
+        };
+        netlist.evaluate();
+        netlist.variable('z').to_string()
     }
 
     fn solve_b(&self, input: &String) -> String {
-        // solve by finding wrong circuits through knowledge of adder and circuit design
-        // originally I wanted to implement a complete circuit solver, but dropped it due to time constraints
-        // this is the reason for the Rc<RefCell<Node>>> though...
-        let mut machine = Machine::parse(input);
-        machine.evaluate();
-        let nodes: Vec<&Rc<RefCell<Node>>> = machine.nodes.values().collect();
-        let mut wrong: HashSet<String> = HashSet::new();
-
-        for node in &nodes {
-            let _node = node.borrow();
-            if _node.operator != NodeType::Input {
-                if _node.name.starts_with('z') && _node.operator != NodeType::XOR {
-                    wrong.insert(node.borrow().name.clone());
-                }
-                let op1_name = _node.inputs.clone().unwrap()[0]
-                    .upgrade()
-                    .unwrap()
-                    .borrow()
-                    .name
-                    .clone();
-                let op2_name = _node.inputs.clone().unwrap()[1]
-                    .upgrade()
-                    .unwrap()
-                    .borrow()
-                    .name
-                    .clone();
-                if _node.operator == NodeType::XOR
-                    && !['x', 'y', 'z'].contains(&op1_name.chars().next().unwrap())
-                    && !['x', 'y', 'z'].contains(&op2_name.chars().next().unwrap())
-                    && !['x', 'y', 'z'].contains(&_node.name.chars().next().unwrap())
-                {
-                    wrong.insert(node.borrow().name.clone());
-                }
-                if _node.operator == NodeType::AND && !(op1_name == "x00" || op2_name == "x00") {
-                    for inner_node in &nodes {
-                        if inner_node.borrow().operator != NodeType::OR
-                            && inner_node.borrow().operator != NodeType::Input
-                        {
-                            let op1_name = inner_node.borrow().inputs.clone().unwrap()[0]
-                                .upgrade()
-                                .unwrap()
-                                .borrow()
-                                .name
-                                .clone();
-                            let op2_name = inner_node.borrow().inputs.clone().unwrap()[1]
-                                .upgrade()
-                                .unwrap()
-                                .borrow()
-                                .name
-                                .clone();
-                            if op1_name == _node.name || op2_name == _node.name {
-                                wrong.insert(node.borrow().name.clone());
-                            }
-                        }
-                    }
-                }
-                if _node.operator == NodeType::XOR {
-                    for inner_node in &nodes {
-                        if inner_node.borrow().operator == NodeType::OR {
-                            let op1_name = inner_node.borrow().inputs.clone().unwrap()[0]
-                                .upgrade()
-                                .unwrap()
-                                .borrow()
-                                .name
-                                .clone();
-                            let op2_name = inner_node.borrow().inputs.clone().unwrap()[1]
-                                .upgrade()
-                                .unwrap()
-                                .borrow()
-                                .name
-                                .clone();
-                            if op1_name == _node.name || op2_name == _node.name {
-                                wrong.insert(node.borrow().name.clone());
-                            }
-                        }
-                    }
-                }
+        // verify the adder bit by bit and search the fewest output swaps repairing it
+        let netlist = match Netlist::parse(input) {
+            Ok(netlist) => netlist,
+            Err(err) => return err.to_string(),
+        };
+        for budget in 0..=MAX_SWAPS {
+            let mut verifier = AdderVerifier::new(netlist.clone());
+            let mut swaps = Vec::new();
+            if !verifier.repair(0, budget, true, &mut swaps) {
+                continue;
             }
+            let mut wrong: Vec<&str> = swaps
+                .iter()
+                .flat_map(|&(a, b)| [netlist.name(a), netlist.name(b)])
+                .collect();
+            wrong.sort();
+            return wrong.join(",");
         }
-        let mut wrong: Vec<String> = wrong.into_iter().collect();
-        wrong.sort();
-        wrong[0..8].join(",")
+        format!("No repair with at most {MAX_SWAPS} swapped pairs makes the circuit add x and y")
+    }
+
+    fn graph(&self, input: &String) -> Option<Graph<String>> {
+        let netlist = Netlist::parse(input).ok()?;
+        let mut graph = Graph::new(true);
+        for wire in &netlist.wires {
+            graph.add_node(wire.label());
+            for &input in wire.inputs.iter().flatten() {
+                graph.connect(netlist.wires[input].label(), wire.label());
+            }
+        }
+        Some(graph)
+    }
+
+    fn parse(&self, input: &String) -> Result<(), ParseError> {
+        Netlist::parse(input).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate;
+
+    /// Two bit ripple carry adder computing 1 + 3.
+    const ADDER: &str = "\
+x00: 1
+x01: 0
+y00: 1
+y01: 1
+
+x00 XOR y00 -> z00
+x00 AND y00 -> c00
+x01 XOR y01 -> s01
+s01 XOR c00 -> z01
+x01 AND y01 -> a01
+s01 AND c00 -> b01
+a01 OR b01 -> z02
+";
+
+    /// Ripple carry adder with `width` bit operands, all inputs 0.
+    fn adder(width: usize) -> String {
+        let mut out = String::new();
+        for var in ['x', 'y'] {
+            for bit in 0..width {
+                writeln!(out, "{var}{bit:02}: 0").unwrap();
+            }
+        }
+        out.push_str("\nx00 XOR y00 -> z00\nx00 AND y00 -> c00\n");
+        for bit in 1..width {
+            let carry = if bit + 1 == width {
+                format!("z{width:02}")
+            } else {
+                format!("c{bit:02}")
+            };
+            let previous = bit - 1;
+            writeln!(out, "x{bit:02} XOR y{bit:02} -> s{bit:02}").unwrap();
+            writeln!(out, "s{bit:02} XOR c{previous:02} -> z{bit:02}").unwrap();
+            writeln!(out, "x{bit:02} AND y{bit:02} -> a{bit:02}").unwrap();
+            writeln!(out, "s{bit:02} AND c{previous:02} -> b{bit:02}").unwrap();
+            writeln!(out, "a{bit:02} OR b{bit:02} -> {carry}").unwrap();
+        }
+        out
+    }
+
+    #[test]
+    fn simulates_the_circuit() {
+        let input = ADDER.to_string();
+        assert_eq!(
+            simulate(&input, None, None, false),
+            Ok("x = 1, y = 3, z = 4\n".to_string())
+        );
+        assert_eq!(
+            simulate(&input, Some(2), Some(3), false),
+            Ok("x = 2, y = 3, z = 5\n".to_string())
+        );
+        let listing = simulate(&input, Some(2), Some(3), true).unwrap();
+        assert!(listing.contains("\na01 AND = 1\n"), "{listing}");
+        assert!(listing.contains("\nb01 AND = 0\n"), "{listing}");
+        assert!(listing.ends_with("\nz02 OR = 1\n"), "{listing}");
+
+        // a wire without driver or value stays floating
+        let input = "x00: 1\n\nx00 AND w -> z00\n".to_string();
+        let listing = simulate(&input, None, None, true).unwrap();
+        assert!(listing.contains("\nw = floating\n"), "{listing}");
+    }
+
+    #[test]
+    fn reports_combinational_loops() {
+        let input = "x00: 1\n\nx00 OR z00 -> a\na AND x00 -> b\nb XOR x00 -> z00\n".to_string();
+        let err = simulate(&input, None, None, false).unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!(err.message.starts_with("combinational loop"), "{err}");
+        for wire in ["a", "b", "z00"] {
+            assert!(err.message.contains(wire), "{err}");
+        }
+    }
+
+    #[test]
+    fn verilog_export_imports_again() {
+        // `and` is a keyword and has to be escaped
+        let input = ADDER.replace("c00", "and");
+        let verilog = export(&input, CircuitFormat::Verilog).unwrap();
+        assert!(verilog.contains("wire \\and ;"), "{verilog}");
+        assert_eq!(
+            simulate(&verilog, Some(1), Some(3), false),
+            Ok("x = 1, y = 3, z = 4\n".to_string())
+        );
+        assert_eq!(
+            check_equivalence(&input, &Reference::Circuit(verilog), 0, 0),
+            Ok("circuit matches the reference circuit on all 16 inputs\n".to_string())
+        );
+    }
+
+    #[test]
+    fn exports_dot() {
+        let dot = export(&ADDER.to_string(), CircuitFormat::Dot).unwrap();
+        assert!(dot.starts_with("digraph day24 {\n"), "{dot}");
+        assert!(
+            dot.contains("[label=\"x00\", fillcolor=lightgrey, shape=box];"),
+            "{dot}"
+        );
+        assert!(
+            dot.contains("[label=\"c00 AND\", fillcolor=lightblue, shape=ellipse];"),
+            "{dot}"
+        );
+        assert!(
+            dot.contains("[label=\"z02 OR\", fillcolor=palegreen, shape=doublecircle];"),
+            "{dot}"
+        );
+        assert_eq!(dot.lines().filter(|line| line.contains(" -> ")).count(), 14);
+    }
+
+    #[test]
+    fn checks_equivalence() {
+        let input = ADDER.to_string();
+        assert_eq!(
+            check_equivalence(&input, &Reference::Add, 0, 0),
+            Ok("circuit matches x + y on all 16 inputs\n".to_string())
+        );
+        // 0 + 1 is the first input where the sum and the bitwise and differ
+        assert_eq!(
+            check_equivalence(&input, &Reference::And, 0, 0),
+            Ok(
+                "x = 0, y = 1: circuit gives z = 1, x & y gives 0\ndiffering bits: z00\n"
+                    .to_string()
+            )
+        );
+
+        // too wide for all inputs, so three edge cases and the random samples are compared
+        let input = adder(9);
+        assert_eq!(
+            check_equivalence(&input, &Reference::Add, 100, 1),
+            Ok("circuit matches x + y on 103 random inputs (seed 1)\n".to_string())
+        );
+        let broken = input.replace("a04 OR b04 -> c04", "a04 AND b04 -> c04");
+        let report = check_equivalence(&broken, &Reference::Add, 100, 1).unwrap();
+        assert!(report.contains("differing bits: z05"), "{report}");
+    }
+
+    /// `d` is dead, `k` is constant and `b` duplicates `a`.
+    const REDUNDANT: &str = "\
+x00: 1
+y00: 0
+
+x00 AND y00 -> a
+x00 AND y00 -> b
+a OR b -> z00
+x00 XOR x00 -> k
+k OR y00 -> z01
+x00 OR y00 -> d
+";
+
+    #[test]
+    fn analyses_redundant_gates() {
+        let report = analyse(&REDUNDANT.to_string(), false).unwrap();
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines[0], "6 gates: 2 AND, 3 OR, 1 XOR; 2 inputs");
+        assert!(lines.contains(&"dead gates: d"), "{report}");
+        assert!(lines.contains(&"constant wires: k = 0"), "{report}");
+        assert!(lines.contains(&"redundant gates: b = a"), "{report}");
+        assert!(
+            lines.contains(&"optimised: 4 of 6 gates remain"),
+            "{report}"
+        );
+    }
+
+    #[test]
+    fn optimises_redundant_gates() {
+        let optimised = optimise(&REDUNDANT.to_string()).unwrap();
+        let mut gates: Vec<&str> = optimised.lines().skip(3).collect();
+        gates.sort();
+        assert!(optimised.starts_with("x00: 1\ny00: 0\n\n"), "{optimised}");
+        assert_eq!(
+            gates,
+            [
+                "a OR a -> z00",
+                "k OR y00 -> z01",
+                "x00 AND y00 -> a",
+                "x00 XOR x00 -> k"
+            ]
+        );
+        assert_eq!(
+            check_equivalence(&optimised, &Reference::Circuit(REDUNDANT.to_string()), 0, 0),
+            Ok("circuit matches the reference circuit on all 4 inputs\n".to_string())
+        );
+    }
+
+    #[test]
+    fn repairs_swaps_in_neighbouring_bits() {
+        // gta/hpq in bit 7 only passes once opf/z08 in bit 8 fixes its carry
+        let input = generate::generate(24, generate::default_size(24), 6).unwrap();
+        assert_eq!(
+            Puzzle24 {}.solve_b(&input),
+            "gta,hpg,hpq,mtl,opf,z08,z11,z33"
+        );
     }
 }
@@ -1,4 +1,4 @@
-use crate::prelude::Aoc2024;
+use crate::prelude::{Aoc2024, ParseError};
 
 const LOCK_HEIGHT: u8 = 5;
 
@@ -10,36 +10,69 @@ struct Blueprint {
 
 pub struct Puzzle25 {}
 
-fn get_blueprints(input: &String) -> Vec<Blueprint> {
-    let parts = input.split("\n\n");
-    parts
-        .map(|blueprint| {
-            let blueprint: Vec<Vec<bool>> = blueprint
-                .trim()
-                .lines()
-                .map(|line| line.bytes().map(|el| el == b'.').collect::<Vec<bool>>())
-                .collect();
-            // it's a key if the top left element is '.'
-            let is_key = blueprint[0][0];
-            let size = blueprint.len();
-            let mut layout = Vec::new();
-            for j in 0..blueprint[0].len() {
-                for i in 0..size {
-                    if is_key ^ blueprint[i][j] {
-                        layout.push(i as u8 - 1);
-                        break;
-                    }
-                }
-            }
-            if is_key {
-                layout = layout.iter().map(|x| LOCK_HEIGHT - x).collect();
+impl Blueprint {
+    /// Parses a schematic of 7 rows with 5 pins. Locks have a full top row, keys a full bottom
+    /// row, and every pin is one solid column starting there.
+    fn parse(rows: &[&str]) -> Result<Self, String> {
+        let size = LOCK_HEIGHT as usize + 2;
+        if rows.len() != size {
+            return Err(format!("expected a schematic of {size} rows"));
+        }
+        let mut blueprint: Vec<Vec<bool>> = Vec::new();
+        for row in rows {
+            if row.len() != 5 || !row.bytes().all(|el| el == b'.' || el == b'#') {
+                return Err("expected 5 pins of `#` or `.` in a row".to_string());
             }
-            Blueprint {
-                is_key,
-                layout: layout.try_into().unwrap(),
+            blueprint.push(row.bytes().map(|el| el == b'.').collect());
+        }
+        // it's a key if the top left element is '.'
+        let is_key = blueprint[0][0];
+        let solid = (0..5).all(|j| {
+            let changes = (1..size)
+                .filter(|&i| blueprint[i][j] != blueprint[i - 1][j])
+                .count();
+            blueprint[0][j] == is_key && blueprint[size - 1][j] != is_key && changes == 1
+        });
+        if !solid {
+            return Err("expected solid pins from the top or the bottom".to_string());
+        }
+        let mut layout = Vec::new();
+        for j in 0..blueprint[0].len() {
+            for i in 0..size {
+                if is_key ^ blueprint[i][j] {
+                    layout.push(i as u8 - 1);
+                    break;
+                }
             }
+        }
+        if is_key {
+            layout = layout.iter().map(|x| LOCK_HEIGHT - x).collect();
+        }
+        Ok(Blueprint {
+            is_key,
+            layout: layout.try_into().unwrap(),
         })
-        .collect()
+    }
+}
+
+/// Locks and keys, separated by blank lines.
+fn get_blueprints(input: &str) -> Result<Vec<Blueprint>, ParseError> {
+    let lines: Vec<&str> = input.lines().map(str::trim).collect();
+    let mut blueprints = Vec::new();
+    let mut start = 0;
+    while start < lines.len() {
+        if lines[start].is_empty() {
+            start += 1;
+            continue;
+        }
+        let end = (start..lines.len())
+            .find(|&i| lines[i].is_empty())
+            .unwrap_or(lines.len());
+        let blueprint = Blueprint::parse(&lines[start..end]);
+        blueprints.push(blueprint.map_err(|err| ParseError::new(start, err))?);
+        start = end;
+    }
+    Ok(blueprints)
 }
 
 fn key_fits_lock(key: &Blueprint, lock: &Blueprint) -> bool {
@@ -55,7 +88,10 @@ impl Aoc2024 for Puzzle25 {
     }
 
     fn solve_a(&self, input: &String) -> String {
-        let blueprints = get_blueprints(input);
+        let blueprints = match get_blueprints(input) {
+            Ok(blueprints) => blueprints,
+            Err(err) => return err.to_string(),
+        };
         let keys: Vec<&Blueprint> = blueprints
             .iter()
             .filter(|blueprint| blueprint.is_key)
@@ -75,4 +111,37 @@ impl Aoc2024 for Puzzle25 {
     fn solve_b(&self, _input: &String) -> String {
         "None".to_string()
     }
+
+    fn parse(&self, input: &String) -> Result<(), ParseError> {
+        get_blueprints(input).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate;
+
+    #[test]
+    fn parses_generated_input() {
+        let input = generate::generate(25, generate::default_size(25), 1).unwrap();
+        assert_eq!(Puzzle25 {}.parse(&input), Ok(()));
+    }
+
+    #[test]
+    fn rejects_broken_input() {
+        let inputs = [
+            // a truncated lock used to index past its rows
+            (
+                "#####\n#####\n##.##\n##.#.\n##.#.\n#..#.\n.....\n\n#####\n#####\n##\n.",
+                8,
+            ),
+            // a pin with a gap
+            ("#####\n#.###\n#####\n.....\n.....\n.....\n.....\n", 0),
+        ];
+        for (input, line) in inputs {
+            let result = Puzzle25 {}.parse(&input.to_string());
+            assert_eq!(result.map_err(|err| err.line), Err(line), "{input:?}");
+        }
+    }
 }
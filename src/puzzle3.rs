@@ -1,56 +1,106 @@
-use crate::prelude::Aoc2024;
+use crate::prelude::{Aoc2024, ParseError};
 use regex::Regex;
 pub struct Puzzle3 {}
 
+enum Instruction {
+    Mul(i64, i64),
+    Do,
+    Dont,
+}
+
+/// Picks the instructions out of the corrupted memory. The memory is printable ASCII, anything
+/// else such as control characters means it is not a puzzle input.
+fn get_instructions(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    for (i, line) in input.lines().enumerate() {
+        if let Some(el) = line
+            .chars()
+            .find(|el| !(el.is_ascii_graphic() || *el == ' '))
+        {
+            let message = format!("unexpected character {el:?} in the memory");
+            return Err(ParseError::new(i, message));
+        }
+    }
+    // [0-9] instead of \d, which would also match digits of other scripts
+    let regex = Regex::new(r"mul\(([0-9]{1,3}),([0-9]{1,3})\)|don't|do").unwrap();
+    Ok(regex
+        .captures_iter(input)
+        .map(|captures| match (captures.get(1), captures.get(2)) {
+            (Some(a), Some(b)) => {
+                Instruction::Mul(a.as_str().parse().unwrap(), b.as_str().parse().unwrap())
+            }
+            _ if captures[0].starts_with("don") => Instruction::Dont,
+            _ => Instruction::Do,
+        })
+        .collect())
+}
+
 impl Aoc2024 for Puzzle3 {
     fn name(&self) -> String {
         "Day 3: Mull It Over".to_string()
     }
 
     fn solve_a(&self, input: &String) -> String {
-        let mul_regex = Regex::new(r"mul\(\d{1,3},\d{1,3}\)").unwrap();
-        let matches = mul_regex.find_iter(&*input);
-
-        let mut result_a: i64 = 0;
+        let instructions = match get_instructions(input) {
+            Ok(instructions) => instructions,
+            Err(err) => return err.to_string(),
+        };
 
         // Part 1: Count compute muls
-        for mul in matches {
-            let mul_str = mul.as_str();
-            let rest: Vec<i64> = mul_str
-                .get(4..mul.len() - 1)
-                .unwrap()
-                .split(",")
-                .filter_map(|num| num.parse::<i64>().ok())
-                .collect();
-            result_a += rest[0] * rest[1];
-        }
+        let result_a: i64 = instructions
+            .iter()
+            .map(|instruction| match instruction {
+                Instruction::Mul(a, b) => a * b,
+                _ => 0,
+            })
+            .sum();
         result_a.to_string()
     }
 
     fn solve_b(&self, input: &String) -> String {
-        let mul_regex = Regex::new(r"mul\(\d{1,3},\d{1,3}\)|don't|do").unwrap();
-        let matches = mul_regex.find_iter(&*input);
+        let instructions = match get_instructions(input) {
+            Ok(instructions) => instructions,
+            Err(err) => return err.to_string(),
+        };
 
         let mut result_b: i64 = 0;
         let mut enabled: i64 = 1;
 
         // Part 2
-        for mul in matches {
-            let mul_str = mul.as_str();
-            if mul_str.starts_with("don") {
-                enabled = 0;
-            } else if mul_str.starts_with("do") {
-                enabled = 1;
-            } else {
-                let rest: Vec<i64> = mul_str
-                    .get(4..mul.len() - 1)
-                    .unwrap()
-                    .split(",")
-                    .filter_map(|num| num.parse::<i64>().ok())
-                    .collect();
-                result_b += enabled * rest[0] * rest[1];
+        for instruction in instructions {
+            match instruction {
+                Instruction::Dont => enabled = 0,
+                Instruction::Do => enabled = 1,
+                Instruction::Mul(a, b) => result_b += enabled * a * b,
             }
         }
         result_b.to_string()
     }
+
+    fn parse(&self, input: &String) -> Result<(), ParseError> {
+        get_instructions(input).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate;
+
+    #[test]
+    fn parses_generated_input() {
+        let input = generate::generate(3, generate::default_size(3), 1).unwrap();
+        assert_eq!(Puzzle3 {}.parse(&input), Ok(()));
+    }
+
+    #[test]
+    fn rejects_broken_input() {
+        let inputs = [
+            // a control character in the memory
+            ("mul(2,4)\nxmul(1,2)\u{0}do()", 1),
+        ];
+        for (input, line) in inputs {
+            let result = Puzzle3 {}.parse(&input.to_string());
+            assert_eq!(result.map_err(|err| err.line), Err(line), "{input:?}");
+        }
+    }
 }
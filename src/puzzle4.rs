@@ -1,23 +1,58 @@
-use crate::prelude::Aoc2024;
+use crate::prelude::{Aoc2024, ParseError};
 use std::collections::HashMap;
 pub struct Puzzle4 {}
 
+/// The word search, a non-empty rectangle of uppercase letters.
+fn get_grid(input: &str) -> Result<Vec<Vec<u8>>, ParseError> {
+    let grid: Vec<Vec<u8>> = input
+        .trim_end()
+        .lines()
+        .map(|line| line.as_bytes().to_vec())
+        .collect();
+    let width = grid.first().map_or(0, |row| row.len());
+    if width == 0 {
+        return Err(ParseError::new(0, "the word search is empty"));
+    }
+    for (i, row) in grid.iter().enumerate() {
+        if row.len() != width {
+            return Err(ParseError::new(i, "all rows must have the same length"));
+        }
+        if let Some(&el) = row.iter().find(|el| !el.is_ascii_uppercase()) {
+            return Err(ParseError::new(
+                i,
+                format!("unexpected letter {:?}", el as char),
+            ));
+        }
+    }
+    Ok(grid)
+}
+
 impl Aoc2024 for Puzzle4 {
     fn name(&self) -> String {
         "Day 4: Ceres Search".to_string()
     }
 
     fn solve_a(&self, input: &String) -> String {
-        let grid: Vec<Vec<u8>> = input.lines().map(|line| line.as_bytes().to_vec()).collect();
+        let grid = match get_grid(input) {
+            Ok(grid) => grid,
+            Err(err) => return err.to_string(),
+        };
         let (count, _) = count_word_search(&grid, "XMAS");
         count.to_string()
     }
 
     fn solve_b(&self, input: &String) -> String {
-        let grid: Vec<Vec<u8>> = input.lines().map(|line| line.as_bytes().to_vec()).collect();
+        let grid = match get_grid(input) {
+            Ok(grid) => grid,
+            Err(err) => return err.to_string(),
+        };
         let (_, cross_count) = count_word_search(&grid, "MAS");
         cross_count.to_string()
     }
+
+    fn parse(&self, input: &String) -> Result<(), ParseError> {
+        get_grid(input).map(|_| ())
+    }
 }
 
 fn count_word_search(grid: &Vec<Vec<u8>>, word: &str) -> (usize, usize) {
@@ -100,3 +135,29 @@ fn check_word(
     }
     res
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate;
+
+    #[test]
+    fn parses_generated_input() {
+        let input = generate::generate(4, generate::default_size(4), 1).unwrap();
+        assert_eq!(Puzzle4 {}.parse(&input), Ok(()));
+    }
+
+    #[test]
+    fn rejects_broken_input() {
+        let inputs = [
+            // a row shorter than the others
+            ("XMAS\nMAS\n", 1),
+            // lowercase letters
+            ("XMAS\nxmas\n", 1),
+        ];
+        for (input, line) in inputs {
+            let result = Puzzle4 {}.parse(&input.to_string());
+            assert_eq!(result.map_err(|err| err.line), Err(line), "{input:?}");
+        }
+    }
+}
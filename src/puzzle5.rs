@@ -1,19 +1,15 @@
-use crate::prelude::Aoc2024;
-use std::cmp::Ordering;
+use crate::graph::Graph;
+use crate::prelude::{Aoc2024, ParseError};
 use std::collections::{HashMap, HashSet};
 
-fn get_correct<'a, I>(lines: I, rules: &HashMap<i64, Vec<i64>>, correct: bool) -> Vec<Vec<i64>>
-where
-    I: Iterator<Item = &'a str>,
-{
-    let orders: Vec<Vec<i64>> = lines
-        .skip_while(|line| line.trim() != "")
-        .skip(1)
-        .filter_map(|line| {
-            let order: Vec<i64> = line
-                .split(",")
-                .filter_map(|num| num.parse::<i64>().ok())
-                .collect();
+/// Pages that must come before each page.
+type Rules = HashMap<i64, Vec<i64>>;
+
+fn get_correct(updates: &[Vec<i64>], rules: &Rules, correct: bool) -> Vec<Vec<i64>> {
+    let orders: Vec<Vec<i64>> = updates
+        .iter()
+        .cloned()
+        .filter_map(|order| {
             let mut forbidden: HashSet<i64> = HashSet::new();
             for &el in &order {
                 if forbidden.contains(&el) {
@@ -37,42 +33,51 @@ where
     orders
 }
 
-fn find_corrected_middle(mut order: Vec<i64>, rules: &HashMap<i64, Vec<i64>>) -> i64 {
-    order.sort_by(|a, b| {
-        if a == b {
-            return Ordering::Equal;
-        }
-        if let Some(after) = rules.get(a) {
-            if after.contains(b) {
-                return Ordering::Less;
-            }
-        }
-        Ordering::Greater
-    });
-    order[order.len() / 2]
+/// Orders the pages by how many pages of the update must come before them. With consistent rules
+/// this is their position, contradicting rules still give some order instead of a broken sort.
+fn find_corrected_middle(order: &[i64], rules: &Rules) -> i64 {
+    let mut ranked: Vec<(usize, i64)> = order
+        .iter()
+        .map(|&page| {
+            let before = rules.get(&page).map_or(0, |before| {
+                order.iter().filter(|other| before.contains(other)).count()
+            });
+            (before, page)
+        })
+        .collect();
+    ranked.sort();
+    ranked[ranked.len() / 2].1
 }
 
 pub struct Puzzle5 {}
 
+/// A page number, non-negative and 32 bit so the sum of the middle pages fits.
+fn parse_page(value: &str) -> Option<i64> {
+    value.trim().parse::<u32>().ok().map(i64::from)
+}
+
 impl Puzzle5 {
-    fn get_rules(input: &String) -> HashMap<i64, Vec<i64>> {
-        input
-            .lines()
-            .take_while(|line| line.trim() != "")
-            .filter_map(|line| {
-                let mut parts = line.split("|").filter_map(|num| num.parse::<i64>().ok());
-                if let (Some(before), Some(after)) = (parts.next(), parts.next()) {
-                    Some((before, after))
-                } else {
-                    None
-                }
-            })
-            .fold(HashMap::new(), |mut acc, (before, after)| {
-                acc.entry(after)
-                    .and_modify(|element| element.push(before))
-                    .or_insert(vec![before]);
-                acc
+    /// The ordering rules, pages that must come before each page, and the updates.
+    fn get_input(input: &str) -> Result<(Rules, Vec<Vec<i64>>), ParseError> {
+        let mut lines = input.lines().enumerate();
+        let mut rules = Rules::new();
+        for (i, line) in lines.by_ref().take_while(|(_, line)| line.trim() != "") {
+            let (before, after) = line
+                .split_once('|')
+                .and_then(|(before, after)| Some((parse_page(before)?, parse_page(after)?)))
+                .ok_or_else(|| ParseError::new(i, "expected a rule `<page>|<page>`"))?;
+            rules.entry(after).or_default().push(before);
+        }
+        let updates = lines
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                line.split(',')
+                    .map(parse_page)
+                    .collect::<Option<Vec<i64>>>()
+                    .ok_or_else(|| ParseError::new(i, "expected pages separated by commas"))
             })
+            .collect::<Result<_, _>>()?;
+        Ok((rules, updates))
     }
 }
 
@@ -82,8 +87,11 @@ impl Aoc2024 for Puzzle5 {
     }
 
     fn solve_a(&self, input: &String) -> String {
-        let rules = Self::get_rules(input);
-        let middle_sum: i64 = get_correct(input.lines(), &rules, true)
+        let (rules, updates) = match Self::get_input(input) {
+            Ok(input) => input,
+            Err(err) => return err.to_string(),
+        };
+        let middle_sum: i64 = get_correct(&updates, &rules, true)
             .iter()
             .map(|order| order[order.len() / 2])
             .sum();
@@ -91,11 +99,62 @@ impl Aoc2024 for Puzzle5 {
     }
 
     fn solve_b(&self, input: &String) -> String {
-        let rules = Self::get_rules(input);
-        let middle_sum: i64 = get_correct(input.lines(), &rules, false)
+        let (rules, updates) = match Self::get_input(input) {
+            Ok(input) => input,
+            Err(err) => return err.to_string(),
+        };
+        let middle_sum: i64 = get_correct(&updates, &rules, false)
             .iter()
-            .map(|order| find_corrected_middle(order.clone(), &rules))
+            .map(|order| find_corrected_middle(order, &rules))
             .sum();
         middle_sum.to_string()
     }
+
+    fn graph(&self, input: &String) -> Option<Graph<String>> {
+        let mut graph = Graph::new(true);
+        let (rules, _) = Self::get_input(input).ok()?;
+        for (after, befores) in rules {
+            for before in befores {
+                graph.connect(before.to_string(), after.to_string());
+            }
+        }
+        Some(graph)
+    }
+
+    fn parse(&self, input: &String) -> Result<(), ParseError> {
+        Self::get_input(input).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate;
+
+    #[test]
+    fn parses_generated_input() {
+        let input = generate::generate(5, generate::default_size(5), 1).unwrap();
+        assert_eq!(Puzzle5 {}.parse(&input), Ok(()));
+    }
+
+    #[test]
+    fn rejects_broken_input() {
+        let inputs = [
+            // a page that is not a number
+            ("47|53\n\n75,47,x\n", 2),
+            // a rule without `|`
+            ("47-53\n\n75,47\n", 0),
+        ];
+        for (input, line) in inputs {
+            let result = Puzzle5 {}.parse(&input.to_string());
+            assert_eq!(result.map_err(|err| err.line), Err(line), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn orders_updates_with_contradicting_rules() {
+        // a cycle of rules used to break the sort
+        let input = "1|2\n2|3\n3|1\n2|4\n\n4,3,2,1,5\n".to_string();
+        assert_eq!(Puzzle5 {}.solve_b(&input), "2");
+    }
 }
@@ -1,4 +1,4 @@
-use crate::prelude::Aoc2024;
+use crate::prelude::{render_grid, Aoc2024, Frames, ParseError};
 use std::cmp::PartialEq;
 use std::fmt::{Debug, Formatter, Write};
 
@@ -36,14 +36,16 @@ impl Field {
     }
 }
 
-impl From<&u8> for Field {
-    fn from(value: &u8) -> Self {
+impl TryFrom<&u8> for Field {
+    type Error = String;
+
+    fn try_from(value: &u8) -> Result<Self, Self::Error> {
         match value {
-            b'.' => Field::FREE,
-            b'#' => Field::BLOCKED,
-            b'X' => Field::VISITED(0), // Initially no directions visited
-            b'^' => Field::GUARD,
-            _ => Field::FREE,
+            b'.' => Ok(Field::FREE),
+            b'#' => Ok(Field::BLOCKED),
+            b'X' => Ok(Field::VISITED(0)), // Initially no directions visited
+            b'^' => Ok(Field::GUARD),
+            _ => Err(format!("unknown field {:?}", *value as char)),
         }
     }
 }
@@ -64,34 +66,47 @@ enum WalkingResult {
     LOOP,
 }
 
-fn walk_board(board: &mut Vec<Vec<Field>>, mut x: i32, mut y: i32) -> WalkingResult {
-    let mut direction = 0;
+struct Guard {
+    x: i32,
+    y: i32,
+    direction: usize,
+}
+
+/// Advances the guard by one move, returning a result once the walk is over.
+fn step_guard(board: &mut Vec<Vec<Field>>, guard: &mut Guard) -> Option<WalkingResult> {
     let directions = [Field::UP, Field::RIGHT, Field::DOWN, Field::LEFT];
+    let current_cell = &mut board[guard.x as usize][guard.y as usize];
+    if current_cell.has_visited(directions[guard.direction]) {
+        return Some(WalkingResult::LOOP);
+    }
+    current_cell.mark_visited(directions[guard.direction]);
+
+    let (dx, dy) = match guard.direction {
+        0 => (-1, 0), // UP
+        1 => (0, 1),  // RIGHT
+        2 => (1, 0),  // DOWN
+        3 => (0, -1), // LEFT
+        _ => unreachable!(),
+    };
+    let next_x = guard.x + dx;
+    let next_y = guard.y + dy;
+    if next_x < 0 || next_x >= board.len() as i32 || next_y < 0 || next_y >= board.len() as i32 {
+        return Some(WalkingResult::EXITED);
+    }
+    if board[next_x as usize][next_y as usize] == Field::BLOCKED {
+        guard.direction = (guard.direction + 1) % 4;
+    } else {
+        guard.x = next_x;
+        guard.y = next_y;
+    }
+    None
+}
+
+fn walk_board(board: &mut Vec<Vec<Field>>, x: i32, y: i32) -> WalkingResult {
+    let mut guard = Guard { x, y, direction: 0 };
     loop {
-        let current_cell = &mut board[x as usize][y as usize];
-        if current_cell.has_visited(directions[direction]) {
-            return WalkingResult::LOOP;
-        }
-        current_cell.mark_visited(directions[direction]);
-
-        let (dx, dy) = match direction {
-            0 => (-1, 0), // UP
-            1 => (0, 1),  // RIGHT
-            2 => (1, 0),  // DOWN
-            3 => (0, -1), // LEFT
-            _ => unreachable!(),
-        };
-        let next_x = x + dx;
-        let next_y = y + dy;
-        if next_x < 0 || next_x >= board.len() as i32 || next_y < 0 || next_y >= board.len() as i32
-        {
-            return WalkingResult::EXITED;
-        }
-        if board[next_x as usize][next_y as usize] == Field::BLOCKED {
-            direction = (direction + 1) % 4;
-        } else {
-            x = next_x;
-            y = next_y;
+        if let Some(result) = step_guard(board, &mut guard) {
+            return result;
         }
     }
 }
@@ -99,12 +114,28 @@ fn walk_board(board: &mut Vec<Vec<Field>>, mut x: i32, mut y: i32) -> WalkingRes
 pub struct Puzzle6 {}
 
 impl Puzzle6 {
-    fn get_board(input: &String) -> Vec<Vec<Field>> {
-        input
+    fn get_board(input: &String) -> Result<Vec<Vec<Field>>, ParseError> {
+        let board: Vec<Vec<Field>> = input
             .lines()
             .map(str::trim)
-            .map(|line| line.as_bytes().iter().map(Field::from).collect())
-            .collect()
+            .enumerate()
+            .map(|(i, line)| {
+                line.as_bytes()
+                    .iter()
+                    .map(Field::try_from)
+                    .collect::<Result<_, _>>()
+                    .map_err(|message| ParseError::new(i, message))
+            })
+            .collect::<Result<_, _>>()?;
+        // the walk assumes a square board
+        if let Some(i) = board.iter().position(|row| row.len() != board.len()) {
+            return Err(ParseError::new(i, "board must be square"));
+        }
+        let guards = board.iter().flatten().filter(|&&el| el == Field::GUARD);
+        if guards.count() != 1 {
+            return Err(ParseError::new(0, "board must contain exactly one guard"));
+        }
+        Ok(board)
     }
 
     fn get_guard(board: &Vec<Vec<Field>>) -> (i32, i32) {
@@ -126,7 +157,10 @@ impl Aoc2024 for Puzzle6 {
     }
 
     fn solve_a(&self, input: &String) -> String {
-        let board = Self::get_board(input);
+        let board = match Self::get_board(input) {
+            Ok(board) => board,
+            Err(err) => return err.to_string(),
+        };
         let (x, y) = Self::get_guard(&board);
 
         let mut a_board = board.clone();
@@ -143,7 +177,10 @@ impl Aoc2024 for Puzzle6 {
     }
 
     fn solve_b(&self, input: &String) -> String {
-        let board = Self::get_board(input);
+        let board = match Self::get_board(input) {
+            Ok(board) => board,
+            Err(err) => return err.to_string(),
+        };
         let (x, y) = Self::get_guard(&board);
 
         let mut loops = 0;
@@ -161,4 +198,68 @@ impl Aoc2024 for Puzzle6 {
         }
         loops.to_string()
     }
+
+    fn parse(&self, input: &String) -> Result<(), ParseError> {
+        Self::get_board(input).map(|_| ())
+    }
+
+    /// Only part a is a simulation, part b tries obstacles on every field of the walk.
+    fn frames(&self, input: &String, part_b: bool) -> Option<Frames> {
+        if part_b {
+            return None;
+        }
+        let mut board = Self::get_board(input).ok()?;
+        let (x, y) = Self::get_guard(&board);
+        let mut guard = Guard { x, y, direction: 0 };
+        let mut done = false;
+        Some(Box::new(std::iter::from_fn(move || {
+            if done {
+                return None;
+            }
+            done = step_guard(&mut board, &mut guard).is_some();
+            let mut frame = board.clone();
+            frame[guard.x as usize][guard.y as usize] = Field::GUARD;
+            Some(render_grid(&frame))
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+....#.....\n\
+.........#\n\
+..........\n\
+..#.......\n\
+.......#..\n\
+..........\n\
+.#..^.....\n\
+........#.\n\
+#.........\n\
+......#...\n";
+
+    #[test]
+    fn reads_every_field() {
+        let fields: Vec<Field> = b".#X^"
+            .iter()
+            .map(|value| Field::try_from(value).unwrap())
+            .collect();
+        assert_eq!(
+            fields,
+            [Field::FREE, Field::BLOCKED, Field::VISITED(0), Field::GUARD]
+        );
+        // unknown fields used to be read as free ones
+        assert_eq!(Field::try_from(&b'O'), Err("unknown field 'O'".to_string()));
+        let input = EXAMPLE.replacen('.', "O", 1);
+        assert_eq!(Puzzle6 {}.parse(&input).map_err(|err| err.line), Err(0));
+    }
+
+    #[test]
+    fn solves_the_example() {
+        let input = EXAMPLE.to_string();
+        assert_eq!(Puzzle6 {}.solve_a(&input), "41");
+        assert_eq!(Puzzle6 {}.solve_b(&input), "6");
+    }
 }
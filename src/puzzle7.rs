@@ -1,4 +1,4 @@
-use crate::prelude::Aoc2024;
+use crate::prelude::{Aoc2024, ParseError};
 
 fn brute_force(
     curr: i64,
@@ -15,21 +15,25 @@ fn brute_force(
         }
     }
     let next_val = values[index as usize];
-    let next = curr + next_val;
-    if next <= target {
+    // results beyond i64 are larger than any target
+    let next = curr.checked_add(next_val);
+    if let Some(next) = next.filter(|&next| next <= target) {
         if let Some(res) = brute_force(next, index + 1, values, target, allow_concat) {
             return Some(res);
         }
     }
-    let next = curr * next_val;
-    if next <= target {
+    let next = curr.checked_mul(next_val);
+    if let Some(next) = next.filter(|&next| next <= target) {
         if let Some(res) = brute_force(next, index + 1, values, target, allow_concat) {
             return Some(res);
         }
     }
     if allow_concat {
-        let next = values[index as usize] + curr * 10i64.pow(next_val.ilog(10) + 1);
-        if next <= target {
+        let digits = next_val.checked_ilog10().unwrap_or(0) + 1;
+        let next = curr
+            .checked_mul(10i64.pow(digits))
+            .and_then(|shifted| shifted.checked_add(next_val));
+        if let Some(next) = next.filter(|&next| next <= target) {
             if let Some(res) = brute_force(next, index + 1, values, target, allow_concat) {
                 return Some(res);
             }
@@ -38,15 +42,24 @@ fn brute_force(
     None
 }
 
-fn handle_line(line: &str, allow_concat: bool) -> Option<i64> {
+/// A non-negative test value and the 32 bit numbers combined to reach it.
+fn parse_line(line: &str) -> Option<(i64, Vec<i64>)> {
     let mut parts = line.split(":");
-    let res = parts.next().and_then(|num| num.parse::<i64>().ok())?;
+    let res = parts
+        .next()
+        .and_then(|num| num.parse::<i64>().ok())
+        .filter(|&res| res >= 0)?;
     let values: Vec<i64> = parts.next().and_then(|rest| {
         rest.trim()
             .split(" ")
-            .map(|num| num.parse::<i64>().ok())
+            .map(|num| num.parse::<u32>().ok().map(i64::from))
             .collect()
     })?;
+    Some((res, values))
+}
+
+fn handle_line(line: &str, allow_concat: bool) -> Option<i64> {
+    let (res, values) = parse_line(line)?;
 
     // test if res can be computed with values
     brute_force(*values.first()?, 1, &values, res, allow_concat)
@@ -76,4 +89,42 @@ impl Aoc2024 for Puzzle7 {
             .sum();
         calibration_result.to_string()
     }
+
+    fn parse(&self, input: &String) -> Result<(), ParseError> {
+        for (i, line) in input.trim().lines().enumerate() {
+            parse_line(line).ok_or_else(|| ParseError::new(i, "expected `<result>: <numbers>`"))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+190: 10 19\n\
+3267: 81 40 27\n\
+83: 17 5\n\
+156: 15 6\n\
+7290: 6 8 6 15\n\
+161011: 16 10 13\n\
+192: 17 8 14\n\
+21037: 9 7 18 13\n\
+292: 11 6 16 20\n";
+
+    #[test]
+    fn solves_the_example() {
+        let input = EXAMPLE.to_string();
+        assert_eq!(Puzzle7 {}.solve_a(&input), "3749");
+        assert_eq!(Puzzle7 {}.solve_b(&input), "11387");
+    }
+
+    #[test]
+    fn concatenates_zeros_and_large_numbers() {
+        // a zero has no integer logarithm and 4294967295 concatenated twice overflows i64
+        let input = "100: 10 0\n9223372036854775807: 4294967295 4294967295 4294967295\n";
+        assert_eq!(Puzzle7 {}.solve_b(&input.to_string()), "100");
+        assert!(Puzzle7 {}.parse(&"-5: 1 2\n".to_string()).is_err());
+    }
 }
@@ -1,5 +1,5 @@
-use crate::prelude::Aoc2024;
-use gcd::Gcd;
+use crate::number_theory::gcd;
+use crate::prelude::{Aoc2024, ParseError};
 use std::collections::HashSet;
 use std::fmt::{Debug, Formatter, Write};
 
@@ -10,11 +10,14 @@ enum Field {
     NODE(u8),
 }
 
-impl From<&u8> for Field {
-    fn from(value: &u8) -> Self {
+impl TryFrom<&u8> for Field {
+    type Error = String;
+
+    fn try_from(value: &u8) -> Result<Self, Self::Error> {
         match value {
-            b'.' => Field::FREE,
-            c => Field::NODE(*c),
+            b'.' => Ok(Field::FREE),
+            c if c.is_ascii_alphanumeric() => Ok(Field::NODE(*c)),
+            _ => Err(format!("unknown field {:?}", *value as char)),
         }
     }
 }
@@ -32,13 +35,27 @@ impl Debug for Field {
 pub struct Puzzle8 {}
 
 impl Puzzle8 {
-    fn count_special_nodes(input: &String) -> (usize, usize) {
-        let mut board: Vec<Vec<Field>> = input
+    fn get_board(input: &str) -> Result<Vec<Vec<Field>>, ParseError> {
+        let board: Vec<Vec<Field>> = input
             .lines()
             .map(str::trim)
-            .map(|line| line.as_bytes().iter().map(Field::from).collect())
-            .collect();
+            .enumerate()
+            .map(|(i, line)| {
+                line.as_bytes()
+                    .iter()
+                    .map(Field::try_from)
+                    .collect::<Result<_, _>>()
+                    .map_err(|message| ParseError::new(i, message))
+            })
+            .collect::<Result<_, _>>()?;
+        // the bounds checks assume a square board
+        if let Some(i) = board.iter().position(|row| row.len() != board.len()) {
+            return Err(ParseError::new(i, "board must be square"));
+        }
+        Ok(board)
+    }
 
+    fn count_special_nodes(mut board: Vec<Vec<Field>>) -> (usize, usize) {
         let mut simple_antinodes: HashSet<(i64, i64)> = HashSet::new();
         let mut harmonic_antinodes: HashSet<(i64, i64)> = HashSet::new();
 
@@ -79,8 +96,8 @@ impl Puzzle8 {
                     simple_antinodes.insert(first);
                     simple_antinodes.insert(second);
 
-                    let diff = ((a.0 - b.0).abs() as u64, (a.1 - b.1).abs() as u64);
-                    let divider: i64 = diff.0.gcd(diff.1) as i64;
+                    let divider =
+                        gcd(a.0 - b.0, a.1 - b.1).expect("offsets in the grid are small");
                     let diff = ((a.0 - b.0) / divider, (a.1 - b.1) / divider);
                     harmonic_antinodes.insert(a);
                     let mut next = (a.0 - diff.0, a.1 - diff.1);
@@ -120,12 +137,50 @@ impl Aoc2024 for Puzzle8 {
     }
 
     fn solve_a(&self, input: &String) -> String {
-        let (simple_antinodes, _) = Self::count_special_nodes(input);
+        let board = match Self::get_board(input) {
+            Ok(board) => board,
+            Err(err) => return err.to_string(),
+        };
+        let (simple_antinodes, _) = Self::count_special_nodes(board);
         simple_antinodes.to_string()
     }
 
     fn solve_b(&self, input: &String) -> String {
-        let (_, harmonitc_antinodes) = Self::count_special_nodes(input);
+        let board = match Self::get_board(input) {
+            Ok(board) => board,
+            Err(err) => return err.to_string(),
+        };
+        let (_, harmonitc_antinodes) = Self::count_special_nodes(board);
         harmonitc_antinodes.to_string()
     }
+
+    fn parse(&self, input: &String) -> Result<(), ParseError> {
+        Self::get_board(input).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate;
+
+    #[test]
+    fn parses_generated_input() {
+        let input = generate::generate(8, generate::default_size(8), 1).unwrap();
+        assert_eq!(Puzzle8 {}.parse(&input), Ok(()));
+    }
+
+    #[test]
+    fn rejects_broken_input() {
+        let inputs = [
+            // a longer row used to index past the map
+            ("....\n..7.Q\n....\n....\n", 1),
+            // an unknown field
+            ("....\n..\u{1a}.\n....\n....\n", 1),
+        ];
+        for (input, line) in inputs {
+            let result = Puzzle8 {}.parse(&input.to_string());
+            assert_eq!(result.map_err(|err| err.line), Err(line), "{input:?}");
+        }
+    }
 }
@@ -1,14 +1,33 @@
-use crate::prelude::Aoc2024;
+use crate::prelude::{Aoc2024, ParseError};
 
 pub struct Puzzle9 {}
 
-fn get_free_spaces(input: &String) -> (Vec<(usize, usize)>, usize) {
-    let mut pos = 0;
-    let free_spaces = input
+/// Block lengths of the disk map, alternating between files and free space.
+fn get_disk_map(input: &str) -> Result<Vec<usize>, ParseError> {
+    input
+        .trim_end()
         .bytes()
         .enumerate()
-        .filter_map(|(i, el)| {
-            let length = (el - b'0') as usize;
+        .map(|(i, el)| match el {
+            b'0'..=b'9' => Ok((el - b'0') as usize),
+            _ => Err(ParseError::new(
+                0,
+                format!(
+                    "expected digit at column {} but got {:?}",
+                    i + 1,
+                    el as char
+                ),
+            )),
+        })
+        .collect()
+}
+
+fn get_free_spaces(disk_map: &[usize]) -> (Vec<(usize, usize)>, usize) {
+    let mut pos = 0;
+    let free_spaces = disk_map
+        .iter()
+        .enumerate()
+        .filter_map(|(i, &length)| {
             if i % 2 == 0 {
                 pos += length;
                 None
@@ -29,13 +48,16 @@ impl Aoc2024 for Puzzle9 {
     }
 
     fn solve_a(&self, input: &String) -> String {
-        let (mut free_spaces, mut pos) = get_free_spaces(input);
+        let disk_map = match get_disk_map(input) {
+            Ok(disk_map) => disk_map,
+            Err(err) => return err.to_string(),
+        };
+        let (mut free_spaces, mut pos) = get_free_spaces(&disk_map);
         free_spaces.reverse();
 
         let mut result = 0;
-        for (i, el) in input.bytes().enumerate().rev() {
+        for (i, &length) in disk_map.iter().enumerate().rev() {
             let id = i / 2;
-            let length = (el - b'0') as usize;
             if i % 2 != 0 {
                 pos -= length;
                 continue;
@@ -63,35 +85,63 @@ impl Aoc2024 for Puzzle9 {
     }
 
     fn solve_b(&self, input: &String) -> String {
-        let (mut free_spaces, mut pos) = get_free_spaces(input);
-        free_spaces.reverse();
+        let disk_map = match get_disk_map(input) {
+            Ok(disk_map) => disk_map,
+            Err(err) => return err.to_string(),
+        };
+        let (mut free_spaces, mut pos) = get_free_spaces(&disk_map);
 
-        let cost = |id: usize, start: usize, len: usize| id * (start * len + (len * (len - 1)) / 2);
+        let cost = |id: usize, start: usize, len: usize| match len {
+            0 => 0,
+            _ => id * (start * len + (len * (len - 1)) / 2),
+        };
 
         let mut result = 0;
-        for (i, el) in input.bytes().enumerate().rev() {
+        for (i, &length) in disk_map.iter().enumerate().rev() {
             let id = i / 2;
-            let length = (el - b'0') as usize;
             pos -= length;
             if i % 2 != 0 {
                 continue; // skip free spaces
             }
+            // the leftmost free space left of the file that is large enough
             if let Some((start, len)) = free_spaces
                 .iter_mut()
-                .filter(|(_, len)| *len >= length)
-                .next()
+                .take_while(|(start, _)| *start < pos)
+                .find(|(_, len)| *len >= length)
             {
-                if *start < pos {
-                    result += cost(id, *start, length);
-                    *start += length;
-                    *len -= length;
-                } else {
-                    result += cost(id, pos, length);
-                }
+                result += cost(id, *start, length);
+                *start += length;
+                *len -= length;
             } else {
                 result += cost(id, pos, length);
             }
         }
         result.to_string()
     }
+
+    fn parse(&self, input: &String) -> Result<(), ParseError> {
+        get_disk_map(input).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solves_the_example_with_a_trailing_newline() {
+        let input = "2333133121414131402\n".to_string();
+        assert_eq!(Puzzle9 {}.parse(&input), Ok(()));
+        assert_eq!(Puzzle9 {}.solve_a(&input), "1928");
+        assert_eq!(Puzzle9 {}.solve_b(&input), "2858");
+    }
+
+    #[test]
+    fn handles_zero_length_files() {
+        for (input, checksum) in [("1203", "0"), ("12031", "2")] {
+            let input = input.to_string();
+            assert_eq!(Puzzle9 {}.solve_a(&input), checksum, "{input}");
+            assert_eq!(Puzzle9 {}.solve_b(&input), checksum, "{input}");
+        }
+    }
 }
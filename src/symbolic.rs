@@ -0,0 +1,490 @@
+use crate::prelude::ParseError;
+use crate::puzzle17::{load, Combo, Instruction, Machine, VmError};
+use std::collections::{BTreeSet, HashMap};
+
+/// Unknown low bits of register A, the highest bit stays zero so A is a positive `i64`.
+const WIDTH: usize = 63;
+/// Instructions executed over all paths before the exploration gives up.
+const STEP_LIMIT: usize = 100_000;
+/// Expression nodes built before the exploration gives up, loops that never shift A out can
+/// grow the other registers without bound.
+const NODE_LIMIT: usize = 1_000_000;
+
+type Expr = usize;
+const FALSE: Expr = 0;
+const TRUE: Expr = 1;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Node {
+    Const(bool),
+    Bit(usize),
+    Not(Expr),
+    And(Expr, Expr),
+    Xor(Expr, Expr),
+}
+
+/// Hash consed boolean expressions over the bits of A.
+/// Children are always created before their parents, so the nodes are topologically sorted.
+struct Circuit {
+    nodes: Vec<Node>,
+    index: HashMap<Node, Expr>,
+}
+
+impl Circuit {
+    fn new() -> Self {
+        let mut circuit = Self {
+            nodes: Vec::new(),
+            index: HashMap::new(),
+        };
+        circuit.intern(Node::Const(false));
+        circuit.intern(Node::Const(true));
+        circuit
+    }
+
+    fn intern(&mut self, node: Node) -> Expr {
+        *self.index.entry(node).or_insert_with(|| {
+            self.nodes.push(node);
+            self.nodes.len() - 1
+        })
+    }
+
+    fn not(&mut self, x: Expr) -> Expr {
+        match self.nodes[x] {
+            Node::Const(value) => !value as Expr,
+            Node::Not(inner) => inner,
+            _ => self.intern(Node::Not(x)),
+        }
+    }
+
+    fn and(&mut self, x: Expr, y: Expr) -> Expr {
+        match (x, y) {
+            (FALSE, _) | (_, FALSE) => FALSE,
+            (TRUE, z) | (z, TRUE) => z,
+            _ if x == y => x,
+            _ => self.intern(Node::And(x.min(y), x.max(y))),
+        }
+    }
+
+    fn xor(&mut self, x: Expr, y: Expr) -> Expr {
+        match (x, y) {
+            (FALSE, z) | (z, FALSE) => z,
+            (TRUE, z) | (z, TRUE) => self.not(z),
+            _ if x == y => FALSE,
+            _ => self.intern(Node::Xor(x.min(y), x.max(y))),
+        }
+    }
+
+    fn or(&mut self, x: Expr, y: Expr) -> Expr {
+        let (not_x, not_y) = (self.not(x), self.not(y));
+        let neither = self.and(not_x, not_y);
+        self.not(neither)
+    }
+
+    /// `if select { x } else { y }`
+    fn mux(&mut self, select: Expr, x: Expr, y: Expr) -> Expr {
+        let when_set = self.and(select, x);
+        let not_select = self.not(select);
+        let when_clear = self.and(not_select, y);
+        self.or(when_set, when_clear)
+    }
+
+    fn any(&mut self, bits: &[Expr]) -> Expr {
+        bits.iter().fold(FALSE, |acc, &bit| self.or(acc, bit))
+    }
+
+    fn equals(&mut self, bits: &[Expr], value: u8) -> Expr {
+        bits.iter().enumerate().fold(TRUE, |acc, (i, &bit)| {
+            let bit = if value >> i & 1 == 1 {
+                bit
+            } else {
+                self.not(bit)
+            };
+            self.and(acc, bit)
+        })
+    }
+
+    /// Bits of A an expression depends on.
+    fn support(&self, expr: Expr) -> BTreeSet<usize> {
+        let mut bits = BTreeSet::new();
+        let mut seen = vec![false; self.nodes.len()];
+        let mut stack = vec![expr];
+        while let Some(expr) = stack.pop() {
+            if std::mem::replace(&mut seen[expr], true) {
+                continue;
+            }
+            match self.nodes[expr] {
+                Node::Const(_) => {}
+                Node::Bit(i) => {
+                    bits.insert(i);
+                }
+                Node::Not(x) => stack.push(x),
+                Node::And(x, y) | Node::Xor(x, y) => stack.extend([x, y]),
+            }
+        }
+        bits
+    }
+
+    /// Three valued evaluation, unassigned bits of A are unknown.
+    fn evaluate(&self, assignment: &[Option<bool>]) -> Vec<Option<bool>> {
+        let mut values: Vec<Option<bool>> = Vec::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            let value = match *node {
+                Node::Const(value) => Some(value),
+                Node::Bit(i) => assignment[i],
+                Node::Not(x) => values[x].map(|x| !x),
+                Node::And(x, y) => match (values[x], values[y]) {
+                    (Some(false), _) | (_, Some(false)) => Some(false),
+                    (Some(true), Some(true)) => Some(true),
+                    _ => None,
+                },
+                Node::Xor(x, y) => values[x].zip(values[y]).map(|(x, y)| x ^ y),
+            };
+            values.push(value);
+        }
+        values
+    }
+
+    /// Assigns the bits of A from the most significant one down, trying zero first.
+    /// The first complete assignment is therefore the smallest A satisfying all constraints.
+    fn search(&self, assignment: &mut [Option<bool>], bit: usize, constraints: &[Expr]) -> bool {
+        let values = self.evaluate(assignment);
+        if constraints.iter().any(|&expr| values[expr] == Some(false)) {
+            return false;
+        }
+        if bit == 0 {
+            return true;
+        }
+        for value in [false, true] {
+            assignment[bit - 1] = Some(value);
+            if self.search(assignment, bit - 1, constraints) {
+                return true;
+            }
+        }
+        assignment[bit - 1] = None;
+        false
+    }
+
+    fn smallest_a(&self, constraints: &[Expr]) -> Option<i64> {
+        let mut assignment = vec![None; WIDTH];
+        if !self.search(&mut assignment, WIDTH, constraints) {
+            return None;
+        }
+        Some(
+            assignment
+                .iter()
+                .rev()
+                .fold(0, |a, &bit| a << 1 | bit.unwrap() as i64),
+        )
+    }
+}
+
+/// 64 bit register, least significant bit first.
+type Word = Vec<Expr>;
+
+fn constant_word(value: i64) -> Word {
+    (0..64).map(|i| (value >> i & 1) as Expr).collect()
+}
+
+/// `word >> amount` as a barrel shifter, shifting by 64 or more gives zero.
+fn shift_right(circuit: &mut Circuit, word: &Word, amount: &Word) -> Word {
+    let mut result = word.clone();
+    for (stage, &select) in amount[..6].iter().enumerate() {
+        let distance = 1 << stage;
+        result = (0..64)
+            .map(|i| {
+                let shifted = result.get(i + distance).copied().unwrap_or(FALSE);
+                circuit.mux(select, shifted, result[i])
+            })
+            .collect();
+    }
+    let overflow = circuit.any(&amount[6..]);
+    let keep = circuit.not(overflow);
+    result.iter().map(|&bit| circuit.and(keep, bit)).collect()
+}
+
+/// Machine state along one execution path.
+#[derive(Clone)]
+struct State {
+    a: Word,
+    b: Word,
+    c: Word,
+    ip: usize,
+    /// Low three bits of every printed value.
+    outputs: Vec<Word>,
+    /// Conditions that hold on this path, from branches and required outputs.
+    constraints: Vec<Expr>,
+    /// Address of the last jump taken with the registers at that point.
+    last_jump: Option<(usize, [Word; 3])>,
+}
+
+impl State {
+    fn resolve(&self, combo: Combo) -> Option<Word> {
+        match combo {
+            Combo::Literal(lit) => Some(constant_word(lit as i64)),
+            Combo::RegA => Some(self.a.clone()),
+            Combo::RegB => Some(self.b.clone()),
+            Combo::RegC => Some(self.c.clone()),
+            Combo::Reserved => None,
+        }
+    }
+
+    /// Adds a constraint, returns false if it can never hold.
+    /// Loops that keep testing the same condition would otherwise grow the list every iteration.
+    fn require(&mut self, expr: Expr) -> bool {
+        match expr {
+            FALSE => false,
+            TRUE => true,
+            _ => {
+                if !self.constraints.contains(&expr) {
+                    self.constraints.push(expr);
+                }
+                true
+            }
+        }
+    }
+}
+
+/// Explores all execution paths with a symbolic, positive register A.
+/// Returns the paths that halt after printing exactly `required.len()` values matching `required`,
+/// or an error once `STEP_LIMIT` instructions were executed or `NODE_LIMIT` expression nodes were
+/// built without exploring every path.
+/// A path taking the same jump twice in a row with unchanged registers loops forever and is dropped.
+fn explore(
+    circuit: &mut Circuit,
+    machine: &Machine,
+    required: &[u8],
+) -> Result<Vec<State>, VmError> {
+    let a: Word = (0..64)
+        .map(|i| match i < WIDTH {
+            true => circuit.intern(Node::Bit(i)),
+            false => FALSE,
+        })
+        .collect();
+    // the puzzle asks for a positive A, like `Machine::find_quine`
+    let positive = circuit.any(&a);
+    let mut stack = vec![State {
+        a,
+        b: constant_word(machine.b),
+        c: constant_word(machine.c),
+        ip: 0,
+        outputs: Vec::new(),
+        constraints: vec![positive],
+        last_jump: None,
+    }];
+    let mut finished = Vec::new();
+    let mut steps = 0;
+    while let Some(mut state) = stack.pop() {
+        steps += 1;
+        if steps > STEP_LIMIT {
+            return Err(VmError::StepLimitExceeded(STEP_LIMIT));
+        }
+        if circuit.nodes.len() > NODE_LIMIT {
+            return Err(VmError::NodeLimitExceeded(NODE_LIMIT));
+        }
+        let Some(&instruction) = machine.instructions.get(state.ip) else {
+            if state.outputs.len() == required.len() {
+                finished.push(state);
+            }
+            continue;
+        };
+        state.ip += 1;
+        let shifted = |circuit: &mut Circuit, state: &State, combo| {
+            state
+                .resolve(combo)
+                .map(|amount| shift_right(circuit, &state.a, &amount))
+        };
+        match instruction {
+            Instruction::Adv(combo) => match shifted(circuit, &state, combo) {
+                Some(a) => state.a = a,
+                None => continue,
+            },
+            Instruction::Bdv(combo) => match shifted(circuit, &state, combo) {
+                Some(b) => state.b = b,
+                None => continue,
+            },
+            Instruction::Cdv(combo) => match shifted(circuit, &state, combo) {
+                Some(c) => state.c = c,
+                None => continue,
+            },
+            Instruction::Bxl(lit) => {
+                let literal = constant_word(lit as i64);
+                state.b = (0..64)
+                    .map(|i| circuit.xor(state.b[i], literal[i]))
+                    .collect();
+            }
+            Instruction::Bxc(_) => {
+                state.b = (0..64)
+                    .map(|i| circuit.xor(state.b[i], state.c[i]))
+                    .collect();
+            }
+            Instruction::Bst(combo) => match state.resolve(combo) {
+                Some(value) => {
+                    state.b = (0..64)
+                        .map(|i| if i < 3 { value[i] } else { FALSE })
+                        .collect()
+                }
+                None => continue,
+            },
+            Instruction::Out(combo) => {
+                let Some(value) = state.resolve(combo) else {
+                    continue;
+                };
+                let Some(&expected) = required.get(state.outputs.len()) else {
+                    continue;
+                };
+                let matches = circuit.equals(&value[..3], expected);
+                if !state.require(matches) {
+                    continue;
+                }
+                state.outputs.push(value[..3].to_vec());
+            }
+            Instruction::Jnz(lit) => {
+                let non_zero = circuit.any(&state.a);
+                let mut jump = state.clone();
+                jump.ip = lit as usize / 2;
+                let registers = [state.a.clone(), state.b.clone(), state.c.clone()];
+                let repeated = state.last_jump.as_ref() == Some(&(state.ip, registers.clone()));
+                jump.last_jump = Some((state.ip, registers));
+                if lit % 2 == 0 && !repeated && jump.require(non_zero) {
+                    stack.push(jump);
+                }
+                let zero = circuit.not(non_zero);
+                if !state.require(zero) {
+                    continue;
+                }
+            }
+        }
+        stack.push(state);
+    }
+    Ok(finished)
+}
+
+/// Smallest register A for which the program prints `required` and halts, for any program shape.
+/// The search is exponential in the worst case, but fast for programs whose outputs only
+/// depend on a few neighbouring bits of A.
+pub(crate) fn solve(machine: &Machine, required: &[u8]) -> Result<Option<i64>, VmError> {
+    let mut circuit = Circuit::new();
+    let paths = explore(&mut circuit, machine, required)?;
+    Ok(paths
+        .iter()
+        .filter_map(|path| circuit.smallest_a(&path.constraints))
+        .min())
+}
+
+/// Compact list of bit indices, e.g. `0-9, 12`.
+fn bit_ranges(bits: &BTreeSet<usize>) -> String {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for &bit in bits {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == bit => *end = bit,
+            _ => ranges.push((bit, bit)),
+        }
+    }
+    let ranges: Vec<String> = ranges
+        .iter()
+        .map(|&(start, end)| match start == end {
+            true => start.to_string(),
+            false => format!("{start}-{end}"),
+        })
+        .collect();
+    ranges.join(", ")
+}
+
+/// Report for the `symbolic` subcommand: which bits of A every output depends on and the smallest
+/// A printing `required`, which defaults to the program itself.
+pub fn analyse(input: &String, required: Option<&str>) -> Result<String, ParseError> {
+    let machine = load(input)?;
+    let required: Vec<u8> = match required {
+        Some(required) => required
+            .split(',')
+            .map(|value| value.trim().parse::<u8>().ok().filter(|&value| value < 8))
+            .collect::<Option<_>>()
+            .ok_or_else(|| ParseError::new(0, "required output must be 3-bit numbers"))?,
+        None => machine.program.clone(),
+    };
+
+    let mut circuit = Circuit::new();
+    let paths = match explore(&mut circuit, &machine, &required) {
+        Ok(paths) => paths,
+        Err(err) => return Ok(format!("symbolic execution gave up: {err}\n")),
+    };
+    let mut lines = vec![format!("{} matching execution paths", paths.len())];
+    let mut best: Option<i64> = None;
+    for (i, path) in paths.iter().enumerate() {
+        lines.push(format!("path {i}:"));
+        for (j, output) in path.outputs.iter().enumerate() {
+            let support = output.iter().fold(BTreeSet::new(), |mut bits, &bit| {
+                bits.extend(circuit.support(bit));
+                bits
+            });
+            lines.push(format!(
+                "  out {j} depends on A bits {}",
+                bit_ranges(&support)
+            ));
+        }
+        match circuit.smallest_a(&path.constraints) {
+            Some(a) => {
+                lines.push(format!("  smallest A: {a}"));
+                best = Some(best.map_or(a, |best| best.min(a)));
+            }
+            None => lines.push("  no value of A satisfies this path".to_string()),
+        }
+    }
+    match best {
+        Some(a) => lines.push(format!("smallest A overall: {a}")),
+        None => lines.push("no value of register A produces the required output".to_string()),
+    }
+    Ok(lines.join("\n") + "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_the_step_limit() {
+        // B alternates, so no iteration repeats the one before and the loop is explored forever
+        let machine = load(&"1,1,3,0".to_string()).unwrap();
+        assert_eq!(
+            solve(&machine, &[]),
+            Err(VmError::StepLimitExceeded(STEP_LIMIT))
+        );
+    }
+
+    #[test]
+    fn drops_loops_that_repeat() {
+        // `out A` never changes a register, so only A == 0 leaves the loop
+        let machine = load(&"5,4,3,0".to_string()).unwrap();
+        assert_eq!(solve(&machine, &machine.program), Ok(None));
+    }
+
+    #[test]
+    fn reports_the_node_limit() {
+        // A is never shifted while `cdv C` builds an ever larger shifter for C
+        let machine = load(&"7,6,3,0".to_string()).unwrap();
+        assert_eq!(
+            solve(&machine, &machine.program),
+            Err(VmError::NodeLimitExceeded(NODE_LIMIT))
+        );
+    }
+
+    #[test]
+    fn finds_no_solution() {
+        // a single output can not match four values
+        let machine = load(&"0,1,5,4".to_string()).unwrap();
+        assert_eq!(solve(&machine, &machine.program), Ok(None));
+    }
+
+    #[test]
+    fn requires_a_positive_a() {
+        // A == 0 prints 0 as well
+        let machine = load(&"5,4".to_string()).unwrap();
+        assert_eq!(solve(&machine, &[0]), Ok(Some(8)));
+    }
+
+    #[test]
+    fn solves_the_quine_example() {
+        let machine = load(&"0,3,5,4,3,0".to_string()).unwrap();
+        assert_eq!(solve(&machine, &machine.program), Ok(Some(117440)));
+    }
+}
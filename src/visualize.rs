@@ -0,0 +1,223 @@
+use crate::prelude::{read_day, Aoc2024, Frames};
+use std::io::{stdin, stdout, BufRead, Write};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+/// Shortest delay between frames, halving it further would end up at zero and stay there.
+const MIN_DELAY: Duration = Duration::from_millis(1);
+/// Longest delay between frames, doubling it without bound would overflow.
+const MAX_DELAY: Duration = Duration::from_secs(60);
+
+enum Command {
+    Step,
+    Pause,
+    Faster,
+    Slower,
+    Quit,
+}
+
+impl Command {
+    fn parse(line: &str) -> Option<Self> {
+        match line {
+            "" | "n" => Some(Command::Step),
+            "p" | " " => Some(Command::Pause),
+            "+" => Some(Command::Faster),
+            "-" => Some(Command::Slower),
+            "q" => Some(Command::Quit),
+            _ => None,
+        }
+    }
+}
+
+fn clamp_delay(delay: Duration) -> Duration {
+    delay.clamp(MIN_DELAY, MAX_DELAY)
+}
+
+/// Screen contents for one frame, drawn over the previous one.
+fn screen(frame: &str, step: usize, delay: Duration, paused: bool) -> String {
+    format!(
+        "\x1b[H{}\nstep {} | delay {:?} | {}\n\
+         [enter] single step, p pause/resume, + faster, - slower, q quit\x1b[J",
+        frame,
+        step,
+        delay,
+        if paused { "paused" } else { "running" }
+    )
+}
+
+/// Reads player commands line by line, so no raw terminal mode is needed.
+fn spawn_commands() -> Receiver<Command> {
+    let (tx, rx) = channel();
+    thread::spawn(move || {
+        for line in stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            if let Some(command) = Command::parse(line.trim()) {
+                if tx.send(command).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+    rx
+}
+
+/// Replays the frames in place using ANSI cursor control.
+fn play(frames: Frames, mut delay: Duration, mut paused: bool) {
+    let commands = spawn_commands();
+    let mut out = stdout();
+    let mut input_closed = false;
+    print!("\x1b[2J");
+    for (step, frame) in frames.enumerate() {
+        loop {
+            print!("{}", screen(&frame, step, delay, paused));
+            out.flush().unwrap();
+
+            let command = if input_closed {
+                thread::sleep(delay);
+                None
+            } else if paused {
+                commands.recv().ok()
+            } else {
+                match commands.recv_timeout(delay) {
+                    Ok(command) => Some(command),
+                    Err(RecvTimeoutError::Timeout) => None,
+                    Err(RecvTimeoutError::Disconnected) => {
+                        input_closed = true;
+                        None
+                    }
+                }
+            };
+            match command {
+                None => {
+                    // without stdin there is nobody to resume a pause
+                    if paused {
+                        input_closed = true;
+                        paused = false;
+                    }
+                    break;
+                }
+                Some(Command::Step) => {
+                    paused = true;
+                    break;
+                }
+                Some(Command::Pause) => {
+                    paused = !paused;
+                    if !paused {
+                        break;
+                    }
+                }
+                Some(Command::Faster) => delay = clamp_delay(delay / 2),
+                Some(Command::Slower) => delay = clamp_delay(delay.saturating_mul(2)),
+                Some(Command::Quit) => {
+                    println!();
+                    return;
+                }
+            }
+        }
+    }
+    println!();
+}
+
+/// Entry point for `--visualize <day> [a|b] [--delay <ms>] [--paused]`.
+pub fn run(puzzles: &[Box<dyn Aoc2024>], args: &[String]) {
+    let day: usize = args
+        .first()
+        .and_then(|day| day.parse().ok())
+        .filter(|day| (1..=puzzles.len()).contains(day))
+        .expect("usage: --visualize <day> [a|b] [--delay <ms>] [--paused]");
+    let mut part_b = false;
+    let mut delay = Duration::from_millis(50);
+    let mut paused = false;
+    let mut options = args[1..].iter();
+    while let Some(option) = options.next() {
+        match option.as_str() {
+            "a" => part_b = false,
+            "b" => part_b = true,
+            "--paused" => paused = true,
+            "--delay" => {
+                let ms = options
+                    .next()
+                    .and_then(|ms| ms.parse().ok())
+                    .expect("--delay needs a number of milliseconds");
+                delay = clamp_delay(Duration::from_millis(ms));
+            }
+            _ => panic!("unknown option {option}"),
+        }
+    }
+
+    let puzzle = &puzzles[day - 1];
+    let content = read_day(day as u8);
+    match puzzle.frames(&content, part_b) {
+        Some(frames) => play(frames, delay, paused),
+        None => println!(
+            "{} has no step-based simulation of part {} to visualize",
+            puzzle.name(),
+            if part_b { "b" } else { "a" }
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle1::Puzzle1;
+    use crate::puzzle14::Puzzle14;
+    use crate::puzzle15::Puzzle15;
+    use crate::puzzle6::Puzzle6;
+
+    #[test]
+    fn clamps_the_delay() {
+        assert_eq!(clamp_delay(Duration::ZERO), MIN_DELAY);
+        assert_eq!(clamp_delay(MIN_DELAY / 2), MIN_DELAY);
+        assert_eq!(
+            clamp_delay(Duration::from_millis(50)),
+            Duration::from_millis(50)
+        );
+        assert_eq!(clamp_delay(Duration::MAX.saturating_mul(2)), MAX_DELAY);
+    }
+
+    #[test]
+    fn draws_the_status_line() {
+        let screen = screen("#.\n.#", 3, Duration::from_millis(50), true);
+        assert!(
+            screen.starts_with("\x1b[H#.\n.#\nstep 3 | delay 50ms | paused\n"),
+            "{screen:?}"
+        );
+        assert!(screen.ends_with("q quit\x1b[J"), "{screen:?}");
+    }
+
+    #[test]
+    fn replays_the_warehouse() {
+        let input = "\
+########\n#..O.O.#\n##@.O..#\n#...O..#\n#.#.O..#\n#...O..#\n#......#\n########\n\n\
+<^^>>>vv<v>>v<<\n"
+            .to_string();
+        let frames: Vec<String> = Puzzle15 {}.frames(&input, false).unwrap().collect();
+        // the initial board and one frame per move
+        assert_eq!(frames.len(), 16);
+        assert!(frames[0].contains("##@.O..#"), "{}", frames[0]);
+        assert!(frames[15].contains("#.#O@..#"), "{}", frames[15]);
+        let frames = Puzzle15 {}.frames(&input, true).unwrap();
+        assert!(frames
+            .take(1)
+            .all(|frame| frame.contains("####@...[]....##")));
+    }
+
+    #[test]
+    fn replays_other_puzzles() {
+        let guard = "....#.....\n.........#\n..........\n..#.......\n.......#..\n\
+                     ..........\n.#..^.....\n........#.\n#.........\n......#...\n"
+            .to_string();
+        let frames: Vec<String> = Puzzle6 {}.frames(&guard, false).unwrap().collect();
+        assert!(frames.len() >= 41, "{}", frames.len());
+        assert!(Puzzle6 {}.frames(&guard, true).is_none());
+
+        let robots = "p=0,4 v=3,-3\np=6,3 v=-1,-3\n".to_string();
+        assert_eq!(Puzzle14 {}.frames(&robots, false).unwrap().count(), 101);
+
+        assert!(Puzzle1 {}.frames(&"3   4\n".to_string(), false).is_none());
+        // broken input gives no frames instead of a panic
+        assert!(Puzzle15 {}.frames(&"#@\n".to_string(), false).is_none());
+    }
+}